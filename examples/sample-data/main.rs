@@ -6,7 +6,7 @@ use ubdb::core::{
 const STORAGE_PATH: &str = "db";
 
 fn main() {
-    let executer = Executer::new(STORAGE_PATH.to_string());
+    let executer = Executer::new(STORAGE_PATH.to_string()).unwrap();
 
     let user_table = Table::new(
         String::from("user"),
@@ -113,6 +113,6 @@ fn main() {
             ]),
         ],
     );
-    executer.storage.flush(&user_table);
-    executer.storage.flush(&todo_table);
+    executer.storage.flush(&user_table).unwrap();
+    executer.storage.flush(&todo_table).unwrap();
 }
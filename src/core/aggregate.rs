@@ -0,0 +1,414 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    error::{Error, Result},
+    query::ast::{AggregateFunction, Expr},
+};
+
+use super::{
+    decimal::Decimal,
+    expr,
+    table::{self, DataType, Record, Table},
+};
+
+/// digits after the point of the average of integers
+const AVG_SCALE: u8 = 6;
+
+/// group `rows` by `group_by` and compute `aggregates` for each group
+///
+/// produces a new table with one column per group by key followed by one column per aggregate,
+/// named after the expressions (see `rewrite` for how expressions refer to them afterwards)
+pub fn aggregate(
+    table: &Table,
+    rows: &[Record],
+    group_by: &[Expr],
+    aggregates: &[Expr],
+) -> Result<Table> {
+    // groups in order of first appearance
+    let mut groups: Vec<(Vec<table::Value>, Vec<&Record>)> = Vec::new();
+    let mut group_indexes: BTreeMap<Vec<table::Value>, usize> = BTreeMap::new();
+    for row in rows.iter() {
+        let key = group_by
+            .iter()
+            .map(|expr| expr::eval_value(table, row, expr))
+            .collect::<Result<Vec<_>>>()?;
+        match group_indexes.get(&key) {
+            Some(idx) => groups[*idx].1.push(row),
+            None => {
+                group_indexes.insert(key.clone(), groups.len());
+                groups.push((key, vec![row]));
+            }
+        }
+    }
+    // without group by, the whole input is a single group even when empty
+    if group_by.is_empty() && groups.is_empty() {
+        groups.push((vec![], vec![]));
+    }
+
+    let mut columns = Vec::new();
+    for expr in group_by.iter() {
+        columns.push((expr.to_string(), expr::data_type_of(table, expr)?));
+    }
+    for expr in aggregates.iter() {
+        columns.push((expr.to_string(), expr::data_type_of(table, expr)?));
+    }
+
+    let mut records = Vec::new();
+    for (key, group) in groups {
+        let mut values = key;
+        for expr in aggregates.iter() {
+            let Expr::Aggregate(function, arg) = expr else {
+                unreachable!("aggregates should only contain aggregate expressions");
+            };
+            values.push(compute(table, &group, *function, arg.as_deref())?);
+        }
+        records.push(Record::new(values));
+    }
+
+    Ok(Table::new(table.name.clone(), columns, records))
+}
+
+/// the type of `function` over values of `arg_type` (None for COUNT(*))
+///
+/// integers are summed as BIGINT and averaged as DECIMAL, so that neither overflows nor
+/// truncates where the values themselves fit
+pub fn data_type_of(function: AggregateFunction, arg_type: Option<DataType>) -> Result<DataType> {
+    match (function, arg_type) {
+        (AggregateFunction::Count, _) => Ok(DataType::BigInt),
+        (AggregateFunction::Min | AggregateFunction::Max, Some(data_type)) => Ok(data_type),
+        (AggregateFunction::Sum, Some(DataType::Int | DataType::BigInt)) => Ok(DataType::BigInt),
+        (AggregateFunction::Avg, Some(DataType::Int | DataType::BigInt)) => {
+            Ok(DataType::Decimal(Decimal::MAX_PRECISION, AVG_SCALE))
+        }
+        (_, Some(DataType::Decimal(_, scale))) => {
+            Ok(DataType::Decimal(Decimal::MAX_PRECISION, scale))
+        }
+        (_, Some(DataType::Double)) => Ok(DataType::Double),
+        _ => Err(Error::Type(format!(
+            "{}() requires numeric values",
+            function
+        ))),
+    }
+}
+
+/// collect the distinct aggregate expressions in `expr`
+pub fn collect_aggregates(expr: &Expr, aggregates: &mut Vec<Expr>) {
+    match expr {
+        Expr::Aggregate(..) => {
+            if !aggregates.contains(expr) {
+                aggregates.push(expr.clone());
+            }
+        }
+        Expr::Binary(left, _, right) => {
+            collect_aggregates(left, aggregates);
+            collect_aggregates(right, aggregates);
+        }
+        Expr::Not(expr) | Expr::Neg(expr) | Expr::IsNull(expr, _) => {
+            collect_aggregates(expr, aggregates)
+        }
+        Expr::Function(_, args) => {
+            for arg in args.iter() {
+                collect_aggregates(arg, aggregates);
+            }
+        }
+        Expr::Column(_) | Expr::Value(_) => {}
+    }
+}
+
+/// rewrite `expr` to refer to the columns of the aggregated table
+pub fn rewrite(expr: &Expr, group_by: &[Expr]) -> Result<Expr> {
+    if group_by.contains(expr) {
+        return Ok(Expr::Column(expr.to_string()));
+    }
+    match expr {
+        Expr::Aggregate(..) => Ok(Expr::Column(expr.to_string())),
+        Expr::Column(name) => Err(Error::Invalid(format!(
+            "column {} must appear in the GROUP BY clause or be used in an aggregate function",
+            name
+        ))),
+        Expr::Value(_) => Ok(expr.clone()),
+        Expr::Binary(left, operator, right) => Ok(Expr::Binary(
+            Box::new(rewrite(left, group_by)?),
+            *operator,
+            Box::new(rewrite(right, group_by)?),
+        )),
+        Expr::Not(expr) => Ok(Expr::Not(Box::new(rewrite(expr, group_by)?))),
+        Expr::Neg(expr) => Ok(Expr::Neg(Box::new(rewrite(expr, group_by)?))),
+        Expr::IsNull(expr, negated) => {
+            Ok(Expr::IsNull(Box::new(rewrite(expr, group_by)?), *negated))
+        }
+        Expr::Function(function, args) => Ok(Expr::Function(
+            *function,
+            args.iter()
+                .map(|arg| rewrite(arg, group_by))
+                .collect::<Result<_>>()?,
+        )),
+    }
+}
+
+fn compute(
+    table: &Table,
+    group: &[&Record],
+    function: AggregateFunction,
+    arg: Option<&Expr>,
+) -> Result<table::Value> {
+    let Some(arg) = arg else {
+        // COUNT(*)
+        return count(group.len());
+    };
+    // NULLs are ignored by every aggregate
+    let mut values = Vec::new();
+    for row in group.iter() {
+        let value = expr::eval_value(table, row, arg)?;
+        if value != table::Value::Null {
+            values.push(value);
+        }
+    }
+    if values.is_empty() && function != AggregateFunction::Count {
+        return Ok(table::Value::Null);
+    }
+
+    match function {
+        AggregateFunction::Count => count(values.len()),
+        AggregateFunction::Sum | AggregateFunction::Avg => sum(function, &values),
+        AggregateFunction::Min => Ok(values.into_iter().min().unwrap()),
+        AggregateFunction::Max => Ok(values.into_iter().max().unwrap()),
+    }
+}
+
+fn count(len: usize) -> Result<table::Value> {
+    i64::try_from(len)
+        .map(table::Value::BigInt)
+        .map_err(|_| Error::Type(String::from("COUNT() out of range")))
+}
+
+/// SUM or AVG of non-null values of one type, of the type given by `data_type_of`
+/// (the average of decimals is rounded to their scale)
+fn sum(function: AggregateFunction, values: &[table::Value]) -> Result<table::Value> {
+    let out_of_range = || Error::Type(format!("{}() out of range", function));
+    let count = values.len();
+    match &values[0] {
+        table::Value::Int(_) | table::Value::BigInt(_) => {
+            let mut sum: i128 = 0;
+            for value in values.iter() {
+                match value {
+                    table::Value::Int(v) => sum += *v as i128,
+                    table::Value::BigInt(v) => sum += *v as i128,
+                    _ => unreachable!("values of an aggregate are of one type"),
+                }
+            }
+            if function == AggregateFunction::Avg {
+                return Decimal::new(sum, 0)
+                    .checked_div(&Decimal::from(count as i64), AVG_SCALE)
+                    .map(table::Value::Decimal)
+                    .ok_or_else(out_of_range);
+            }
+            i64::try_from(sum)
+                .map(table::Value::BigInt)
+                .map_err(|_| out_of_range())
+        }
+        table::Value::Double(_) => {
+            let mut sum = 0.0;
+            for value in values.iter() {
+                if let table::Value::Double(v) = value {
+                    sum += v;
+                }
+            }
+            if function == AggregateFunction::Avg {
+                sum /= count as f64;
+            }
+            Ok(table::Value::Double(sum))
+        }
+        table::Value::Decimal(first) => {
+            let mut sum = Decimal::new(0, first.scale());
+            for value in values.iter() {
+                if let table::Value::Decimal(v) = value {
+                    sum = sum.checked_add(v).ok_or_else(out_of_range)?;
+                }
+            }
+            if function == AggregateFunction::Avg {
+                sum = sum
+                    .checked_div(&Decimal::from(count as i64), sum.scale())
+                    .ok_or_else(out_of_range)?;
+            }
+            Ok(table::Value::Decimal(sum))
+        }
+        _ => Err(Error::Type(format!(
+            "{}() requires numeric values",
+            function
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::table::DataType;
+
+    fn todo_table() -> Table {
+        let row = |id, user_id, title: &str| {
+            Record::new(vec![
+                table::Value::Int(id),
+                table::Value::Int(user_id),
+                table::Value::VarChar(String::from(title)),
+            ])
+        };
+        Table::new(
+            String::from("todo"),
+            vec![
+                (String::from("id"), DataType::Int),
+                (String::from("user_id"), DataType::Int),
+                (String::from("title"), DataType::VarChar(10)),
+            ],
+            vec![
+                row(1, 1, "alice 1"),
+                row(2, 2, "bob 1"),
+                row(3, 1, "alice 2"),
+                row(4, 3, "carol 1"),
+                row(5, 2, "bob 2"),
+                row(6, 1, "alice 3"),
+            ],
+        )
+    }
+
+    fn aggregate_of(function: AggregateFunction, column: Option<&str>) -> Expr {
+        Expr::Aggregate(
+            function,
+            column.map(|name| Box::new(Expr::Column(String::from(name)))),
+        )
+    }
+
+    #[test]
+    fn test_aggregate_group_by() {
+        let table = todo_table();
+        let group_by = vec![Expr::Column(String::from("user_id"))];
+        let aggregates = vec![
+            aggregate_of(AggregateFunction::Count, None),
+            aggregate_of(AggregateFunction::Sum, Some("id")),
+            aggregate_of(AggregateFunction::Avg, Some("id")),
+            aggregate_of(AggregateFunction::Min, Some("title")),
+            aggregate_of(AggregateFunction::Max, Some("id")),
+        ];
+
+        let result = aggregate(&table, &table.rows, &group_by, &aggregates).unwrap();
+        assert_eq!(
+            result.columns,
+            vec![
+                (String::from("user_id"), DataType::Int),
+                (String::from("COUNT(*)"), DataType::BigInt),
+                (String::from("SUM(id)"), DataType::BigInt),
+                (String::from("AVG(id)"), DataType::Decimal(38, 6)),
+                (String::from("MIN(title)"), DataType::VarChar(10)),
+                (String::from("MAX(id)"), DataType::Int),
+            ]
+        );
+        let row = |user_id, count, sum, avg, min: &str, max| {
+            Record::new(vec![
+                table::Value::Int(user_id),
+                table::Value::BigInt(count),
+                table::Value::BigInt(sum),
+                table::Value::Decimal(Decimal::new(avg, 6)),
+                table::Value::VarChar(String::from(min)),
+                table::Value::Int(max),
+            ])
+        };
+        assert_eq!(
+            result.rows,
+            vec![
+                row(1, 3, 10, 3333333, "alice 1", 6),
+                row(2, 2, 7, 3500000, "bob 1", 5),
+                row(3, 1, 4, 4000000, "carol 1", 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_without_group_by() {
+        let table = todo_table();
+        let aggregates = vec![aggregate_of(AggregateFunction::Count, Some("id"))];
+
+        let result = aggregate(&table, &table.rows, &[], &aggregates).unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Record::new(vec![table::Value::BigInt(6)])]
+        );
+
+        // a single group even for empty input
+        let aggregates = vec![
+            aggregate_of(AggregateFunction::Count, Some("id")),
+            aggregate_of(AggregateFunction::Sum, Some("id")),
+        ];
+        let result = aggregate(&table, &[], &[], &aggregates).unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Record::new(vec![
+                table::Value::BigInt(0),
+                table::Value::Null
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_of_integers() {
+        let table = Table::new(
+            String::from("t"),
+            vec![(String::from("v"), DataType::Int)],
+            vec![
+                Record::new(vec![table::Value::Int(-5)]),
+                Record::new(vec![table::Value::Int(7)]),
+                Record::new(vec![table::Value::Int(i32::MAX)]),
+            ],
+        );
+        let aggregates = vec![
+            aggregate_of(AggregateFunction::Sum, Some("v")),
+            aggregate_of(AggregateFunction::Avg, Some("v")),
+        ];
+
+        // the sum goes beyond INT and the average is not truncated
+        let result = aggregate(&table, &table.rows, &[], &aggregates).unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Record::new(vec![
+                table::Value::BigInt(i32::MAX as i64 + 2),
+                table::Value::Decimal(Decimal::new(715827883000000, 6)),
+            ])]
+        );
+        let result = aggregate(&table, &table.rows[..2], &[], &aggregates).unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Record::new(vec![
+                table::Value::BigInt(2),
+                table::Value::Decimal(Decimal::new(1, 0)),
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_type_error() {
+        let table = todo_table();
+        let aggregates = vec![aggregate_of(AggregateFunction::Sum, Some("title"))];
+        assert!(matches!(
+            aggregate(&table, &table.rows, &[], &aggregates),
+            Err(Error::Type(_))
+        ));
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let group_by = vec![Expr::Column(String::from("user_id"))];
+        let count = aggregate_of(AggregateFunction::Count, None);
+
+        assert_eq!(
+            rewrite(&Expr::Column(String::from("user_id")), &group_by).unwrap(),
+            Expr::Column(String::from("user_id"))
+        );
+        assert_eq!(
+            rewrite(&count, &group_by).unwrap(),
+            Expr::Column(String::from("COUNT(*)"))
+        );
+        assert!(matches!(
+            rewrite(&Expr::Column(String::from("title")), &group_by),
+            Err(Error::Invalid(_))
+        ));
+    }
+}
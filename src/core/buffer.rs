@@ -1,11 +1,327 @@
-use super::table::Table;
+use std::collections::HashMap;
 
+use crate::error::{Error, Result};
+
+use super::storage::{PageId, Storage, PAGE_SIZE};
+
+/// index of a frame of the pool, valid while its page is pinned
+pub type FrameId = usize;
+
+/// counters of the pool since it was created
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BufferStats {
+    // pages found in the pool
+    pub hits: u64,
+    // pages read through storage
+    pub misses: u64,
+    // pages dropped to make room for others
+    pub evictions: u64,
+    // changed pages written back through storage
+    pub write_backs: u64,
+    // pages held now
+    pub pages: usize,
+}
+
+struct Frame {
+    table_name: String,
+    page_id: PageId,
+    bytes: Vec<u8>,
+    pins: u32,
+    dirty: bool,
+    // set on every pin, cleared as the clock hand passes
+    referenced: bool,
+}
+
+/// a cache of table file pages within a memory budget
+///
+/// a page is pinned while in use and cannot be evicted until it is unpinned;
+/// once the pool is full the CLOCK algorithm picks an unpinned page to make room,
+/// which is written back through `Storage` first if it was changed
 pub struct BufferPool {
-    pub body: Vec<Table>,
+    capacity: usize,
+    frames: Vec<Frame>,
+    frame_ids: HashMap<(String, PageId), FrameId>,
+    hand: FrameId,
+    stats: BufferStats,
 }
 
 impl BufferPool {
-    pub fn new() -> Self {
-        Self { body: Vec::new() }
+    /// 8 MiB
+    pub const DEFAULT_BUDGET: usize = 8 << 20;
+
+    /// a pool of as many pages as fit in `budget` bytes, at least one
+    pub fn new(budget: usize) -> Self {
+        Self {
+            capacity: (budget / PAGE_SIZE).max(1),
+            frames: Vec::new(),
+            frame_ids: HashMap::new(),
+            hand: 0,
+            stats: BufferStats::default(),
+        }
+    }
+
+    /// number of pages the pool can hold
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn stats(&self) -> BufferStats {
+        BufferStats {
+            pages: self.frames.len(),
+            ..self.stats
+        }
+    }
+
+    /// pin the page, reading it through storage unless it is in the pool
+    pub fn pin(&mut self, storage: &Storage, table_name: &str, page_id: PageId) -> Result<FrameId> {
+        if let Some(frame_id) = self.pin_cached(table_name, page_id) {
+            self.stats.hits += 1;
+            return Ok(frame_id);
+        }
+        self.stats.misses += 1;
+        let bytes = storage.read_page(table_name, page_id)?;
+        self.install(storage, table_name, page_id, bytes)
+    }
+
+    /// pin the page without reading it, for one about to be overwritten whole
+    /// (a page past the end of the file cannot be read)
+    pub fn pin_new(
+        &mut self,
+        storage: &Storage,
+        table_name: &str,
+        page_id: PageId,
+    ) -> Result<FrameId> {
+        if let Some(frame_id) = self.pin_cached(table_name, page_id) {
+            return Ok(frame_id);
+        }
+        self.install(storage, table_name, page_id, vec![0; PAGE_SIZE])
+    }
+
+    pub fn page(&self, frame_id: FrameId) -> &[u8] {
+        &self.frames[frame_id].bytes
+    }
+
+    /// the page, to be written back once changed
+    pub fn page_mut(&mut self, frame_id: FrameId) -> &mut [u8] {
+        let frame = &mut self.frames[frame_id];
+        frame.dirty = true;
+        &mut frame.bytes
+    }
+
+    pub fn unpin(&mut self, frame_id: FrameId) {
+        let frame = &mut self.frames[frame_id];
+        debug_assert!(frame.pins > 0, "unpinned a page that was not pinned");
+        frame.pins = frame.pins.saturating_sub(1);
+    }
+
+    /// write back the changed pages of the table together, in file order
+    pub fn flush_table(&mut self, storage: &Storage, table_name: &str) -> Result<()> {
+        let mut frame_ids = (0..self.frames.len())
+            .filter(|frame_id| {
+                let frame = &self.frames[*frame_id];
+                frame.dirty && frame.table_name == table_name
+            })
+            .collect::<Vec<_>>();
+        frame_ids.sort_by_key(|frame_id| self.frames[*frame_id].page_id);
+        self.write_back(storage, &frame_ids)
+    }
+
+    /// forget every page, changed or not, as the files were changed behind the pool
+    pub fn discard_all(&mut self) {
+        self.frames.clear();
+        self.frame_ids.clear();
+        self.hand = 0;
+    }
+
+    /// forget the pages of the table, changed or not, as its file was replaced or removed
+    pub fn discard_table(&mut self, table_name: &str) {
+        self.frames.retain(|frame| frame.table_name != table_name);
+        self.frame_ids = self
+            .frames
+            .iter()
+            .enumerate()
+            .map(|(frame_id, frame)| ((frame.table_name.clone(), frame.page_id), frame_id))
+            .collect();
+        self.hand = 0;
+    }
+
+    fn pin_cached(&mut self, table_name: &str, page_id: PageId) -> Option<FrameId> {
+        let frame_id = *self.frame_ids.get(&(table_name.to_string(), page_id))?;
+        let frame = &mut self.frames[frame_id];
+        frame.pins += 1;
+        frame.referenced = true;
+        Some(frame_id)
+    }
+
+    /// put the page in a free frame, or else in that of an evicted page, pinned
+    fn install(
+        &mut self,
+        storage: &Storage,
+        table_name: &str,
+        page_id: PageId,
+        bytes: Vec<u8>,
+    ) -> Result<FrameId> {
+        let frame = Frame {
+            table_name: table_name.to_string(),
+            page_id,
+            bytes,
+            pins: 1,
+            dirty: false,
+            referenced: true,
+        };
+        let frame_id = if self.frames.len() < self.capacity {
+            self.frames.push(frame);
+            self.frames.len() - 1
+        } else {
+            let frame_id = self.evict(storage)?;
+            self.frames[frame_id] = frame;
+            frame_id
+        };
+        self.frame_ids
+            .insert((table_name.to_string(), page_id), frame_id);
+        Ok(frame_id)
+    }
+
+    /// the frame of the first unpinned page the clock hand finds unreferenced since it last passed
+    fn evict(&mut self, storage: &Storage) -> Result<FrameId> {
+        // the first turn may only clear the reference bits
+        for _ in 0..2 * self.frames.len() {
+            let frame_id = self.hand;
+            self.hand = (self.hand + 1) % self.frames.len();
+            let frame = &mut self.frames[frame_id];
+            if frame.pins > 0 {
+                continue;
+            }
+            if frame.referenced {
+                frame.referenced = false;
+                continue;
+            }
+            self.write_back(storage, &[frame_id])?;
+            let frame = &self.frames[frame_id];
+            self.frame_ids
+                .remove(&(frame.table_name.clone(), frame.page_id));
+            self.stats.evictions += 1;
+            return Ok(frame_id);
+        }
+        Err(Error::Invalid(String::from(
+            "buffer pool is full: every page is pinned",
+        )))
+    }
+
+    /// write the changed pages of the frames, all of the same table
+    fn write_back(&mut self, storage: &Storage, frame_ids: &[FrameId]) -> Result<()> {
+        let frame_ids = frame_ids
+            .iter()
+            .copied()
+            .filter(|frame_id| self.frames[*frame_id].dirty)
+            .collect::<Vec<_>>();
+        let Some(first) = frame_ids.first() else {
+            return Ok(());
+        };
+        let pages = frame_ids
+            .iter()
+            .map(|frame_id| {
+                let frame = &self.frames[*frame_id];
+                (frame.page_id, &frame.bytes[..])
+            })
+            .collect::<Vec<_>>();
+        storage.write_pages(&self.frames[*first].table_name, &pages)?;
+        for frame_id in frame_ids {
+            self.frames[frame_id].dirty = false;
+            self.stats.write_backs += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// a storage with a table file of `pages_len` pages, each filled with its index
+    fn setup(name: &str, pages_len: u8) -> Storage {
+        let dir =
+            std::env::temp_dir().join(format!("ubdb-test-buffer-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let bytes = (0..pages_len)
+            .flat_map(|page_id| vec![page_id; PAGE_SIZE])
+            .collect::<Vec<_>>();
+        std::fs::write(dir.join("t.ubdb"), bytes).unwrap();
+        Storage::new(dir.to_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn test_pin_and_evict() {
+        let storage = setup("evict", 4);
+        let mut pool = BufferPool::new(PAGE_SIZE * 2 + 1);
+        assert_eq!(pool.capacity(), 2);
+
+        let first = pool.pin(&storage, "t", 0).unwrap();
+        assert_eq!(pool.page(first), &[0; PAGE_SIZE][..]);
+        pool.unpin(first);
+        let second = pool.pin(&storage, "t", 1).unwrap();
+        pool.unpin(second);
+        let again = pool.pin(&storage, "t", 0).unwrap();
+        assert_eq!(again, first);
+        pool.unpin(again);
+        assert_eq!(
+            pool.stats(),
+            BufferStats {
+                hits: 1,
+                misses: 2,
+                evictions: 0,
+                write_backs: 0,
+                pages: 2,
+            }
+        );
+
+        // the clock hand clears both reference bits, then evicts the first page it passed
+        let third = pool.pin(&storage, "t", 2).unwrap();
+        assert_eq!(third, first);
+        assert_eq!(pool.page(third)[0], 2);
+        assert_eq!(pool.stats().evictions, 1);
+
+        // a pinned page is never evicted
+        let fourth = pool.pin(&storage, "t", 3).unwrap();
+        assert_eq!(fourth, second);
+        assert!(matches!(pool.pin(&storage, "t", 0), Err(Error::Invalid(_))));
+        pool.unpin(third);
+        pool.unpin(fourth);
+        assert!(pool.pin(&storage, "t", 0).is_ok());
+    }
+
+    #[test]
+    fn test_write_back() {
+        let storage = setup("write-back", 3);
+        let mut pool = BufferPool::new(PAGE_SIZE);
+
+        let frame = pool.pin(&storage, "t", 1).unwrap();
+        pool.page_mut(frame)[0] = 0xff;
+        pool.unpin(frame);
+        assert_eq!(storage.read_page("t", 1).unwrap()[0], 1);
+
+        // written back when evicted
+        let frame = pool.pin(&storage, "t", 2).unwrap();
+        pool.unpin(frame);
+        assert_eq!(storage.read_page("t", 1).unwrap()[0], 0xff);
+        assert_eq!(pool.stats().write_backs, 1);
+
+        // or flushed, past the end of the file for a new page
+        let frame = pool.pin_new(&storage, "t", 3).unwrap();
+        pool.page_mut(frame)[0] = 0xee;
+        pool.unpin(frame);
+        pool.flush_table(&storage, "t").unwrap();
+        assert_eq!(storage.read_page("t", 3).unwrap()[0], 0xee);
+        assert_eq!(pool.stats().write_backs, 2);
+
+        // a discarded page is read again, its changes lost
+        let frame = pool.pin(&storage, "t", 3).unwrap();
+        pool.page_mut(frame)[0] = 0xdd;
+        pool.unpin(frame);
+        pool.discard_table("t");
+        assert_eq!(pool.stats().pages, 0);
+        let frame = pool.pin(&storage, "t", 3).unwrap();
+        assert_eq!(pool.page(frame)[0], 0xee);
+        std::fs::remove_dir_all(&storage.storage_dir).unwrap();
     }
 }
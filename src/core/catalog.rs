@@ -0,0 +1,258 @@
+use std::{cell::RefCell, collections::BTreeMap};
+
+use crate::error::{CatalogError, Result};
+
+use super::{
+    storage::{RecordId, Storage, TableScan},
+    table::{Record, Table},
+};
+
+/// the tables of the database, the only way the executer reads and writes them
+///
+/// the definitions of the tables (columns and constraints) are read from storage on first use
+/// and kept in memory, while rows are always read from storage a page at a time,
+/// its buffer pool caching the pages, and written back one record at a time;
+/// every change is written to storage before the definitions follow it, so that a read sees
+/// what a restart would see
+#[derive(Default)]
+pub struct Catalog {
+    // the tables without their rows, by name; None until read, and again after a failed write
+    schemas: RefCell<Option<BTreeMap<String, Table>>>,
+}
+
+impl Catalog {
+    /// every table without its rows, sorted by name
+    pub fn schemas(&self, storage: &Storage) -> Result<Vec<Table>> {
+        self.with_schemas(storage, |schemas| schemas.values().cloned().collect())
+    }
+
+    pub fn contains(&self, storage: &Storage, table_name: &str) -> Result<bool> {
+        self.with_schemas(storage, |schemas| schemas.contains_key(table_name))
+    }
+
+    /// the table without its rows
+    pub fn schema(&self, storage: &Storage, table_name: &str) -> Result<Table> {
+        self.with_schemas(storage, |schemas| schemas.get(table_name).cloned())?
+            .ok_or_else(|| CatalogError::UnknownTable(table_name.to_string()).into())
+    }
+
+    /// the rows of the table with where they are kept
+    pub fn scan<'a>(&self, storage: &'a Storage, table_name: &str) -> Result<TableScan<'a>> {
+        match storage.scan(&self.schema(storage, table_name)?)? {
+            Some(scan) => Ok(scan),
+            // removed behind our back
+            None => {
+                self.schemas.replace(None);
+                Err(CatalogError::UnknownTable(table_name.to_string()).into())
+            }
+        }
+    }
+
+    /// the table with its rows
+    pub fn table(&self, storage: &Storage, table_name: &str) -> Result<Table> {
+        let mut table = self.schema(storage, table_name)?;
+        for row in self.scan(storage, table_name)? {
+            table.rows.push(row?.1);
+        }
+        Ok(table)
+    }
+
+    pub fn create(&mut self, storage: &Storage, table: &Table) -> Result<()> {
+        if self.contains(storage, &table.name)? {
+            return Err(CatalogError::TableExists(table.name.clone()).into());
+        }
+        self.save(storage, table)
+    }
+
+    /// write the table with all its rows, in place of what was stored before
+    pub fn save(&mut self, storage: &Storage, table: &Table) -> Result<()> {
+        self.write(|| storage.flush(table))?;
+        self.cache_schema(table);
+        Ok(())
+    }
+
+    /// write the new definition of the table, whose columns keep their types
+    pub fn save_schema(&mut self, storage: &Storage, table: &Table) -> Result<()> {
+        self.write(|| storage.write_schema(table))?;
+        self.cache_schema(table);
+        Ok(())
+    }
+
+    /// store the new rows of the table
+    pub fn insert(&mut self, storage: &Storage, table_name: &str, rows: &[Record]) -> Result<()> {
+        let schema = self.schema(storage, table_name)?;
+        self.write(|| storage.insert(&schema, rows))?;
+        Ok(())
+    }
+
+    /// replace the rows kept at the ids, which the table may keep elsewhere afterwards
+    pub fn update(
+        &mut self,
+        storage: &Storage,
+        table_name: &str,
+        rows: &[(RecordId, Record)],
+    ) -> Result<()> {
+        let schema = self.schema(storage, table_name)?;
+        self.write(|| storage.update(&schema, rows))?;
+        Ok(())
+    }
+
+    pub fn delete(&mut self, storage: &Storage, table_name: &str, ids: &[RecordId]) -> Result<()> {
+        let schema = self.schema(storage, table_name)?;
+        self.write(|| storage.delete(&schema, ids))
+    }
+
+    /// move the table to the new name, which the caller saves it with afterwards
+    pub fn rename(&mut self, storage: &Storage, from: &str, to: &str) -> Result<()> {
+        if !self.contains(storage, from)? {
+            return Err(CatalogError::UnknownTable(from.to_string()).into());
+        }
+        if self.contains(storage, to)? {
+            return Err(CatalogError::TableExists(to.to_string()).into());
+        }
+        self.write(|| storage.rename(from, to))?;
+        if let Some(schemas) = self.schemas.get_mut() {
+            if let Some(mut schema) = schemas.remove(from) {
+                schema.name = to.to_string();
+                schemas.insert(to.to_string(), schema);
+            }
+        }
+        Ok(())
+    }
+
+    /// returns false if the table did not exist
+    pub fn drop(&mut self, storage: &Storage, table_name: &str) -> Result<bool> {
+        if !self.contains(storage, table_name)? {
+            return Ok(false);
+        }
+        self.write(|| storage.remove(table_name))?;
+        if let Some(schemas) = self.schemas.get_mut() {
+            schemas.remove(table_name);
+        }
+        Ok(true)
+    }
+
+    /// forget the definitions, to be read from storage again
+    pub fn reload(&mut self) {
+        *self.schemas.get_mut() = None;
+    }
+
+    fn with_schemas<T>(
+        &self,
+        storage: &Storage,
+        f: impl FnOnce(&BTreeMap<String, Table>) -> T,
+    ) -> Result<T> {
+        if self.schemas.borrow().is_none() {
+            let mut schemas = BTreeMap::new();
+            for name in storage.list()? {
+                if let Some(schema) = storage.load_schema(&name)? {
+                    schemas.insert(name, schema);
+                }
+            }
+            self.schemas.replace(Some(schemas));
+        }
+        let schemas = self.schemas.borrow();
+        Ok(f(schemas.as_ref().unwrap()))
+    }
+
+    /// a failed write may have left storage in any state, so the definitions are read again
+    fn write<T>(&mut self, write: impl FnOnce() -> Result<T>) -> Result<T> {
+        let result = write();
+        if result.is_err() {
+            self.reload();
+        }
+        result
+    }
+
+    /// keep the definition of the table written last
+    fn cache_schema(&mut self, table: &Table) {
+        if let Some(schemas) = self.schemas.get_mut() {
+            let schema = Table::new(table.name.clone(), table.columns.clone(), vec![])
+                .with_constraints(table.constraints.clone());
+            schemas.insert(table.name.clone(), schema);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::table::{DataType, Record, Value},
+        error::Error,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_catalog() {
+        let dir = std::env::temp_dir().join(format!("ubdb-test-catalog-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let storage = Storage::new(dir.to_str().unwrap().to_string());
+        let mut catalog = Catalog::default();
+        assert!(catalog.schemas(&storage).unwrap().is_empty());
+
+        let mut table = Table::new(
+            String::from("user"),
+            vec![(String::from("id"), DataType::Int)],
+            vec![Record::new(vec![Value::Int(1)])],
+        );
+        catalog.create(&storage, &table).unwrap();
+        assert!(matches!(
+            catalog.create(&storage, &table),
+            Err(Error::Catalog(CatalogError::TableExists(_)))
+        ));
+        assert_eq!(catalog.table(&storage, "user").unwrap(), table);
+        assert!(catalog.schemas(&storage).unwrap()[0].rows.is_empty());
+
+        table.columns.push((String::from("name"), DataType::Text));
+        table.rows = vec![Record::new(vec![Value::Int(1), Value::Null])];
+        catalog.save(&storage, &table).unwrap();
+        assert_eq!(catalog.schemas(&storage).unwrap()[0].columns.len(), 2);
+
+        // rows are written one at a time, where they are kept
+        let rows = vec![
+            Record::new(vec![Value::Int(2), Value::VarChar(String::from("bob"))]),
+            Record::new(vec![Value::Int(3), Value::Null]),
+        ];
+        catalog.insert(&storage, "user", &rows).unwrap();
+        let ids = catalog
+            .scan(&storage, "user")
+            .unwrap()
+            .map(|row| row.unwrap().0)
+            .collect::<Vec<_>>();
+        let updated = Record::new(vec![Value::Int(1), Value::VarChar(String::from("alice"))]);
+        catalog
+            .update(&storage, "user", &[(ids[0], updated.clone())])
+            .unwrap();
+        catalog.delete(&storage, "user", &ids[1..]).unwrap();
+        table.rows = vec![updated];
+        assert_eq!(catalog.table(&storage, "user").unwrap(), table);
+
+        catalog.rename(&storage, "user", "member").unwrap();
+        table.name = String::from("member");
+        catalog.save_schema(&storage, &table).unwrap();
+        assert_eq!(catalog.schemas(&storage).unwrap()[0].name, "member");
+        assert!(matches!(
+            catalog.table(&storage, "user"),
+            Err(Error::Catalog(CatalogError::UnknownTable(_)))
+        ));
+
+        // a new catalog reads the same tables back
+        let mut restarted = Catalog::default();
+        assert_eq!(restarted.table(&storage, "member").unwrap(), table);
+        assert_eq!(
+            restarted.schemas(&storage).unwrap(),
+            catalog.schemas(&storage).unwrap()
+        );
+
+        assert!(restarted.drop(&storage, "member").unwrap());
+        assert!(!restarted.drop(&storage, "member").unwrap());
+        // the other catalog finds the file gone
+        assert!(matches!(
+            catalog.table(&storage, "member"),
+            Err(Error::Catalog(CatalogError::UnknownTable(_)))
+        ));
+        assert!(catalog.schemas(&storage).unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
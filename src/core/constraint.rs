@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+
+use crate::error::{Error, Result};
+
+use super::{
+    expr,
+    table::{Constraint, Record, Table, Value},
+};
+
+/// check `rows`, new or updated rows of `table`, against the constraints of `table`
+/// that each row must meet on its own
+pub fn validate(table: &Table, rows: &[Record]) -> Result<()> {
+    for constraint in table.constraints.iter() {
+        match constraint {
+            Constraint::PrimaryKey(names) => {
+                let indexes = column_indexes(table, names)?;
+                for row in rows.iter() {
+                    if let Some(pos) = indexes
+                        .iter()
+                        .position(|idx| row.values[*idx] == Value::Null)
+                    {
+                        return Err(not_null_violation(table, &names[pos]));
+                    }
+                }
+            }
+            // checked against the other rows by validate_keys
+            Constraint::Unique(_) => {}
+            Constraint::NotNull(name) => {
+                let idx = table.resolve_column(name)?;
+                if rows.iter().any(|row| row.values[idx] == Value::Null) {
+                    return Err(not_null_violation(table, name));
+                }
+            }
+            Constraint::Default(..) => {}
+            // checked against the referenced table by foreign_key::check_references
+            Constraint::ForeignKey(_) => {}
+            Constraint::Check(cond) => {
+                for row in rows.iter() {
+                    // only FALSE violates a check, UNKNOWN passes
+                    if expr::eval_logic(table, row, cond)? == Some(false) {
+                        return Err(Error::Constraint(format!(
+                            "new row for {} violates CHECK ({})",
+                            table.name, cond
+                        )));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// check that no row of `stored`, the rows of `table` once `rows` are written,
+/// has the primary or unique key of one of `rows`, other than itself
+///
+/// only the keys of `rows` are kept while `stored` is read, and it is not read at all
+/// when `table` has no such key; a key with a NULL in it is unique
+pub fn validate_keys(
+    table: &Table,
+    rows: &[Record],
+    stored: impl Iterator<Item = Result<Record>>,
+) -> Result<()> {
+    // the columns of each key, and the number of stored rows having each key of `rows`
+    let mut keys = Vec::new();
+    for constraint in table.constraints.iter() {
+        let (Constraint::PrimaryKey(names) | Constraint::Unique(names)) = constraint else {
+            continue;
+        };
+        let indexes = column_indexes(table, names)?;
+        let counts = rows
+            .iter()
+            .filter_map(|row| key(row, &indexes))
+            .map(|key| (key, 0))
+            .collect::<BTreeMap<_, usize>>();
+        keys.push((constraint, names, indexes, counts));
+    }
+    if keys.iter().all(|(.., counts)| counts.is_empty()) {
+        return Ok(());
+    }
+
+    for row in stored {
+        let row = row?;
+        for (_, _, indexes, counts) in keys.iter_mut() {
+            if let Some(count) = key(&row, indexes).and_then(|key| counts.get_mut(&key)) {
+                *count += 1;
+            }
+        }
+    }
+
+    for (constraint, names, indexes, counts) in keys.iter() {
+        let duplicate = rows
+            .iter()
+            .filter_map(|row| key(row, indexes))
+            .find(|key| counts[key] > 1);
+        if let Some(key) = duplicate {
+            return Err(Error::Constraint(format!(
+                "duplicate key ({})=({}) violates {} constraint of {}",
+                names.join(", "),
+                format_values(&key),
+                match constraint {
+                    Constraint::PrimaryKey(_) => "PRIMARY KEY",
+                    _ => "UNIQUE",
+                },
+                table.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub fn column_indexes(table: &Table, names: &[String]) -> Result<Vec<usize>> {
+    let mut indexes = Vec::new();
+    for name in names.iter() {
+        indexes.push(table.resolve_column(name)?);
+    }
+    Ok(indexes)
+}
+
+/// the values of the key columns, or None if any of them is NULL
+pub fn key(row: &Record, indexes: &[usize]) -> Option<Vec<Value>> {
+    let key = indexes
+        .iter()
+        .map(|idx| row.values[*idx].clone())
+        .collect::<Vec<_>>();
+    if key.contains(&Value::Null) {
+        None
+    } else {
+        Some(key)
+    }
+}
+
+fn not_null_violation(table: &Table, column_name: &str) -> Error {
+    Error::Constraint(format!(
+        "null value in column {} of {} violates NOT NULL constraint",
+        column_name, table.name
+    ))
+}
+
+pub fn format_values(values: &[Value]) -> String {
+    values
+        .iter()
+        .map(format_value)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::from("NULL"),
+        Value::Boolean(true) => String::from("TRUE"),
+        Value::Boolean(false) => String::from("FALSE"),
+        Value::Int(v) => v.to_string(),
+        Value::BigInt(v) => v.to_string(),
+        Value::Double(v) => v.to_string(),
+        Value::Decimal(v) => v.to_string(),
+        Value::VarChar(v) => format!("'{}'", v),
+        Value::Date(v) => format!("DATE '{}'", v),
+        Value::Time(v) => format!("TIME '{}'", v),
+        Value::Timestamp(v) => format!("TIMESTAMP '{}'", v),
+        Value::Interval(v) => format!("INTERVAL '{}'", v),
+        Value::Json(v) => format!("JSON '{}'", v),
+        Value::Blob(v) => format!(
+            "X'{}'",
+            v.iter().map(|b| format!("{:02X}", b)).collect::<String>()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::table::DataType,
+        query::ast::{self, BinaryOperator, Expr},
+    };
+
+    use super::*;
+
+    fn user_table(constraints: Vec<Constraint>) -> Table {
+        Table::new(
+            String::from("user"),
+            vec![
+                (String::from("id"), DataType::Int),
+                (String::from("name"), DataType::VarChar(10)),
+            ],
+            vec![],
+        )
+        .with_constraints(constraints)
+    }
+
+    fn row(id: Value, name: Value) -> Record {
+        Record::new(vec![id, name])
+    }
+
+    fn name(name: &str) -> Value {
+        Value::VarChar(String::from(name))
+    }
+
+    #[test]
+    fn test_validate_keys() {
+        let table = user_table(vec![
+            Constraint::PrimaryKey(vec![String::from("id")]),
+            Constraint::Unique(vec![String::from("name")]),
+        ]);
+
+        let validate_keys = |rows: &[Record], stored: &[Record]| {
+            super::validate_keys(&table, rows, stored.iter().cloned().map(Ok))
+        };
+        let stored = [
+            row(Value::Int(1), name("alice")),
+            row(Value::Int(2), Value::Null),
+            row(Value::Int(3), Value::Null),
+        ];
+        assert!(validate_keys(&stored, &stored).is_ok());
+        assert!(validate_keys(&stored[2..], &stored).is_ok());
+
+        let stored = [
+            row(Value::Int(1), name("alice")),
+            row(Value::Int(2), name("carol")),
+            row(Value::Int(1), name("bob")),
+        ];
+        // the rows not written are only read for the keys of those written
+        assert!(validate_keys(&stored[1..2], &stored).is_ok());
+        let err = validate_keys(&stored[2..], &stored).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "duplicate key (id)=(1) violates PRIMARY KEY constraint of user"
+        );
+
+        let stored = [row(Value::Int(1), name("a")), row(Value::Int(2), name("a"))];
+        let err = validate_keys(&stored[..1], &stored).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "duplicate key (name)=('a') violates UNIQUE constraint of user"
+        );
+
+        assert!(matches!(
+            validate(&table, &[row(Value::Null, name("alice"))]),
+            Err(Error::Constraint(_))
+        ));
+
+        // nothing is read without keys to look for
+        let table = user_table(vec![]);
+        let stored = std::iter::once(Err(Error::Corruption(String::new())));
+        assert!(super::validate_keys(&table, &[row(Value::Int(1), name("a"))], stored).is_ok());
+    }
+
+    #[test]
+    fn test_validate_not_null_and_check() {
+        let table = user_table(vec![
+            Constraint::NotNull(String::from("id")),
+            Constraint::Check(Expr::Binary(
+                Box::new(Expr::Column(String::from("id"))),
+                BinaryOperator::Gt,
+                Box::new(Expr::Value(ast::Value::Int(0))),
+            )),
+        ]);
+
+        assert!(validate(&table, &[row(Value::Int(1), Value::Null)]).is_ok());
+        assert_eq!(
+            validate(&table, &[row(Value::Null, Value::Null)])
+                .unwrap_err()
+                .to_string(),
+            "null value in column id of user violates NOT NULL constraint"
+        );
+        assert_eq!(
+            validate(&table, &[row(Value::Int(0), Value::Null)])
+                .unwrap_err()
+                .to_string(),
+            "new row for user violates CHECK (id > 0)"
+        );
+
+        // UNKNOWN passes a check
+        let table = user_table(vec![Constraint::Check(Expr::Binary(
+            Box::new(Expr::Column(String::from("name"))),
+            BinaryOperator::Eq,
+            Box::new(Expr::Value(ast::Value::VarChar(String::from("alice")))),
+        ))]);
+        assert!(validate(&table, &[row(Value::Int(1), Value::Null)]).is_ok());
+    }
+}
@@ -0,0 +1,603 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub const MICROS_PER_SECOND: i64 = 1_000_000;
+pub const MICROS_PER_MINUTE: i64 = 60 * MICROS_PER_SECOND;
+pub const MICROS_PER_HOUR: i64 = 60 * MICROS_PER_MINUTE;
+pub const MICROS_PER_DAY: i64 = 24 * MICROS_PER_HOUR;
+
+/// a calendar date between 0001-01-01 and 9999-12-31, stored as days since 1970-01-01
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date(i32);
+
+impl Date {
+    pub fn from_ymd(year: i64, month: i64, day: i64) -> Option<Self> {
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        Self::from_days(days_from_civil(year, month, day))
+    }
+
+    pub fn from_days(days: i64) -> Option<Self> {
+        let min = days_from_civil(1, 1, 1);
+        let max = days_from_civil(9999, 12, 31);
+        if !(min..=max).contains(&days) {
+            return None;
+        }
+        Some(Self(days as i32))
+    }
+
+    /// days since 1970-01-01
+    pub fn days(&self) -> i64 {
+        self.0 as i64
+    }
+
+    /// (year, month, day)
+    pub fn ymd(&self) -> (i64, i64, i64) {
+        civil_from_days(self.days())
+    }
+
+    /// parse `YYYY-MM-DD`
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.trim().splitn(3, '-');
+        let year = parse_digits(parts.next()?, 4)?;
+        let month = parse_digits(parts.next()?, 2)?;
+        let day = parse_digits(parts.next()?, 2)?;
+        Self::from_ymd(year, month, day)
+    }
+
+    pub fn checked_add_days(&self, days: i64) -> Option<Self> {
+        Self::from_days(self.days().checked_add(days)?)
+    }
+
+    /// the day of the month is clamped to the length of the resulting month: 01-31 + 1 month is
+    /// 02-28 (or 02-29)
+    pub fn checked_add_months(&self, months: i64) -> Option<Self> {
+        let (year, month, day) = self.ymd();
+        let months = (year * 12 + month - 1).checked_add(months)?;
+        let (year, month) = (months.div_euclid(12), months.rem_euclid(12) + 1);
+        if !(1..=9999).contains(&year) {
+            return None;
+        }
+        Self::from_ymd(year, month, day.min(days_in_month(year, month)))
+    }
+
+    /// 0 for Sunday through 6 for Saturday
+    pub fn weekday(&self) -> i64 {
+        // 1970-01-01 was a Thursday
+        (self.days() + 4).rem_euclid(7)
+    }
+
+    /// the day of the year, starting at 1
+    pub fn ordinal(&self) -> i64 {
+        let (year, _, _) = self.ymd();
+        self.days() - days_from_civil(year, 1, 1) + 1
+    }
+
+    /// the ISO 8601 week number, weeks start on Monday and week 1 contains the first Thursday
+    pub fn iso_week(&self) -> i64 {
+        let monday = self.days() - (self.weekday() + 6) % 7;
+        let thursday = monday + 3;
+        let (year, _, _) = civil_from_days(thursday);
+        (thursday - days_from_civil(year, 1, 1)) / 7 + 1
+    }
+
+    /// the Monday of the week this date is in
+    pub fn week_start(&self) -> Option<Self> {
+        self.checked_add_days(-((self.weekday() + 6) % 7))
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (year, month, day) = self.ymd();
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+/// a time of day, stored as microseconds since midnight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Time(i64);
+
+impl Time {
+    pub const MIDNIGHT: Time = Time(0);
+
+    pub fn from_micros(micros: i64) -> Option<Self> {
+        if !(0..MICROS_PER_DAY).contains(&micros) {
+            return None;
+        }
+        Some(Self(micros))
+    }
+
+    /// microseconds since midnight
+    pub fn micros(&self) -> i64 {
+        self.0
+    }
+
+    /// parse `HH:MM[:SS[.ffffff]]`
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::from_micros(parse_clock(s.trim())?)
+    }
+
+    /// wraps around midnight
+    pub fn add_micros(&self, micros: i64) -> Self {
+        Self((self.0 + micros % MICROS_PER_DAY).rem_euclid(MICROS_PER_DAY))
+    }
+}
+
+impl Display for Time {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Clock(self.0 as u64))
+    }
+}
+
+/// a date and time without a time zone, stored as microseconds since 1970-01-01 00:00:00
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    pub fn new(date: Date, time: Time) -> Self {
+        Self(date.days() * MICROS_PER_DAY + time.micros())
+    }
+
+    pub fn from_micros(micros: i64) -> Option<Self> {
+        let date = Date::from_days(micros.div_euclid(MICROS_PER_DAY))?;
+        let time = Time::from_micros(micros.rem_euclid(MICROS_PER_DAY))?;
+        Some(Self::new(date, time))
+    }
+
+    /// the current time in UTC
+    pub fn now() -> Self {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self(since_epoch.as_micros() as i64)
+    }
+
+    /// microseconds since 1970-01-01 00:00:00
+    pub fn micros(&self) -> i64 {
+        self.0
+    }
+
+    pub fn date(&self) -> Date {
+        Date(self.0.div_euclid(MICROS_PER_DAY) as i32)
+    }
+
+    pub fn time(&self) -> Time {
+        Time(self.0.rem_euclid(MICROS_PER_DAY))
+    }
+
+    /// parse `YYYY-MM-DD[( |T)HH:MM[:SS[.ffffff]]]`
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        match s.split_once([' ', 'T']) {
+            Some((date, time)) => Some(Self::new(Date::parse(date)?, Time::parse(time)?)),
+            None => Some(Self::from(Date::parse(s)?)),
+        }
+    }
+
+    /// months first, then days, then the time part, like calendar arithmetic by hand
+    pub fn checked_add_interval(&self, interval: &Interval) -> Option<Self> {
+        let date = self
+            .date()
+            .checked_add_months(interval.months as i64)?
+            .checked_add_days(interval.days as i64)?;
+        Self::from_micros(
+            Self::new(date, self.time())
+                .0
+                .checked_add(interval.micros)?,
+        )
+    }
+
+    pub fn checked_sub_interval(&self, interval: &Interval) -> Option<Self> {
+        self.checked_add_interval(&interval.checked_neg()?)
+    }
+
+    /// the difference in days and time, never in months
+    pub fn interval_since(&self, other: &Self) -> Interval {
+        let micros = self.0 - other.0;
+        Interval::new(0, (micros / MICROS_PER_DAY) as i32, micros % MICROS_PER_DAY)
+    }
+}
+
+impl From<Date> for Timestamp {
+    /// midnight of the date
+    fn from(date: Date) -> Self {
+        Self::new(date, Time::MIDNIGHT)
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.date(), self.time())
+    }
+}
+
+/// a duration in months, days and microseconds, kept apart because
+/// months and days vary in length
+#[derive(Debug, Clone, Copy)]
+pub struct Interval {
+    months: i32,
+    days: i32,
+    micros: i64,
+}
+
+impl Interval {
+    pub fn new(months: i32, days: i32, micros: i64) -> Self {
+        Self {
+            months,
+            days,
+            micros,
+        }
+    }
+
+    pub fn months(&self) -> i32 {
+        self.months
+    }
+
+    pub fn days(&self) -> i32 {
+        self.days
+    }
+
+    pub fn micros(&self) -> i64 {
+        self.micros
+    }
+
+    /// parse quantities like `1 year 2 months -3 days 04:05:06.5`
+    ///
+    /// units are microsecond, millisecond, second, minute, hour, day, week, month (or mon) and
+    /// year, singular or plural
+    pub fn parse(s: &str) -> Option<Self> {
+        let (mut months, mut days, mut micros) = (0i64, 0i64, 0i64);
+        let mut words = s.split_whitespace().peekable();
+        words.peek()?;
+        while let Some(word) = words.next() {
+            if word.contains(':') {
+                let (negative, clock) = match word.strip_prefix('-') {
+                    Some(clock) => (true, clock),
+                    None => (false, word.strip_prefix('+').unwrap_or(word)),
+                };
+                let clock = parse_clock(clock)?;
+                micros = micros.checked_add(if negative { -clock } else { clock })?;
+                continue;
+            }
+            let quantity = word.parse::<i64>().ok()?;
+            let unit = words.next()?.to_lowercase();
+            let (months_per_unit, days_per_unit, micros_per_unit) =
+                match unit.strip_suffix('s').unwrap_or(&unit) {
+                    "microsecond" => (0, 0, 1),
+                    "millisecond" => (0, 0, 1_000),
+                    "second" | "sec" => (0, 0, MICROS_PER_SECOND),
+                    "minute" | "min" => (0, 0, MICROS_PER_MINUTE),
+                    "hour" => (0, 0, MICROS_PER_HOUR),
+                    "day" => (0, 1, 0),
+                    "week" => (0, 7, 0),
+                    "month" | "mon" => (1, 0, 0),
+                    "year" => (12, 0, 0),
+                    _ => return None,
+                };
+            months = months.checked_add(quantity.checked_mul(months_per_unit)?)?;
+            days = days.checked_add(quantity.checked_mul(days_per_unit)?)?;
+            micros = micros.checked_add(quantity.checked_mul(micros_per_unit)?)?;
+        }
+        Some(Self::new(
+            i32::try_from(months).ok()?,
+            i32::try_from(days).ok()?,
+            micros,
+        ))
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(Self::new(
+            self.months.checked_add(other.months)?,
+            self.days.checked_add(other.days)?,
+            self.micros.checked_add(other.micros)?,
+        ))
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_add(&other.checked_neg()?)
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(Self::new(
+            self.months.checked_neg()?,
+            self.days.checked_neg()?,
+            self.micros.checked_neg()?,
+        ))
+    }
+
+    pub fn checked_mul(&self, factor: i64) -> Option<Self> {
+        let factor32 = i32::try_from(factor).ok()?;
+        Some(Self::new(
+            self.months.checked_mul(factor32)?,
+            self.days.checked_mul(factor32)?,
+            self.micros.checked_mul(factor)?,
+        ))
+    }
+
+    /// the length in microseconds, counting a month as 30 days
+    pub fn total_micros(&self) -> i128 {
+        (self.months as i128 * 30 + self.days as i128) * MICROS_PER_DAY as i128
+            + self.micros as i128
+    }
+}
+
+impl PartialEq for Interval {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Interval {}
+
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// by length, so that 1 month = 30 days and 1 day = 24 hours
+impl Ord for Interval {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.total_micros().cmp(&other.total_micros())
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |n: i64| if n == 1 { "" } else { "s" };
+        let mut parts = Vec::new();
+        let (years, months) = (self.months / 12, self.months % 12);
+        if years != 0 {
+            parts.push(format!("{} year{}", years, plural(years as i64)));
+        }
+        if months != 0 {
+            parts.push(format!("{} mon{}", months, plural(months as i64)));
+        }
+        if self.days != 0 {
+            parts.push(format!("{} day{}", self.days, plural(self.days as i64)));
+        }
+        if self.micros != 0 || parts.is_empty() {
+            let sign = if self.micros < 0 { "-" } else { "" };
+            parts.push(format!("{}{}", sign, Clock(self.micros.unsigned_abs())));
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// `HH:MM:SS` with the fraction of a second if any, hours may exceed 24
+struct Clock(u64);
+
+impl Display for Clock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let micros = self.0;
+        let (hours, micros) = (
+            micros / MICROS_PER_HOUR as u64,
+            micros % MICROS_PER_HOUR as u64,
+        );
+        let (minutes, micros) = (
+            micros / MICROS_PER_MINUTE as u64,
+            micros % MICROS_PER_MINUTE as u64,
+        );
+        let (seconds, micros) = (
+            micros / MICROS_PER_SECOND as u64,
+            micros % MICROS_PER_SECOND as u64,
+        );
+        write!(f, "{:02}:{:02}:{:02}", hours, minutes, seconds)?;
+        if micros != 0 {
+            let fraction = format!("{:06}", micros);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        Ok(())
+    }
+}
+
+/// `H+:MM[:SS[.ffffff]]` as microseconds, minutes and seconds below 60
+fn parse_clock(s: &str) -> Option<i64> {
+    let mut parts = s.splitn(3, ':');
+    let hours = parse_digits(parts.next()?, 9)?;
+    let minutes = parse_digits(parts.next()?, 2)?;
+    let (seconds, fraction) = match parts.next() {
+        Some(seconds) => seconds.split_once('.').unwrap_or((seconds, "")),
+        None => ("0", ""),
+    };
+    let seconds = parse_digits(seconds, 2)?;
+    if minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+    let micros = match fraction {
+        "" => 0,
+        fraction if fraction.len() <= 6 => {
+            parse_digits(fraction, 6)? * 10i64.pow(6 - fraction.len() as u32)
+        }
+        _ => return None,
+    };
+    Some(
+        hours * MICROS_PER_HOUR
+            + minutes * MICROS_PER_MINUTE
+            + seconds * MICROS_PER_SECOND
+            + micros,
+    )
+}
+
+/// 1 to `max_len` ascii digits
+fn parse_digits(s: &str, max_len: usize) -> Option<i64> {
+    if s.is_empty() || s.len() > max_len || !s.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// days since 1970-01-01 of a date in the proleptic Gregorian calendar
+/// (http://howardhinnant.github.io/date_algorithms.html)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// the inverse of `days_from_civil`
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        Date::parse(s).unwrap()
+    }
+
+    fn timestamp(s: &str) -> Timestamp {
+        Timestamp::parse(s).unwrap()
+    }
+
+    fn interval(s: &str) -> Interval {
+        Interval::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_date() {
+        assert_eq!(date("1970-01-01").days(), 0);
+        assert_eq!(date("2026-10-18").to_string(), "2026-10-18");
+        assert_eq!(date("0001-01-01").to_string(), "0001-01-01");
+        assert_eq!(date("2024-02-29").ymd(), (2024, 2, 29));
+        assert_eq!(Date::parse("2023-02-29"), None);
+        assert_eq!(Date::parse("2026-13-01"), None);
+        assert_eq!(Date::parse("2026-10"), None);
+        assert_eq!(Date::parse("10000-01-01"), None);
+        assert_eq!(Date::parse("0000-12-31"), None);
+
+        // 2026-10-18 is a Sunday
+        assert_eq!(date("2026-10-18").weekday(), 0);
+        assert_eq!(date("2026-10-18").ordinal(), 291);
+        assert_eq!(date("2026-10-18").iso_week(), 42);
+        assert_eq!(date("2027-01-01").iso_week(), 53);
+        assert_eq!(date("2026-10-18").week_start().unwrap(), date("2026-10-12"));
+
+        assert_eq!(
+            date("2024-01-31").checked_add_months(1).unwrap(),
+            date("2024-02-29")
+        );
+        assert_eq!(
+            date("2024-03-31").checked_add_months(-13).unwrap(),
+            date("2023-02-28")
+        );
+        assert_eq!(date("9999-12-31").checked_add_days(1), None);
+    }
+
+    #[test]
+    fn test_time_and_timestamp() {
+        assert_eq!(Time::parse("04:05").unwrap().to_string(), "04:05:00");
+        assert_eq!(
+            Time::parse("23:59:59.5").unwrap().micros(),
+            MICROS_PER_DAY - MICROS_PER_SECOND / 2
+        );
+        assert_eq!(Time::parse("24:00:00"), None);
+        assert_eq!(Time::parse("12:60"), None);
+        assert_eq!(Time::parse("12:00:00.1234567"), None);
+        assert_eq!(
+            Time::parse("23:00")
+                .unwrap()
+                .add_micros(2 * MICROS_PER_HOUR),
+            Time::parse("01:00").unwrap()
+        );
+
+        assert_eq!(
+            timestamp("2026-10-18 04:05:06.789").to_string(),
+            "2026-10-18 04:05:06.789"
+        );
+        assert_eq!(
+            timestamp("2026-10-18T04:05:06"),
+            timestamp("2026-10-18 04:05:06")
+        );
+        assert_eq!(timestamp("2026-10-18").to_string(), "2026-10-18 00:00:00");
+        assert_eq!(
+            timestamp("1969-12-31 23:59:59").micros(),
+            -MICROS_PER_SECOND
+        );
+        assert_eq!(Timestamp::parse("2026-10-18 25:00"), None);
+    }
+
+    #[test]
+    fn test_interval() {
+        assert_eq!(
+            interval("1 year 14 months -3 days 04:05:06.5").to_string(),
+            "2 years 2 mons -3 days 04:05:06.5"
+        );
+        assert_eq!(interval("2 weeks 1 hour").to_string(), "14 days 01:00:00");
+        assert_eq!(interval("-90 MINUTES").to_string(), "-01:30:00");
+        assert_eq!(interval("0 days").to_string(), "00:00:00");
+        assert_eq!(interval("1 mon").to_string(), "1 mon");
+        assert_eq!(Interval::parse(""), None);
+        assert_eq!(Interval::parse("1"), None);
+        assert_eq!(Interval::parse("1 fortnight"), None);
+
+        // displayed intervals parse back
+        let i = interval("-1 year 2 days -00:00:01");
+        assert_eq!(interval(&i.to_string()).to_string(), i.to_string());
+
+        // compared by length
+        assert_eq!(interval("1 month"), interval("30 days"));
+        assert!(interval("1 day") < interval("25 hours"));
+    }
+
+    #[test]
+    fn test_timestamp_arithmetic() {
+        let ts = timestamp("2024-01-31 12:00");
+        assert_eq!(
+            ts.checked_add_interval(&interval("1 month 1 day 13:00"))
+                .unwrap(),
+            timestamp("2024-03-02 01:00")
+        );
+        assert_eq!(
+            ts.checked_sub_interval(&interval("1 year")).unwrap(),
+            timestamp("2023-01-31 12:00")
+        );
+        assert_eq!(
+            timestamp("2026-10-18 06:00")
+                .interval_since(&timestamp("2026-10-16 12:30"))
+                .to_string(),
+            "1 day 17:30:00"
+        );
+        assert_eq!(
+            timestamp("9999-12-31 23:00").checked_add_interval(&interval("1 hour")),
+            None
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use std::{cmp::Ordering, fmt::Display};
+
+/// an exact fixed-point number, `mantissa * 10^-scale`
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u8,
+}
+
+impl Decimal {
+    /// the most digits a decimal can hold, both sides of the point together
+    pub const MAX_PRECISION: u8 = 38;
+
+    pub fn new(mantissa: i128, scale: u8) -> Self {
+        Self { mantissa, scale }
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// parse `[+-]digits[.digits]`
+    pub fn parse(s: &str) -> Option<Self> {
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+        if int.is_empty() && frac.is_empty() {
+            return None;
+        }
+        if !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let scale = u8::try_from(frac.len())
+            .ok()
+            .filter(|scale| *scale <= Self::MAX_PRECISION)?;
+        let mantissa = format!("{}{}", int, frac).parse::<i128>().ok()?;
+        Some(Self::new(
+            if negative { -mantissa } else { mantissa },
+            scale,
+        ))
+    }
+
+    /// number of digits of the mantissa, at least 1
+    pub fn precision(&self) -> u8 {
+        let mut digits = 1;
+        let mut rest = self.mantissa.unsigned_abs() / 10;
+        while rest > 0 {
+            digits += 1;
+            rest /= 10;
+        }
+        digits
+    }
+
+    /// round half away from zero to `scale` digits after the point
+    pub fn rescale(&self, scale: u8) -> Option<Self> {
+        if scale > Self::MAX_PRECISION {
+            return None;
+        }
+        match scale.cmp(&self.scale) {
+            Ordering::Equal => Some(*self),
+            Ordering::Greater => {
+                let mantissa = self.mantissa.checked_mul(pow10(scale - self.scale)?)?;
+                Some(Self::new(mantissa, scale))
+            }
+            Ordering::Less => {
+                let mantissa = div_round(self.mantissa, pow10(self.scale - scale)?)?;
+                Some(Self::new(mantissa, scale))
+            }
+        }
+    }
+
+    /// rescale to fit DECIMAL(precision, scale), None if it has too many digits
+    pub fn fit(&self, precision: u8, scale: u8) -> Option<Self> {
+        Some(self.rescale(scale)?).filter(|decimal| decimal.precision() <= precision)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.mantissa as f64 / 10f64.powi(self.scale as i32)
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let (l, r, scale) = Self::align(self, other)?;
+        Some(Self::new(l.checked_add(r)?, scale))
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let (l, r, scale) = Self::align(self, other)?;
+        Some(Self::new(l.checked_sub(r)?, scale))
+    }
+
+    /// the scale of a product is the sum of the scales
+    /// (rounded to the max precision beyond that)
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let mantissa = self.mantissa.checked_mul(other.mantissa)?;
+        let scale = self.scale.checked_add(other.scale)?;
+        if scale > Self::MAX_PRECISION {
+            let mantissa = div_round(mantissa, pow10(scale - Self::MAX_PRECISION)?)?;
+            return Some(Self::new(mantissa, Self::MAX_PRECISION));
+        }
+        Some(Self::new(mantissa, scale))
+    }
+
+    /// the quotient rounded to `scale` digits, None on division by zero or overflow
+    pub fn checked_div(&self, other: &Self, scale: u8) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
+        // (l / 10^ls) / (r / 10^rs) * 10^scale = l * 10^(rs + scale) / (r * 10^ls)
+        let numerator = self
+            .mantissa
+            .checked_mul(pow10(other.scale.checked_add(scale)?)?)?;
+        let denominator = other.mantissa.checked_mul(pow10(self.scale)?)?;
+        Some(Self::new(div_round(numerator, denominator)?, scale))
+    }
+
+    pub fn checked_rem(&self, other: &Self) -> Option<Self> {
+        let (l, r, scale) = Self::align(self, other)?;
+        Some(Self::new(l.checked_rem(r)?, scale))
+    }
+
+    /// both mantissas at the larger scale of the two
+    fn align(l: &Self, r: &Self) -> Option<(i128, i128, u8)> {
+        let scale = l.scale.max(r.scale);
+        Some((
+            l.rescale(scale)?.mantissa,
+            r.rescale(scale)?.mantissa,
+            scale,
+        ))
+    }
+
+    /// (integer part, fraction part in units of 10^-38), which orders like the number itself
+    /// without overflowing when the scales differ
+    fn ordering_key(&self) -> (i128, i128) {
+        let unit = pow10(self.scale).expect("scale should not exceed the max precision");
+        let fraction = self.mantissa % unit;
+        (
+            self.mantissa / unit,
+            fraction * pow10(Self::MAX_PRECISION - self.scale).unwrap(),
+        )
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Self::new(value as i128, 0)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// numerically, regardless of scale: 1.50 = 1.5
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ordering_key().cmp(&other.ordering_key())
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (int, frac) = digits.split_at(digits.len() - scale);
+        if self.mantissa < 0 {
+            write!(f, "-")?;
+        }
+        if frac.is_empty() {
+            write!(f, "{}", int)
+        } else {
+            write!(f, "{}.{}", int, frac)
+        }
+    }
+}
+
+fn pow10(exp: u8) -> Option<i128> {
+    10i128.checked_pow(exp as u32)
+}
+
+/// integer division rounding half away from zero
+fn div_round(numerator: i128, denominator: i128) -> Option<i128> {
+    let quotient = numerator.checked_div(denominator)?;
+    let remainder = numerator % denominator;
+    if remainder.unsigned_abs() * 2 >= denominator.unsigned_abs() {
+        let sign = if (numerator < 0) == (denominator < 0) {
+            1
+        } else {
+            -1
+        };
+        return quotient.checked_add(sign);
+    }
+    Some(quotient)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn decimal(s: &str) -> Decimal {
+        Decimal::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!(decimal("12.345").mantissa(), 12345);
+        assert_eq!(decimal("12.345").scale(), 3);
+        assert_eq!(decimal("-0.05").to_string(), "-0.05");
+        assert_eq!(decimal("+7").to_string(), "7");
+        assert_eq!(decimal(".5").to_string(), "0.5");
+        assert_eq!(Decimal::new(-5, 3).to_string(), "-0.005");
+        assert_eq!(Decimal::parse("1.2.3"), None);
+        assert_eq!(Decimal::parse("-"), None);
+        assert_eq!(Decimal::parse("1e3"), None);
+    }
+
+    #[test]
+    fn test_rescale_and_fit() {
+        assert_eq!(decimal("1.005").rescale(2).unwrap().to_string(), "1.01");
+        assert_eq!(decimal("-1.005").rescale(2).unwrap().to_string(), "-1.01");
+        assert_eq!(decimal("1.004").rescale(2).unwrap().to_string(), "1.00");
+        assert_eq!(decimal("1.5").rescale(3).unwrap().to_string(), "1.500");
+        assert_eq!(decimal("123.45").fit(5, 2).unwrap().to_string(), "123.45");
+        assert_eq!(decimal("123.456").fit(5, 2).unwrap().to_string(), "123.46");
+        assert_eq!(decimal("1234.5").fit(5, 2), None);
+        assert_eq!(decimal("99.995").fit(4, 2), None);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(
+            decimal("1.5")
+                .checked_add(&decimal("0.25"))
+                .unwrap()
+                .to_string(),
+            "1.75"
+        );
+        assert_eq!(
+            decimal("1.5")
+                .checked_sub(&decimal("2"))
+                .unwrap()
+                .to_string(),
+            "-0.5"
+        );
+        assert_eq!(
+            decimal("1.5")
+                .checked_mul(&decimal("-0.25"))
+                .unwrap()
+                .to_string(),
+            "-0.375"
+        );
+        assert_eq!(
+            decimal("10")
+                .checked_div(&decimal("3"), 6)
+                .unwrap()
+                .to_string(),
+            "3.333333"
+        );
+        assert_eq!(
+            decimal("2")
+                .checked_div(&decimal("3"), 2)
+                .unwrap()
+                .to_string(),
+            "0.67"
+        );
+        assert_eq!(decimal("1").checked_div(&decimal("0"), 2), None);
+        assert_eq!(
+            decimal("5.5")
+                .checked_rem(&decimal("2"))
+                .unwrap()
+                .to_string(),
+            "1.5"
+        );
+        assert_eq!(Decimal::new(i128::MAX, 0).checked_add(&decimal("1")), None);
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert_eq!(decimal("1.50"), decimal("1.5"));
+        let mut values = [
+            decimal("1.5"),
+            decimal("-0.9"),
+            decimal("-1"),
+            decimal("0.25"),
+            decimal("-1.5"),
+            decimal("1"),
+        ];
+        values.sort();
+        assert_eq!(
+            values.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+            vec!["-1.5", "-1", "-0.9", "0.25", "1", "1.5"]
+        );
+    }
+}
@@ -0,0 +1,1075 @@
+use std::cmp::Ordering;
+
+use crate::{
+    error::{Error, Result},
+    query::ast::{BinaryOperator, Expr, Order, Value},
+};
+
+use super::{
+    aggregate,
+    datetime::{Date, Interval, Time, Timestamp},
+    decimal::Decimal,
+    function,
+    json::Json,
+    table::{self, DataType, Record, Table},
+};
+
+/// evaluate `expr` as a where condition against `row`
+/// a row satisfies the condition only if it is TRUE, not when it is UNKNOWN
+pub fn eval_cond(table: &Table, row: &Record, expr: &Expr) -> Result<bool> {
+    Ok(eval_logic(table, row, expr)? == Some(true))
+}
+
+/// evaluate `expr` with SQL three-valued logic, UNKNOWN being None
+pub fn eval_logic(table: &Table, row: &Record, expr: &Expr) -> Result<Option<bool>> {
+    match expr {
+        Expr::Not(expr) => Ok(eval_logic(table, row, expr)?.map(|v| !v)),
+        Expr::Binary(left, BinaryOperator::And, right) => {
+            let left = eval_logic(table, row, left)?;
+            let right = eval_logic(table, row, right)?;
+            Ok(match (left, right) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            })
+        }
+        Expr::Binary(left, BinaryOperator::Or, right) => {
+            let left = eval_logic(table, row, left)?;
+            let right = eval_logic(table, row, right)?;
+            Ok(match (left, right) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            })
+        }
+        Expr::Binary(left, operator, right) if operator.is_comparison() => {
+            let left = eval_value(table, row, left)?;
+            let right = eval_value(table, row, right)?;
+            // comparing with NULL is UNKNOWN
+            if left == table::Value::Null || right == table::Value::Null {
+                return Ok(None);
+            }
+            let Some(ordering) = compare(&left, &right) else {
+                // JSON values of different kinds are simply not equal
+                let is_json =
+                    matches!(left, table::Value::Json(_)) || matches!(right, table::Value::Json(_));
+                match operator {
+                    BinaryOperator::Eq if is_json => return Ok(Some(false)),
+                    BinaryOperator::NotEq if is_json => return Ok(Some(true)),
+                    _ => {}
+                }
+                return Err(Error::Type(format!(
+                    "operator {} is not defined for {:?} and {:?}",
+                    operator, left, right
+                )));
+            };
+            Ok(Some(match operator {
+                BinaryOperator::Eq => ordering == Ordering::Equal,
+                BinaryOperator::NotEq => ordering != Ordering::Equal,
+                BinaryOperator::Lt => ordering == Ordering::Less,
+                BinaryOperator::LtEq => ordering != Ordering::Greater,
+                BinaryOperator::Gt => ordering == Ordering::Greater,
+                BinaryOperator::GtEq => ordering != Ordering::Less,
+                _ => unreachable!("not a comparison operator"),
+            }))
+        }
+        Expr::IsNull(expr, negated) => {
+            let is_null = eval_value(table, row, expr)? == table::Value::Null;
+            Ok(Some(is_null != *negated))
+        }
+        // numbers are true unless zero, anything else is rejected by check_cond
+        Expr::Column(_)
+        | Expr::Value(_)
+        | Expr::Neg(_)
+        | Expr::Aggregate(..)
+        | Expr::Function(..)
+        | Expr::Binary(..) => Ok(match eval_value(table, row, expr)? {
+            table::Value::Null => None,
+            table::Value::Boolean(v) => Some(v),
+            table::Value::Int(v) => Some(v != 0),
+            table::Value::BigInt(v) => Some(v != 0),
+            table::Value::Double(v) => Some(v != 0.0),
+            table::Value::Decimal(v) => Some(!v.is_zero()),
+            value => {
+                return Err(Error::Type(format!(
+                    "a condition must be BOOLEAN or numeric, not {:?}",
+                    value
+                )))
+            }
+        }),
+    }
+}
+
+/// type check `cond`, which must be a boolean, or a number that is true unless zero
+pub fn check_cond(table: &Table, cond: &Expr) -> Result<()> {
+    logic_type(&data_type_of(table, cond)?)
+}
+
+fn logic_type(data_type: &DataType) -> Result<()> {
+    if *data_type == DataType::Boolean || data_type.is_numeric() {
+        return Ok(());
+    }
+    Err(Error::Type(format!(
+        "a condition must be BOOLEAN or numeric, not {:?}",
+        data_type
+    )))
+}
+
+/// whether values of the types can be compared, as `compare` does for values
+fn comparison_type(operator: BinaryOperator, left: &DataType, right: &DataType) -> Result<()> {
+    let is_text = |data_type: &DataType| matches!(data_type, DataType::VarChar(_) | DataType::Text);
+    let is_date = |data_type: &DataType| matches!(data_type, DataType::Date | DataType::Timestamp);
+    // JSON strings, numbers and booleans compare with SQL values of their kind
+    let is_json_scalar = |data_type: &DataType| {
+        is_text(data_type) || data_type.is_numeric() || *data_type == DataType::Boolean
+    };
+    let comparable = (left.is_numeric() && right.is_numeric())
+        || (is_text(left) && is_text(right))
+        || (is_date(left) && is_date(right))
+        || (*left == DataType::Json && (*right == DataType::Json || is_json_scalar(right)))
+        || (*right == DataType::Json && is_json_scalar(left))
+        || left == right;
+    if comparable {
+        return Ok(());
+    }
+    Err(Error::Type(format!(
+        "operator {} is not defined for {:?} and {:?}",
+        operator, left, right
+    )))
+}
+
+/// keep the rows that satisfy `cond`
+pub fn filter_rows(table: &Table, rows: Vec<Record>, cond: &Expr) -> Result<Vec<Record>> {
+    // report unknown columns even when there is nothing to filter
+    check_cond(table, cond)?;
+    let mut filtered = Vec::new();
+    for row in rows {
+        if eval_cond(table, &row, cond)? {
+            filtered.push(row);
+        }
+    }
+    Ok(filtered)
+}
+
+/// stable sort by the order by keys, compared left to right
+pub fn sort_rows(table: &Table, rows: &mut Vec<Record>, order_by: &[(Expr, Order)]) -> Result<()> {
+    // evaluate the keys up front so that evaluation errors surface before sorting
+    let mut keyed = Vec::new();
+    for row in std::mem::take(rows) {
+        let mut keys = Vec::new();
+        for (key, _) in order_by.iter() {
+            keys.push(eval_value(table, &row, key)?);
+        }
+        keyed.push((keys, row));
+    }
+    keyed.sort_by(|(a, _), (b, _)| {
+        for ((a, b), (_, order)) in a.iter().zip(b.iter()).zip(order_by.iter()) {
+            let ordering = match order {
+                Order::Asc => a.cmp(b),
+                Order::Desc => a.cmp(b).reverse(),
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    });
+    rows.extend(keyed.into_iter().map(|(_, row)| row));
+    Ok(())
+}
+
+/// numbers numerically whatever their types, strings lexicographically, false before true,
+/// dates and times chronologically (a date being midnight when compared with a timestamp)
+/// intervals by length, blobs bytewise, and JSON documents as `Json` orders them
+/// (a JSON string, number or boolean also compares with a SQL value of its kind)
+fn compare(left: &table::Value, right: &table::Value) -> Option<Ordering> {
+    match (left, right) {
+        (table::Value::Boolean(l), table::Value::Boolean(r)) => Some(l.cmp(r)),
+        (table::Value::VarChar(l), table::Value::VarChar(r)) => Some(l.cmp(r)),
+        (table::Value::Date(l), table::Value::Date(r)) => Some(l.cmp(r)),
+        (table::Value::Time(l), table::Value::Time(r)) => Some(l.cmp(r)),
+        (table::Value::Timestamp(l), table::Value::Timestamp(r)) => Some(l.cmp(r)),
+        (table::Value::Interval(l), table::Value::Interval(r)) => Some(l.cmp(r)),
+        (table::Value::Blob(l), table::Value::Blob(r)) => Some(l.cmp(r)),
+        (table::Value::Json(l), table::Value::Json(r)) => Some(l.cmp(r)),
+        (table::Value::Json(l), r) => compare(&json_scalar(l)?, r),
+        (l, table::Value::Json(r)) => compare(l, &json_scalar(r)?),
+        (table::Value::Date(l), table::Value::Timestamp(r)) => Some(Timestamp::from(*l).cmp(r)),
+        (table::Value::Timestamp(l), table::Value::Date(r)) => Some(l.cmp(&Timestamp::from(*r))),
+        _ => match Number::widen(Number::of(left)?, Number::of(right)?) {
+            (Number::Integer(l), Number::Integer(r)) => Some(l.cmp(&r)),
+            (Number::Decimal(l), Number::Decimal(r)) => Some(l.cmp(&r)),
+            (Number::Double(l), Number::Double(r)) => Some(table::compare_f64(l, r)),
+            _ => unreachable!("widened numbers are of the same kind"),
+        },
+    }
+}
+
+/// the SQL value of a JSON string, number or boolean
+fn json_scalar(json: &Json) -> Option<table::Value> {
+    match json {
+        Json::String(s) => Some(table::Value::VarChar(s.clone())),
+        Json::Number(v) => Some(table::Value::Double(*v)),
+        Json::Bool(v) => Some(table::Value::Boolean(*v)),
+        Json::Null | Json::Array(_) | Json::Object(_) => None,
+    }
+}
+
+/// a numeric value, widened to a common kind for mixed-type comparison and arithmetic:
+/// integers < decimals < doubles
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Integer(i64),
+    Decimal(Decimal),
+    Double(f64),
+}
+
+impl Number {
+    fn of(value: &table::Value) -> Option<Self> {
+        match value {
+            table::Value::Int(v) => Some(Number::Integer(*v as i64)),
+            table::Value::BigInt(v) => Some(Number::Integer(*v)),
+            table::Value::Decimal(v) => Some(Number::Decimal(*v)),
+            table::Value::Double(v) => Some(Number::Double(*v)),
+            _ => None,
+        }
+    }
+
+    fn widen(left: Self, right: Self) -> (Self, Self) {
+        match (left, right) {
+            (Number::Double(_), _) | (_, Number::Double(_)) => (
+                Number::Double(left.to_f64()),
+                Number::Double(right.to_f64()),
+            ),
+            (Number::Decimal(_), _) | (_, Number::Decimal(_)) => (
+                Number::Decimal(left.to_decimal()),
+                Number::Decimal(right.to_decimal()),
+            ),
+            _ => (left, right),
+        }
+    }
+
+    fn to_f64(self) -> f64 {
+        match self {
+            Number::Integer(v) => v as f64,
+            Number::Decimal(v) => v.to_f64(),
+            Number::Double(v) => v,
+        }
+    }
+
+    fn to_decimal(self) -> Decimal {
+        match self {
+            Number::Integer(v) => Decimal::from(v),
+            Number::Decimal(v) => v,
+            Number::Double(_) => unreachable!("doubles are never narrowed to decimals"),
+        }
+    }
+}
+
+/// the type of `left operator right` for numeric, date and time operands
+fn arithmetic_type(
+    operator: BinaryOperator,
+    left: &DataType,
+    right: &DataType,
+) -> Result<DataType> {
+    if let Some(data_type) = temporal_arithmetic_type(operator, left, right) {
+        return Ok(data_type);
+    }
+    if !left.is_numeric() || !right.is_numeric() {
+        return Err(Error::Type(format!(
+            "operator {} is not defined for {:?} and {:?}",
+            operator, left, right
+        )));
+    }
+    Ok(match (left, right) {
+        (DataType::Double, _) | (_, DataType::Double) => DataType::Double,
+        (DataType::Decimal(..), _) | (_, DataType::Decimal(..)) => {
+            let scale_of = |data_type: &DataType| match data_type {
+                DataType::Decimal(_, scale) => *scale,
+                _ => 0,
+            };
+            DataType::Decimal(
+                Decimal::MAX_PRECISION,
+                decimal_scale(operator, scale_of(left), scale_of(right)),
+            )
+        }
+        (DataType::Int, DataType::Int) => DataType::Int,
+        _ => DataType::BigInt,
+    })
+}
+
+/// the type of `left operator right` for date and time operands, None if it is not defined
+/// (the rules of `eval_temporal_arithmetic`)
+fn temporal_arithmetic_type(
+    operator: BinaryOperator,
+    left: &DataType,
+    right: &DataType,
+) -> Option<DataType> {
+    let weight = |data_type: &DataType| match data_type {
+        DataType::Date | DataType::Time | DataType::Timestamp => 2,
+        DataType::Interval => 1,
+        _ => 0,
+    };
+    // keep the date or time on the left of commutative operators
+    let (left, right) = match operator {
+        BinaryOperator::Add | BinaryOperator::Mul if weight(right) > weight(left) => (right, left),
+        _ => (left, right),
+    };
+    let is_integer = matches!(right, DataType::Int | DataType::BigInt);
+    match (left, operator, right) {
+        (
+            DataType::Date | DataType::Timestamp,
+            BinaryOperator::Add | BinaryOperator::Sub,
+            DataType::Interval,
+        ) => Some(DataType::Timestamp),
+        (DataType::Date, BinaryOperator::Add | BinaryOperator::Sub, _) if is_integer => {
+            Some(DataType::Date)
+        }
+        (DataType::Date, BinaryOperator::Sub, DataType::Date) => Some(DataType::Int),
+        (DataType::Timestamp, BinaryOperator::Sub, DataType::Timestamp)
+        | (DataType::Time, BinaryOperator::Sub, DataType::Time)
+        | (DataType::Interval, BinaryOperator::Add | BinaryOperator::Sub, DataType::Interval) => {
+            Some(DataType::Interval)
+        }
+        (DataType::Interval, BinaryOperator::Mul, _) if is_integer => Some(DataType::Interval),
+        (DataType::Time, BinaryOperator::Add | BinaryOperator::Sub, DataType::Interval) => {
+            Some(DataType::Time)
+        }
+        _ => None,
+    }
+}
+
+/// evaluate `left operator right` for date and time operands, None if it is not defined:
+/// - a timestamp or a date plus or minus an interval is a timestamp, adding months first
+/// - a date plus or minus an integer is a date that many days later or earlier
+/// - a date minus a date is the number of days in between
+/// - a timestamp minus a timestamp, or a time minus a time, is an interval in days and time
+/// - a time plus or minus an interval is a time, wrapping around midnight
+/// - intervals can be added together and multiplied by integers
+fn eval_temporal_arithmetic(
+    operator: BinaryOperator,
+    left: &table::Value,
+    right: &table::Value,
+) -> Option<Result<table::Value>> {
+    let weight = |value: &table::Value| match value {
+        table::Value::Date(_) | table::Value::Time(_) | table::Value::Timestamp(_) => 2,
+        table::Value::Interval(_) => 1,
+        _ => 0,
+    };
+    let (left, right) = match operator {
+        BinaryOperator::Add | BinaryOperator::Mul if weight(right) > weight(left) => (right, left),
+        _ => (left, right),
+    };
+    let integer = match right {
+        table::Value::Int(v) => Some(*v as i64),
+        table::Value::BigInt(v) => Some(*v),
+        _ => None,
+    };
+    let out_of_range = |type_name: &str| Error::Type(format!("{} out of range", type_name));
+    let timestamp = |v: Option<Timestamp>| {
+        v.map(table::Value::Timestamp)
+            .ok_or_else(|| out_of_range("timestamp"))
+    };
+    let interval = |v: Option<Interval>| {
+        v.map(table::Value::Interval)
+            .ok_or_else(|| out_of_range("interval"))
+    };
+    let time = |time: &Time, micros: i64| Ok(table::Value::Time(time.add_micros(micros)));
+
+    Some(match (left, operator, right) {
+        (table::Value::Timestamp(l), BinaryOperator::Add, table::Value::Interval(r)) => {
+            timestamp(l.checked_add_interval(r))
+        }
+        (table::Value::Timestamp(l), BinaryOperator::Sub, table::Value::Interval(r)) => {
+            timestamp(l.checked_sub_interval(r))
+        }
+        (table::Value::Date(l), BinaryOperator::Add, table::Value::Interval(r)) => {
+            timestamp(Timestamp::from(*l).checked_add_interval(r))
+        }
+        (table::Value::Date(l), BinaryOperator::Sub, table::Value::Interval(r)) => {
+            timestamp(Timestamp::from(*l).checked_sub_interval(r))
+        }
+        (table::Value::Date(l), BinaryOperator::Add | BinaryOperator::Sub, _)
+            if integer.is_some() =>
+        {
+            let days = match operator {
+                BinaryOperator::Add => integer,
+                _ => integer.and_then(i64::checked_neg),
+            };
+            days.and_then(|days| l.checked_add_days(days))
+                .map(table::Value::Date)
+                .ok_or_else(|| out_of_range("date"))
+        }
+        (table::Value::Date(l), BinaryOperator::Sub, table::Value::Date(r)) => {
+            Ok(table::Value::Int((l.days() - r.days()) as i32))
+        }
+        (table::Value::Timestamp(l), BinaryOperator::Sub, table::Value::Timestamp(r)) => {
+            Ok(table::Value::Interval(l.interval_since(r)))
+        }
+        (table::Value::Time(l), BinaryOperator::Sub, table::Value::Time(r)) => Ok(
+            table::Value::Interval(Interval::new(0, 0, l.micros() - r.micros())),
+        ),
+        (table::Value::Time(l), BinaryOperator::Add, table::Value::Interval(r)) => {
+            time(l, r.micros())
+        }
+        (table::Value::Time(l), BinaryOperator::Sub, table::Value::Interval(r)) => {
+            time(l, -r.micros())
+        }
+        (table::Value::Interval(l), BinaryOperator::Add, table::Value::Interval(r)) => {
+            interval(l.checked_add(r))
+        }
+        (table::Value::Interval(l), BinaryOperator::Sub, table::Value::Interval(r)) => {
+            interval(l.checked_sub(r))
+        }
+        (table::Value::Interval(l), BinaryOperator::Mul, _) if integer.is_some() => {
+            interval(integer.and_then(|factor| l.checked_mul(factor)))
+        }
+        _ => return None,
+    })
+}
+
+/// the scale of a decimal result, at least 6 digits for a quotient
+fn decimal_scale(operator: BinaryOperator, left: u8, right: u8) -> u8 {
+    match operator {
+        BinaryOperator::Mul => (left + right).min(Decimal::MAX_PRECISION),
+        BinaryOperator::Div => left.max(right).max(6),
+        _ => left.max(right),
+    }
+}
+
+/// evaluate `left operator right`, NULL if either side is NULL
+///
+/// integer division truncates, and dividing by zero is an error for every type
+fn eval_arithmetic(
+    operator: BinaryOperator,
+    left: &table::Value,
+    right: &table::Value,
+) -> Result<table::Value> {
+    if *left == table::Value::Null || *right == table::Value::Null {
+        return Ok(table::Value::Null);
+    }
+    if let Some(result) = eval_temporal_arithmetic(operator, left, right) {
+        return result;
+    }
+    let type_error = || {
+        Error::Type(format!(
+            "operator {} is not defined for {:?} and {:?}",
+            operator, left, right
+        ))
+    };
+    let (Some(l), Some(r)) = (Number::of(left), Number::of(right)) else {
+        return Err(type_error());
+    };
+    let division_by_zero = || Error::Invalid(String::from("division by zero"));
+    let out_of_range = |type_name: &str| Error::Type(format!("{} out of range", type_name));
+
+    match Number::widen(l, r) {
+        (Number::Integer(l), Number::Integer(r)) => {
+            if matches!(operator, BinaryOperator::Div | BinaryOperator::Mod) && r == 0 {
+                return Err(division_by_zero());
+            }
+            let v = match operator {
+                BinaryOperator::Add => l.checked_add(r),
+                BinaryOperator::Sub => l.checked_sub(r),
+                BinaryOperator::Mul => l.checked_mul(r),
+                BinaryOperator::Div => l.checked_div(r),
+                BinaryOperator::Mod => l.checked_rem(r),
+                _ => unreachable!("not an arithmetic operator"),
+            };
+            // INT op INT stays INT, anything with a BIGINT is BIGINT
+            match (left, right) {
+                (table::Value::Int(_), table::Value::Int(_)) => v
+                    .and_then(|v| i32::try_from(v).ok())
+                    .map(table::Value::Int)
+                    .ok_or_else(|| out_of_range("integer")),
+                _ => v
+                    .map(table::Value::BigInt)
+                    .ok_or_else(|| out_of_range("bigint")),
+            }
+        }
+        (Number::Decimal(l), Number::Decimal(r)) => {
+            if matches!(operator, BinaryOperator::Div | BinaryOperator::Mod) && r.is_zero() {
+                return Err(division_by_zero());
+            }
+            let v = match operator {
+                BinaryOperator::Add => l.checked_add(&r),
+                BinaryOperator::Sub => l.checked_sub(&r),
+                BinaryOperator::Mul => l.checked_mul(&r),
+                BinaryOperator::Div => {
+                    l.checked_div(&r, decimal_scale(operator, l.scale(), r.scale()))
+                }
+                BinaryOperator::Mod => l.checked_rem(&r),
+                _ => unreachable!("not an arithmetic operator"),
+            };
+            v.map(table::Value::Decimal)
+                .ok_or_else(|| out_of_range("numeric"))
+        }
+        (Number::Double(l), Number::Double(r)) => {
+            if matches!(operator, BinaryOperator::Div | BinaryOperator::Mod) && r == 0.0 {
+                return Err(division_by_zero());
+            }
+            Ok(table::Value::Double(match operator {
+                BinaryOperator::Add => l + r,
+                BinaryOperator::Sub => l - r,
+                BinaryOperator::Mul => l * r,
+                BinaryOperator::Div => l / r,
+                BinaryOperator::Mod => l % r,
+                _ => unreachable!("not an arithmetic operator"),
+            }))
+        }
+        _ => unreachable!("widened numbers are of the same kind"),
+    }
+}
+
+/// the type of `-operand`, that of the operand for numbers and intervals
+fn negation_type(operand: &DataType) -> Result<DataType> {
+    if operand.is_numeric() || *operand == DataType::Interval {
+        return Ok(*operand);
+    }
+    Err(Error::Type(format!(
+        "operator - is not defined for {:?}",
+        operand
+    )))
+}
+
+/// evaluate `-operand`, NULL if it is NULL, out of range like `0 - operand` would be
+fn eval_negation(operand: &table::Value) -> Result<table::Value> {
+    let out_of_range = |type_name: &str| Error::Type(format!("{} out of range", type_name));
+    match operand {
+        table::Value::Null => Ok(table::Value::Null),
+        table::Value::Int(v) => v
+            .checked_neg()
+            .map(table::Value::Int)
+            .ok_or_else(|| out_of_range("integer")),
+        table::Value::BigInt(v) => v
+            .checked_neg()
+            .map(table::Value::BigInt)
+            .ok_or_else(|| out_of_range("bigint")),
+        table::Value::Double(v) => Ok(table::Value::Double(-v)),
+        table::Value::Decimal(v) => Decimal::new(0, v.scale())
+            .checked_sub(v)
+            .map(table::Value::Decimal)
+            .ok_or_else(|| out_of_range("numeric")),
+        table::Value::Interval(v) => v
+            .checked_neg()
+            .map(table::Value::Interval)
+            .ok_or_else(|| out_of_range("interval")),
+        _ => Err(Error::Type(format!(
+            "operator - is not defined for {:?}",
+            operand
+        ))),
+    }
+}
+
+/// the type of `left -> right` (JSON) or `left ->> right` (TEXT)
+fn json_access_type(
+    operator: BinaryOperator,
+    left: &DataType,
+    right: &DataType,
+) -> Result<DataType> {
+    match (left, right) {
+        (
+            DataType::Json,
+            DataType::VarChar(_) | DataType::Text | DataType::Int | DataType::BigInt,
+        ) => Ok(match operator {
+            BinaryOperator::JsonGet => DataType::Json,
+            _ => DataType::Text,
+        }),
+        _ => Err(Error::Type(format!(
+            "operator {} is not defined for {:?} and {:?}",
+            operator, left, right
+        ))),
+    }
+}
+
+/// the field of a JSON object by name or the element of an array by index (negative ones
+/// counting from the end), NULL if there is none
+///
+/// `->>` gives strings unquoted and anything else serialized, a JSON null being NULL
+fn eval_json_access(
+    operator: BinaryOperator,
+    left: &table::Value,
+    right: &table::Value,
+) -> Result<table::Value> {
+    if *left == table::Value::Null || *right == table::Value::Null {
+        return Ok(table::Value::Null);
+    }
+    let type_error = || {
+        Error::Type(format!(
+            "operator {} is not defined for {:?} and {:?}",
+            operator, left, right
+        ))
+    };
+    let table::Value::Json(json) = left else {
+        return Err(type_error());
+    };
+    let found = match right {
+        table::Value::VarChar(key) => json.get(key),
+        table::Value::Int(index) => json.index(*index as i64),
+        table::Value::BigInt(index) => json.index(*index),
+        _ => return Err(type_error()),
+    };
+    Ok(match (operator, found) {
+        (BinaryOperator::JsonGet, Some(json)) => table::Value::Json(json.clone()),
+        (_, Some(json)) => json
+            .to_text()
+            .map(table::Value::VarChar)
+            .unwrap_or(table::Value::Null),
+        (_, None) => table::Value::Null,
+    })
+}
+
+/// evaluate `expr` as a scalar value against `row`
+pub fn eval_value(table: &Table, row: &Record, expr: &Expr) -> Result<table::Value> {
+    match expr {
+        Expr::Column(name) => Ok(row.values[table.resolve_column(name)?].clone()),
+        Expr::Value(value) => eval_literal(value),
+        Expr::Binary(left, operator, right) if operator.is_arithmetic() => {
+            let left = eval_value(table, row, left)?;
+            let right = eval_value(table, row, right)?;
+            eval_arithmetic(*operator, &left, &right)
+        }
+        Expr::Neg(expr) => eval_negation(&eval_value(table, row, expr)?),
+        Expr::Binary(
+            left,
+            operator @ (BinaryOperator::JsonGet | BinaryOperator::JsonGetText),
+            right,
+        ) => {
+            let left = eval_value(table, row, left)?;
+            let right = eval_value(table, row, right)?;
+            eval_json_access(*operator, &left, &right)
+        }
+        Expr::Not(_) | Expr::Binary(..) | Expr::IsNull(..) => {
+            Ok(match eval_logic(table, row, expr)? {
+                Some(v) => table::Value::Boolean(v),
+                None => table::Value::Null,
+            })
+        }
+        Expr::Function(function, args) => {
+            let mut values = Vec::new();
+            for arg in args.iter() {
+                values.push(eval_value(table, row, arg)?);
+            }
+            function::eval(*function, &values)
+        }
+        // aggregates are resolved by the aggregation stage, and refused anywhere else
+        Expr::Aggregate(function, _) => Err(Error::Invalid(format!(
+            "aggregate function {}() is not allowed here",
+            function
+        ))),
+    }
+}
+
+/// the value of a literal, integers being INT when they fit and BIGINT otherwise
+pub fn eval_literal(value: &Value) -> Result<table::Value> {
+    let invalid = |type_name: &str, v: &str| {
+        Error::Type(format!("invalid input for type {}: '{}'", type_name, v))
+    };
+    match value {
+        Value::Int(v) => match i32::try_from(*v) {
+            Ok(v) => Ok(table::Value::Int(v)),
+            Err(_) => Ok(table::Value::BigInt(*v)),
+        },
+        Value::Decimal(v) => Ok(table::Value::Decimal(parse_decimal(v)?)),
+        Value::Boolean(v) => Ok(table::Value::Boolean(*v)),
+        Value::VarChar(v) => Ok(table::Value::VarChar(v.clone())),
+        Value::Date(v) => Date::parse(v)
+            .map(table::Value::Date)
+            .ok_or_else(|| invalid("DATE", v)),
+        Value::Time(v) => Time::parse(v)
+            .map(table::Value::Time)
+            .ok_or_else(|| invalid("TIME", v)),
+        Value::Timestamp(v) => Timestamp::parse(v)
+            .map(table::Value::Timestamp)
+            .ok_or_else(|| invalid("TIMESTAMP", v)),
+        Value::Interval(v) => Interval::parse(v)
+            .map(table::Value::Interval)
+            .ok_or_else(|| invalid("INTERVAL", v)),
+        Value::Json(v) => Json::parse(v)
+            .map(table::Value::Json)
+            .ok_or_else(|| invalid("JSON", v)),
+        Value::Blob(v) => Ok(table::Value::Blob(v.clone())),
+        Value::Null => Ok(table::Value::Null),
+    }
+}
+
+/// the type of the values `expr` evaluates to
+///
+/// every column reference is resolved on the way, so this also reports unknown columns
+/// of a table without rows
+pub fn data_type_of(table: &Table, expr: &Expr) -> Result<DataType> {
+    match expr {
+        Expr::Column(name) => Ok(table.columns[table.resolve_column(name)?].1),
+        Expr::Value(Value::Int(v)) if i32::try_from(*v).is_err() => Ok(DataType::BigInt),
+        Expr::Value(Value::Int(_) | Value::Null) => Ok(DataType::Int),
+        Expr::Value(Value::Decimal(v)) => {
+            let v = parse_decimal(v)?;
+            Ok(DataType::Decimal(v.precision().max(v.scale()), v.scale()))
+        }
+        Expr::Value(Value::Boolean(_)) => Ok(DataType::Boolean),
+        Expr::Value(Value::VarChar(v)) => Ok(DataType::VarChar(v.len() as u16)),
+        Expr::Value(Value::Blob(_)) => Ok(DataType::Blob),
+        Expr::Value(value) => {
+            // malformed date, time and JSON literals are reported up front
+            eval_literal(value)?;
+            Ok(match value {
+                Value::Date(_) => DataType::Date,
+                Value::Time(_) => DataType::Time,
+                Value::Timestamp(_) => DataType::Timestamp,
+                Value::Json(_) => DataType::Json,
+                _ => DataType::Interval,
+            })
+        }
+        Expr::Binary(left_expr, operator, right_expr) => {
+            let left = data_type_of(table, left_expr)?;
+            let right = data_type_of(table, right_expr)?;
+            match operator {
+                operator if operator.is_arithmetic() => arithmetic_type(*operator, &left, &right),
+                BinaryOperator::JsonGet | BinaryOperator::JsonGetText => {
+                    json_access_type(*operator, &left, &right)
+                }
+                operator if operator.is_comparison() => {
+                    // NULL compares with anything, to UNKNOWN
+                    let is_null = |expr: &Expr| *expr == Expr::Value(Value::Null);
+                    if !is_null(left_expr) && !is_null(right_expr) {
+                        comparison_type(*operator, &left, &right)?;
+                    }
+                    Ok(DataType::Boolean)
+                }
+                _ => {
+                    logic_type(&left)?;
+                    logic_type(&right)?;
+                    Ok(DataType::Boolean)
+                }
+            }
+        }
+        Expr::Not(expr) => {
+            logic_type(&data_type_of(table, expr)?)?;
+            Ok(DataType::Boolean)
+        }
+        Expr::IsNull(expr, _) => {
+            data_type_of(table, expr)?;
+            Ok(DataType::Boolean)
+        }
+        Expr::Neg(expr) => negation_type(&data_type_of(table, expr)?),
+        Expr::Function(function, args) => {
+            let mut arg_types = Vec::new();
+            for arg in args.iter() {
+                arg_types.push(data_type_of(table, arg)?);
+            }
+            function::data_type_of(*function, &arg_types)
+        }
+        Expr::Aggregate(function, arg) => {
+            let arg_type = match arg {
+                Some(arg) => Some(data_type_of(table, arg)?),
+                None => None,
+            };
+            aggregate::data_type_of(*function, arg_type)
+        }
+    }
+}
+
+fn parse_decimal(v: &str) -> Result<Decimal> {
+    Decimal::parse(v).ok_or_else(|| Error::Type(format!("numeric out of range: {}", v)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::query::{ast::QueryStatement, lex::Lexer, parser::Parser};
+
+    use super::*;
+
+    fn cond(input: &str) -> Expr {
+        let input = format!("SELECT * FROM user WHERE {};", input);
+        match Parser::new(Lexer::new(input)).parse().unwrap().remove(0) {
+            QueryStatement::Select(select) => select.cond.unwrap(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn user_table() -> Table {
+        Table::new(
+            String::from("user"),
+            vec![
+                (String::from("id"), table::DataType::Int),
+                (String::from("name"), table::DataType::VarChar(10)),
+            ],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_eval_cond() {
+        let table = user_table();
+        let row = Record::new(vec![
+            table::Value::Int(1),
+            table::Value::VarChar(String::from("alice")),
+        ]);
+        let matches = |input| eval_cond(&table, &row, &cond(input)).unwrap();
+
+        assert!(matches("id = 1"));
+        assert!(!matches("id = 2"));
+        assert!(matches("id = 2 OR name = 'alice'"));
+        assert!(!matches("id = 1 AND name = 'bob'"));
+        assert!(matches("NOT id = 2"));
+        assert!(matches("NOT (id = 2 OR name = 'bob')"));
+        // AND binds tighter than OR
+        assert!(matches("id = 1 OR id = 2 AND name = 'bob'"));
+        assert!(!matches("(id = 1 OR id = 2) AND name = 'bob'"));
+    }
+
+    #[test]
+    fn test_eval_cond_comparison() {
+        let table = user_table();
+        let row = Record::new(vec![
+            table::Value::Int(3),
+            table::Value::VarChar(String::from("bob")),
+        ]);
+        let matches = |input| eval_cond(&table, &row, &cond(input)).unwrap();
+
+        assert!(matches("id > 2"));
+        assert!(matches("id >= 3"));
+        assert!(!matches("id < 3"));
+        assert!(matches("id <= 3"));
+        assert!(matches("id <> 4"));
+        assert!(matches("id != 4"));
+        assert!(matches("1 < id"));
+        assert!(matches("name > 'alice'"));
+        assert!(matches("name < 'bobby'"));
+        assert!(!matches("name >= 'carol'"));
+        // mismatched types do not compare
+        assert!(matches!(
+            eval_cond(&table, &row, &cond("name <> 1")),
+            Err(Error::Type(_))
+        ));
+    }
+
+    #[test]
+    fn test_eval_arithmetic() {
+        let table = user_table();
+        let row = Record::new(vec![table::Value::Int(7), table::Value::Null]);
+        let eval = |input: &str| {
+            let Expr::Binary(left, _, _) = cond(&format!("{} = 0", input)) else {
+                unreachable!()
+            };
+            eval_value(&table, &row, &left)
+        };
+        let decimal = |s| table::Value::Decimal(Decimal::parse(s).unwrap());
+
+        assert_eq!(eval("id + 1 * 2").unwrap(), table::Value::Int(9));
+        assert_eq!(eval("id / 2").unwrap(), table::Value::Int(3));
+        assert_eq!(
+            eval("id - 2147483648").unwrap(),
+            table::Value::BigInt(-2147483641)
+        );
+        assert_eq!(eval("id / 2.0").unwrap(), decimal("3.500000"));
+        assert_eq!(eval("id * 0.25").unwrap(), decimal("1.75"));
+        assert_eq!(eval("name + 1").unwrap(), table::Value::Null);
+        assert!(matches!(eval("id % 0"), Err(Error::Invalid(_))));
+        assert!(matches!(eval("id * 2147483647"), Err(Error::Type(_))));
+        assert!(matches!(eval("id + 'a'"), Err(Error::Type(_))));
+    }
+
+    #[test]
+    fn test_eval_date_time() {
+        let table = Table::new(
+            String::from("event"),
+            vec![
+                (String::from("day"), table::DataType::Date),
+                (String::from("created"), table::DataType::Timestamp),
+            ],
+            vec![],
+        );
+        let row = Record::new(vec![
+            table::Value::Date(Date::parse("2024-01-31").unwrap()),
+            table::Value::Timestamp(Timestamp::parse("2024-01-31 12:00").unwrap()),
+        ]);
+        let input = |input: &str| {
+            let Expr::Binary(left, _, _) = cond(&format!("{} = 0", input)) else {
+                unreachable!()
+            };
+            *left
+        };
+        let eval = |s: &str| eval_value(&table, &row, &input(s));
+        let matches = |s: &str| eval_cond(&table, &row, &cond(s)).unwrap();
+        let timestamp = |s| table::Value::Timestamp(Timestamp::parse(s).unwrap());
+
+        // months are added first, clamping the day to the end of the month
+        assert_eq!(
+            eval("created + INTERVAL '1 month 1 day'").unwrap(),
+            timestamp("2024-03-01 12:00")
+        );
+        assert_eq!(
+            eval("INTERVAL '1 month' + day").unwrap(),
+            timestamp("2024-02-29")
+        );
+        assert_eq!(
+            eval("day - 31").unwrap(),
+            table::Value::Date(Date::parse("2023-12-31").unwrap())
+        );
+        assert_eq!(
+            eval("INTERVAL '1 hour' * 3 - INTERVAL '30 minutes'").unwrap(),
+            table::Value::Interval(Interval::parse("02:30:00").unwrap())
+        );
+        assert_eq!(
+            data_type_of(&table, &input("3 * INTERVAL '1 day'")).unwrap(),
+            DataType::Interval
+        );
+        assert_eq!(
+            data_type_of(&table, &input("day + 1")).unwrap(),
+            DataType::Date
+        );
+        assert!(matches!(eval("day * 2"), Err(Error::Type(_))));
+        assert!(matches!(eval("day + day"), Err(Error::Type(_))));
+        assert!(matches!(
+            eval("INTERVAL '1 fortnight'"),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            data_type_of(&table, &input("created - day")),
+            Err(Error::Type(_))
+        ));
+
+        assert!(matches("created > day"));
+        assert!(matches("day = TIMESTAMP '2024-01-31 00:00'"));
+        assert!(matches("created < DATE '2024-02-01'"));
+        assert!(matches("INTERVAL '1 month' = INTERVAL '30 days'"));
+        assert!(matches("INTERVAL '1 day' < INTERVAL '25 hours'"));
+        assert!(matches!(
+            eval_cond(
+                &table,
+                &row,
+                &cond("TIME '12:00' = TIMESTAMP '2024-01-31 12:00'")
+            ),
+            Err(Error::Type(_))
+        ));
+    }
+
+    #[test]
+    fn test_eval_json() {
+        let table = Table::new(
+            String::from("doc"),
+            vec![(String::from("body"), table::DataType::Json)],
+            vec![],
+        );
+        let json = |s| Json::parse(s).unwrap();
+        let row = Record::new(vec![table::Value::Json(json(
+            r#"{"name": "alice", "age": 30, "tags": ["a", "b"], "admin": true, "boss": null}"#,
+        ))]);
+        let input = |input: &str| {
+            let Expr::Binary(left, _, _) = cond(&format!("{} = 0", input)) else {
+                unreachable!()
+            };
+            *left
+        };
+        let eval = |s: &str| eval_value(&table, &row, &input(s));
+        let matches = |s: &str| eval_cond(&table, &row, &cond(s)).unwrap();
+
+        assert_eq!(
+            eval("body -> 'tags'").unwrap(),
+            table::Value::Json(json(r#"["a", "b"]"#))
+        );
+        assert_eq!(
+            eval("body -> 'tags' ->> -1").unwrap(),
+            table::Value::VarChar(String::from("b"))
+        );
+        assert_eq!(
+            eval("body ->> 'tags'").unwrap(),
+            table::Value::VarChar(String::from("[\"a\", \"b\"]"))
+        );
+        assert_eq!(
+            eval("body -> 'boss'").unwrap(),
+            table::Value::Json(Json::Null)
+        );
+        assert_eq!(eval("body ->> 'boss'").unwrap(), table::Value::Null);
+        assert_eq!(eval("body -> 'missing'").unwrap(), table::Value::Null);
+        assert_eq!(eval("body -> 0").unwrap(), table::Value::Null);
+        assert_eq!(
+            data_type_of(&table, &input("body -> 'tags' -> 0")).unwrap(),
+            DataType::Json
+        );
+        assert_eq!(
+            data_type_of(&table, &input("body ->> 'name'")).unwrap(),
+            DataType::Text
+        );
+        assert!(matches!(
+            data_type_of(&table, &input("body -> TRUE")),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(eval("'x' -> 'a'"), Err(Error::Type(_))));
+        assert!(matches!(eval("JSON '{'"), Err(Error::Type(_))));
+
+        assert!(matches("body ->> 'name' = 'alice'"));
+        assert!(matches("body -> 'age' > 29"));
+        assert!(matches("body -> 'name' = 'alice'"));
+        assert!(matches("body -> 'admin' = TRUE"));
+        assert!(matches("body -> 'tags' = JSON '[\"a\", \"b\"]'"));
+        assert!(!matches("body -> 'tags' = 'a'"));
+        assert!(!matches("body -> 'boss' = 0"));
+        assert!(matches("body -> 'tags' <> 'a'"));
+        assert!(matches!(
+            eval_cond(&table, &row, &cond("body -> 'tags' < 'a'")),
+            Err(Error::Type(_))
+        ));
+        assert!(matches("body -> 'missing' IS NULL"));
+    }
+
+    #[test]
+    fn test_eval_logic_null() {
+        let table = user_table();
+        let row = Record::new(vec![table::Value::Int(1), table::Value::Null]);
+        let logic = |input| eval_logic(&table, &row, &cond(input)).unwrap();
+
+        assert_eq!(logic("name = 'alice'"), None);
+        assert_eq!(logic("name <> 'alice'"), None);
+        assert_eq!(logic("NOT name = 'alice'"), None);
+        assert_eq!(logic("id = NULL"), None);
+        assert_eq!(logic("name = 'alice' AND id = 2"), Some(false));
+        assert_eq!(logic("name = 'alice' AND id = 1"), None);
+        assert_eq!(logic("name = 'alice' OR id = 1"), Some(true));
+        assert_eq!(logic("name = 'alice' OR id = 2"), None);
+        assert_eq!(logic("name IS NULL"), Some(true));
+        assert_eq!(logic("name IS NOT NULL"), Some(false));
+        assert_eq!(logic("id IS NULL"), Some(false));
+        assert_eq!(logic("NULL IS NULL"), Some(true));
+
+        // UNKNOWN never satisfies a condition
+        assert!(!eval_cond(&table, &row, &cond("NOT name = 'alice'")).unwrap());
+    }
+
+    #[test]
+    fn test_sort_rows() {
+        let table = user_table();
+        let row = |id, name: &str| {
+            Record::new(vec![
+                table::Value::Int(id),
+                table::Value::VarChar(String::from(name)),
+            ])
+        };
+        let mut rows = vec![
+            row(2, "bob"),
+            row(1, "bob"),
+            row(3, "alice"),
+            row(1, "carol"),
+        ];
+
+        sort_rows(
+            &table,
+            &mut rows,
+            &[
+                (Expr::Column(String::from("name")), Order::Desc),
+                (Expr::Column(String::from("id")), Order::Asc),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                row(1, "carol"),
+                row(1, "bob"),
+                row(2, "bob"),
+                row(3, "alice")
+            ]
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    error::{Error, Result},
+    query::ast::{ForeignKey, ReferentialAction},
+};
+
+use super::{
+    constraint::{self, column_indexes, key},
+    storage::RecordId,
+    table::{Record, Table, Value},
+    Executer,
+};
+
+/// guards against ON UPDATE CASCADE chains that never settle
+const MAX_CASCADE_DEPTH: usize = 64;
+
+/// a row, where it is kept and what it becomes: deleted (None) or updated (Some)
+pub type RowChange = (RecordId, Record, Option<Record>);
+
+/// write the changes to the rows of `table`, checking the updated rows against
+/// its constraints and running the ON DELETE / ON UPDATE actions of the foreign keys
+/// referring to it
+pub fn apply(executer: &mut Executer, table: &Table, changes: Vec<RowChange>) -> Result<()> {
+    apply_at(executer, table, changes, 0)
+}
+
+fn apply_at(
+    executer: &mut Executer,
+    table: &Table,
+    changes: Vec<RowChange>,
+    depth: usize,
+) -> Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    if depth > MAX_CASCADE_DEPTH {
+        return Err(Error::Invalid(String::from(
+            "foreign key actions are nested too deeply",
+        )));
+    }
+
+    let mut updated = Vec::new();
+    let mut deleted = Vec::new();
+    for (id, _, new) in changes.iter() {
+        match new {
+            Some(new) => updated.push((*id, new.clone())),
+            None => deleted.push(*id),
+        }
+    }
+    let rows = updated
+        .iter()
+        .map(|(_, row)| row.clone())
+        .collect::<Vec<_>>();
+    constraint::validate(table, &rows)?;
+    executer.update_rows(&table.name, &updated)?;
+    executer.delete_rows(&table.name, &deleted)?;
+    constraint::validate_keys(table, &rows, executer.rows(&table.name)?)?;
+    check_references(executer, table, &rows)?;
+    propagate(executer, table, &changes, depth)
+}
+
+/// every one of `rows` in `table` must match a row of each table it references,
+/// unless one of the referencing columns is NULL
+pub fn check_references(executer: &Executer, table: &Table, rows: &[Record]) -> Result<()> {
+    for foreign_key in table.foreign_keys() {
+        let indexes = column_indexes(table, &foreign_key.columns)?;
+        let mut missing = rows
+            .iter()
+            .filter_map(|row| key(row, &indexes))
+            .collect::<BTreeSet<_>>();
+        if missing.is_empty() {
+            continue;
+        }
+        let parent = executer.schema(&foreign_key.ref_table)?;
+        let ref_indexes = column_indexes(&parent, &foreign_key.ref_columns)?;
+        for row in executer.rows(&parent.name)? {
+            if let Some(key) = key(&row?, &ref_indexes) {
+                missing.remove(&key);
+            }
+            if missing.is_empty() {
+                break;
+            }
+        }
+        let first_missing = rows
+            .iter()
+            .filter_map(|row| key(row, &indexes))
+            .find(|key| missing.contains(key));
+        if let Some(key) = first_missing {
+            return Err(Error::Constraint(format!(
+                "insert or update on {} violates foreign key constraint: key ({})=({}) is not present in {}",
+                table.name,
+                foreign_key.columns.join(", "),
+                constraint::format_values(&key),
+                foreign_key.ref_table
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// run the ON DELETE / ON UPDATE actions of the foreign keys referring to `table`,
+/// whose rows have already been changed as described by `changes`
+fn propagate(
+    executer: &mut Executer,
+    table: &Table,
+    changes: &[RowChange],
+    depth: usize,
+) -> Result<()> {
+    for (child_name, foreign_key) in referencing(executer, &table.name)? {
+        // referenced keys that no longer exist, with the key that replaced them on update
+        let ref_indexes = column_indexes(table, &foreign_key.ref_columns)?;
+        let mut vanished: Vec<(Vec<Value>, Option<Vec<Value>>)> = Vec::new();
+        for (_, old, new) in changes.iter() {
+            let Some(old_key) = key(old, &ref_indexes) else {
+                continue;
+            };
+            if vanished.iter().any(|(key, _)| *key == old_key) {
+                continue;
+            }
+            let new_key = new.as_ref().map(|new| {
+                ref_indexes
+                    .iter()
+                    .map(|idx| new.values[*idx].clone())
+                    .collect()
+            });
+            vanished.push((old_key, new_key));
+        }
+        if !vanished.is_empty() {
+            for row in executer.rows(&table.name)? {
+                if let Some(key) = key(&row?, &ref_indexes) {
+                    vanished.retain(|(old_key, _)| *old_key != key);
+                }
+            }
+        }
+        if vanished.is_empty() {
+            continue;
+        }
+
+        let child = executer.schema(&child_name)?;
+        let indexes = column_indexes(&child, &foreign_key.columns)?;
+        let mut child_changes = Vec::new();
+        for row in executer.scan(&child_name)? {
+            let (id, row) = row?;
+            let Some((old_key, new_key)) = key(&row, &indexes)
+                .and_then(|key| vanished.iter().find(|(old_key, _)| *old_key == key))
+            else {
+                continue;
+            };
+            let action = match new_key {
+                Some(_) => foreign_key.on_update,
+                None => foreign_key.on_delete,
+            };
+            let mut new_row = row.clone();
+            match (action, new_key) {
+                (ReferentialAction::Restrict, _) => {
+                    return Err(Error::Constraint(format!(
+                        "{} on {} violates foreign key constraint of {}: key ({})=({}) is still referenced",
+                        if new_key.is_some() { "update" } else { "delete" },
+                        table.name,
+                        child_name,
+                        foreign_key.ref_columns.join(", "),
+                        constraint::format_values(old_key)
+                    )));
+                }
+                (ReferentialAction::Cascade, None) => {
+                    child_changes.push((id, row, None));
+                    continue;
+                }
+                (ReferentialAction::Cascade, Some(new_key)) => {
+                    for (idx, value) in indexes.iter().zip(new_key.iter()) {
+                        new_row.values[*idx] = value.clone();
+                    }
+                }
+                (ReferentialAction::SetNull, _) => {
+                    for idx in indexes.iter() {
+                        new_row.values[*idx] = Value::Null;
+                    }
+                }
+                (ReferentialAction::SetDefault, _) => {
+                    for idx in indexes.iter() {
+                        new_row.values[*idx] = child.default_value(*idx);
+                    }
+                }
+            }
+            child_changes.push((id, row, Some(new_row)));
+        }
+        apply_at(executer, &child, child_changes, depth + 1)?;
+    }
+    Ok(())
+}
+
+/// every foreign key referring to `table_name`, with the name of the table it belongs to
+fn referencing(executer: &Executer, table_name: &str) -> Result<Vec<(String, ForeignKey)>> {
+    let mut foreign_keys = Vec::new();
+    for schema in executer.schemas()? {
+        for foreign_key in schema.foreign_keys() {
+            if foreign_key.ref_table == table_name {
+                foreign_keys.push((schema.name.clone(), foreign_key.clone()));
+            }
+        }
+    }
+    Ok(foreign_keys)
+}
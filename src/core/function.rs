@@ -0,0 +1,465 @@
+use std::borrow::Cow;
+
+use crate::{
+    error::{Error, Result},
+    query::ast::{DateTimeField, Function},
+};
+
+use super::{
+    datetime::{Date, Timestamp, MICROS_PER_HOUR, MICROS_PER_MINUTE, MICROS_PER_SECOND},
+    decimal::Decimal,
+    json::{Json, PathStep},
+    table::{DataType, Value},
+};
+
+/// the type of `function` applied to arguments of `arg_types`
+pub fn data_type_of(function: Function, arg_types: &[DataType]) -> Result<DataType> {
+    match (function, arg_types) {
+        (Function::Now, []) => Ok(DataType::Timestamp),
+        (Function::Extract(field), [arg_type]) => {
+            check_extract(field, arg_type)?;
+            Ok(match field {
+                DateTimeField::Second | DateTimeField::Epoch => {
+                    DataType::Decimal(Decimal::MAX_PRECISION, 6)
+                }
+                _ => DataType::BigInt,
+            })
+        }
+        (Function::DateTrunc(field), [arg_type]) => {
+            check_date_trunc(field, arg_type)?;
+            Ok(DataType::Timestamp)
+        }
+        (Function::JsonExtract, [_, _]) => {
+            check_json_args(function, arg_types)?;
+            Ok(DataType::Json)
+        }
+        (Function::JsonArrayLength, [_] | [_, _]) => {
+            check_json_args(function, arg_types)?;
+            Ok(DataType::BigInt)
+        }
+        _ => Err(wrong_number_of_arguments(function)),
+    }
+}
+
+/// evaluate `function` on evaluated arguments, NULL if any argument is NULL
+pub fn eval(function: Function, args: &[Value]) -> Result<Value> {
+    if args.contains(&Value::Null) {
+        return Ok(Value::Null);
+    }
+    match (function, args) {
+        // the time of evaluation, in UTC
+        (Function::Now, []) => Ok(Value::Timestamp(Timestamp::now())),
+        (Function::Extract(field), [arg]) => {
+            check_extract(field, &type_of(arg)?)?;
+            Ok(extract(field, arg))
+        }
+        (Function::DateTrunc(field), [arg]) => {
+            check_date_trunc(field, &type_of(arg)?)?;
+            date_trunc(field, arg)
+                .map(Value::Timestamp)
+                .ok_or_else(|| Error::Type(String::from("timestamp out of range")))
+        }
+        // the value at the path, NULL if there is none
+        (Function::JsonExtract, [doc, path]) => {
+            let doc = json_arg(doc)?;
+            Ok(doc
+                .at(&path_arg(path)?)
+                .map(|json| Value::Json(json.clone()))
+                .unwrap_or(Value::Null))
+        }
+        // NULL if the document, or the value at the path, is not an array
+        (Function::JsonArrayLength, [doc, path @ ..]) if path.len() <= 1 => {
+            let doc = json_arg(doc)?;
+            let target = match path {
+                [path] => doc.at(&path_arg(path)?),
+                _ => Some(doc.as_ref()),
+            };
+            Ok(match target {
+                Some(Json::Array(elements)) => Value::BigInt(elements.len() as i64),
+                _ => Value::Null,
+            })
+        }
+        _ => Err(wrong_number_of_arguments(function)),
+    }
+}
+
+/// a JSON document, or a string holding one, followed by paths
+fn check_json_args(function: Function, arg_types: &[DataType]) -> Result<()> {
+    let is_string =
+        |data_type: &DataType| matches!(data_type, DataType::VarChar(_) | DataType::Text);
+    let (doc, paths) = arg_types.split_first().expect("checked by the caller");
+    if !(*doc == DataType::Json || is_string(doc)) || !paths.iter().all(is_string) {
+        return Err(Error::Type(format!(
+            "{}() requires a JSON document and a path, got {:?}",
+            function, arg_types
+        )));
+    }
+    Ok(())
+}
+
+fn json_arg(value: &Value) -> Result<Cow<'_, Json>> {
+    match value {
+        Value::Json(json) => Ok(Cow::Borrowed(json)),
+        Value::VarChar(s) => Json::parse(s)
+            .map(Cow::Owned)
+            .ok_or_else(|| Error::Type(format!("invalid input for type JSON: '{}'", s))),
+        _ => Err(Error::Type(format!(
+            "a JSON document is required, got {:?}",
+            value
+        ))),
+    }
+}
+
+fn path_arg(value: &Value) -> Result<Vec<PathStep>> {
+    match value {
+        Value::VarChar(s) => {
+            Json::parse_path(s).ok_or_else(|| Error::Invalid(format!("invalid JSON path: '{}'", s)))
+        }
+        _ => Err(Error::Type(format!(
+            "a JSON path is required, got {:?}",
+            value
+        ))),
+    }
+}
+
+fn wrong_number_of_arguments(function: Function) -> Error {
+    Error::Invalid(format!("wrong number of arguments to {}()", function))
+}
+
+/// the type of a date or time value
+fn type_of(value: &Value) -> Result<DataType> {
+    match value {
+        Value::Date(_) => Ok(DataType::Date),
+        Value::Time(_) => Ok(DataType::Time),
+        Value::Timestamp(_) => Ok(DataType::Timestamp),
+        Value::Interval(_) => Ok(DataType::Interval),
+        _ => Err(Error::Type(format!(
+            "a date or time is required, got {:?}",
+            value
+        ))),
+    }
+}
+
+/// dates have no time of day, times have no date, and intervals are not on a calendar
+fn check_extract(field: DateTimeField, data_type: &DataType) -> Result<()> {
+    let supported = match data_type {
+        DataType::Timestamp => true,
+        DataType::Date => !matches!(
+            field,
+            DateTimeField::Hour | DateTimeField::Minute | DateTimeField::Second
+        ),
+        DataType::Time => matches!(
+            field,
+            DateTimeField::Hour
+                | DateTimeField::Minute
+                | DateTimeField::Second
+                | DateTimeField::Epoch
+        ),
+        DataType::Interval => !matches!(
+            field,
+            DateTimeField::Week | DateTimeField::DayOfWeek | DateTimeField::DayOfYear
+        ),
+        _ => false,
+    };
+    if !supported {
+        return Err(Error::Type(format!(
+            "EXTRACT({} FROM ...) is not supported for {:?}",
+            field, data_type
+        )));
+    }
+    Ok(())
+}
+
+fn check_date_trunc(field: DateTimeField, data_type: &DataType) -> Result<()> {
+    if !matches!(data_type, DataType::Date | DataType::Timestamp) {
+        return Err(Error::Type(format!(
+            "DATE_TRUNC() requires a date or timestamp, got {:?}",
+            data_type
+        )));
+    }
+    if matches!(
+        field,
+        DateTimeField::DayOfWeek | DateTimeField::DayOfYear | DateTimeField::Epoch
+    ) {
+        return Err(Error::Type(format!(
+            "DATE_TRUNC() cannot truncate to {}",
+            field
+        )));
+    }
+    Ok(())
+}
+
+/// integers, except for seconds (with their fraction) and the epoch which are DECIMALs
+fn extract(field: DateTimeField, value: &Value) -> Value {
+    let seconds = |micros: i128| Value::Decimal(Decimal::new(micros, 6));
+    match value {
+        Value::Date(date) => match field {
+            DateTimeField::Epoch => seconds(Timestamp::from(*date).micros() as i128),
+            _ => Value::BigInt(date_field(field, date)),
+        },
+        Value::Time(time) => match field {
+            DateTimeField::Epoch => seconds(time.micros() as i128),
+            _ => time_field(field, time.micros()),
+        },
+        Value::Timestamp(timestamp) => match field {
+            DateTimeField::Epoch => seconds(timestamp.micros() as i128),
+            DateTimeField::Hour | DateTimeField::Minute | DateTimeField::Second => {
+                time_field(field, timestamp.time().micros())
+            }
+            _ => Value::BigInt(date_field(field, &timestamp.date())),
+        },
+        // each field is taken from its own part: EXTRACT(HOUR FROM INTERVAL '1 day 30 hours') is 30
+        Value::Interval(interval) => match field {
+            DateTimeField::Year => Value::BigInt(interval.months() as i64 / 12),
+            DateTimeField::Quarter => Value::BigInt((interval.months() as i64 % 12) / 3 + 1),
+            DateTimeField::Month => Value::BigInt(interval.months() as i64 % 12),
+            DateTimeField::Day => Value::BigInt(interval.days() as i64),
+            DateTimeField::Epoch => seconds(interval.total_micros()),
+            _ => time_field(field, interval.micros()),
+        },
+        _ => unreachable!("checked by check_extract"),
+    }
+}
+
+fn date_field(field: DateTimeField, date: &Date) -> i64 {
+    let (year, month, day) = date.ymd();
+    match field {
+        DateTimeField::Year => year,
+        DateTimeField::Quarter => (month - 1) / 3 + 1,
+        DateTimeField::Month => month,
+        DateTimeField::Week => date.iso_week(),
+        DateTimeField::Day => day,
+        DateTimeField::DayOfWeek => date.weekday(),
+        DateTimeField::DayOfYear => date.ordinal(),
+        _ => unreachable!("not a field of a date"),
+    }
+}
+
+fn time_field(field: DateTimeField, micros: i64) -> Value {
+    match field {
+        DateTimeField::Hour => Value::BigInt(micros / MICROS_PER_HOUR),
+        DateTimeField::Minute => Value::BigInt(micros % MICROS_PER_HOUR / MICROS_PER_MINUTE),
+        DateTimeField::Second => {
+            Value::Decimal(Decimal::new((micros % MICROS_PER_MINUTE) as i128, 6))
+        }
+        _ => unreachable!("not a field of a time"),
+    }
+}
+
+/// the start of the period `value` is in, weeks starting on Monday
+fn date_trunc(field: DateTimeField, value: &Value) -> Option<Timestamp> {
+    let timestamp = match value {
+        Value::Date(date) => Timestamp::from(*date),
+        Value::Timestamp(timestamp) => *timestamp,
+        _ => unreachable!("checked by check_date_trunc"),
+    };
+    let date = timestamp.date();
+    let (year, month, _) = date.ymd();
+    let truncate = |unit: i64| {
+        let micros = timestamp.micros();
+        Timestamp::from_micros(micros - micros.rem_euclid(unit))
+    };
+    match field {
+        DateTimeField::Year => Some(Timestamp::from(Date::from_ymd(year, 1, 1)?)),
+        DateTimeField::Quarter => Some(Timestamp::from(Date::from_ymd(
+            year,
+            (month - 1) / 3 * 3 + 1,
+            1,
+        )?)),
+        DateTimeField::Month => Some(Timestamp::from(Date::from_ymd(year, month, 1)?)),
+        DateTimeField::Week => Some(Timestamp::from(date.week_start()?)),
+        DateTimeField::Day => Some(Timestamp::from(date)),
+        DateTimeField::Hour => truncate(MICROS_PER_HOUR),
+        DateTimeField::Minute => truncate(MICROS_PER_MINUTE),
+        DateTimeField::Second => truncate(MICROS_PER_SECOND),
+        _ => unreachable!("checked by check_date_trunc"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn timestamp(s: &str) -> Value {
+        Value::Timestamp(Timestamp::parse(s).unwrap())
+    }
+
+    #[test]
+    fn test_extract() {
+        let extract =
+            |field, value: &Value| eval(Function::Extract(field), std::slice::from_ref(value));
+        let ts = timestamp("2026-10-18 04:05:06.5");
+
+        assert_eq!(
+            extract(DateTimeField::Year, &ts).unwrap(),
+            Value::BigInt(2026)
+        );
+        assert_eq!(
+            extract(DateTimeField::Quarter, &ts).unwrap(),
+            Value::BigInt(4)
+        );
+        assert_eq!(
+            extract(DateTimeField::Week, &ts).unwrap(),
+            Value::BigInt(42)
+        );
+        assert_eq!(
+            extract(DateTimeField::DayOfWeek, &ts).unwrap(),
+            Value::BigInt(0)
+        );
+        assert_eq!(extract(DateTimeField::Hour, &ts).unwrap(), Value::BigInt(4));
+        assert_eq!(
+            extract(DateTimeField::Second, &ts).unwrap(),
+            Value::Decimal(Decimal::new(6_500_000, 6))
+        );
+        assert_eq!(
+            extract(DateTimeField::Epoch, &timestamp("1970-01-02")).unwrap(),
+            Value::Decimal(Decimal::new(86400, 0))
+        );
+
+        let interval = Value::Interval(
+            crate::core::datetime::Interval::parse("1 year 14 months 3 days 30:00:00").unwrap(),
+        );
+        assert_eq!(
+            extract(DateTimeField::Month, &interval).unwrap(),
+            Value::BigInt(2)
+        );
+        assert_eq!(
+            extract(DateTimeField::Hour, &interval).unwrap(),
+            Value::BigInt(30)
+        );
+
+        let date = Value::Date(Date::parse("2026-10-18").unwrap());
+        assert_eq!(
+            extract(DateTimeField::DayOfYear, &date).unwrap(),
+            Value::BigInt(291)
+        );
+        assert!(matches!(
+            extract(DateTimeField::Hour, &date),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            extract(DateTimeField::Year, &Value::Int(1)),
+            Err(Error::Type(_))
+        ));
+        assert_eq!(
+            extract(DateTimeField::Year, &Value::Null).unwrap(),
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn test_json_functions() {
+        let doc = Value::Json(Json::parse(r#"{"a": {"b": [1, 2, 3]}, "s": "x"}"#).unwrap());
+        let string = |s: &str| Value::VarChar(s.to_string());
+
+        assert_eq!(
+            eval(Function::JsonExtract, &[doc.clone(), string("$.a.b[1]")]).unwrap(),
+            Value::Json(Json::Number(2.0))
+        );
+        assert_eq!(
+            eval(Function::JsonExtract, &[doc.clone(), string("$.a.c")]).unwrap(),
+            Value::Null
+        );
+        // a string holding a document is accepted
+        assert_eq!(
+            eval(
+                Function::JsonExtract,
+                &[string(r#"{"a": 1}"#), string("$.a")]
+            )
+            .unwrap(),
+            Value::Json(Json::Number(1.0))
+        );
+        assert_eq!(
+            eval(Function::JsonArrayLength, &[doc.clone(), string("$.a.b")]).unwrap(),
+            Value::BigInt(3)
+        );
+        assert_eq!(
+            eval(Function::JsonArrayLength, std::slice::from_ref(&doc)).unwrap(),
+            Value::Null
+        );
+        assert_eq!(
+            eval(Function::JsonArrayLength, &[string("[]")]).unwrap(),
+            Value::BigInt(0)
+        );
+        assert_eq!(
+            eval(Function::JsonExtract, &[Value::Null, string("$")]).unwrap(),
+            Value::Null
+        );
+        assert!(matches!(
+            eval(Function::JsonExtract, &[doc.clone(), string("a.b")]),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            eval(Function::JsonExtract, &[string("{"), string("$")]),
+            Err(Error::Type(_))
+        ));
+
+        assert_eq!(
+            data_type_of(
+                Function::JsonExtract,
+                &[DataType::Json, DataType::VarChar(4)]
+            )
+            .unwrap(),
+            DataType::Json
+        );
+        assert_eq!(
+            data_type_of(Function::JsonArrayLength, &[DataType::Text]).unwrap(),
+            DataType::BigInt
+        );
+        assert!(matches!(
+            data_type_of(Function::JsonExtract, &[DataType::Int, DataType::Text]),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            data_type_of(Function::JsonExtract, &[DataType::Json]),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_date_trunc() {
+        let date_trunc = |field, value: &Value| {
+            eval(Function::DateTrunc(field), std::slice::from_ref(value)).unwrap()
+        };
+        let ts = timestamp("2026-10-18 04:05:06.5");
+
+        assert_eq!(
+            date_trunc(DateTimeField::Year, &ts),
+            timestamp("2026-01-01")
+        );
+        assert_eq!(
+            date_trunc(DateTimeField::Quarter, &ts),
+            timestamp("2026-10-01")
+        );
+        assert_eq!(
+            date_trunc(DateTimeField::Week, &ts),
+            timestamp("2026-10-12")
+        );
+        assert_eq!(
+            date_trunc(DateTimeField::Hour, &ts),
+            timestamp("2026-10-18 04:00")
+        );
+        assert_eq!(
+            date_trunc(DateTimeField::Second, &ts),
+            timestamp("2026-10-18 04:05:06")
+        );
+        assert_eq!(
+            date_trunc(
+                DateTimeField::Month,
+                &Value::Date(Date::parse("2026-10-18").unwrap())
+            ),
+            timestamp("2026-10-01")
+        );
+        assert!(matches!(
+            data_type_of(
+                Function::DateTrunc(DateTimeField::Epoch),
+                &[DataType::Timestamp]
+            ),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            data_type_of(Function::DateTrunc(DateTimeField::Day), &[DataType::Time]),
+            Err(Error::Type(_))
+        ));
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{
+    error::{CatalogError, Result},
+    query::ast::{Expr, JoinType},
+};
+
+use super::{
+    expr,
+    table::{Record, Table, Value},
+};
+
+/// nested loop join of two tables
+///
+/// the columns of the result are qualified with the name (or alias) of the table they come from,
+/// and the side without a match is padded with NULLs for outer joins
+pub fn join(left: Table, join_type: JoinType, right: Table, on: Option<&Expr>) -> Result<Table> {
+    let left_columns = qualified_columns(&left);
+    let right_columns = qualified_columns(&right);
+    // a name (or alias) given twice would make its qualified columns ambiguous
+    let right_names = qualifiers(&right_columns);
+    if let Some(name) = qualifiers(&left_columns)
+        .into_iter()
+        .find(|name| right_names.contains(name))
+    {
+        return Err(CatalogError::DuplicateTable(name.to_string()).into());
+    }
+    let columns = left_columns.into_iter().chain(right_columns).collect();
+    // the name can never be referred to as a qualifier
+    let name = format!("{} JOIN {}", left.name, right.name);
+    let mut joined = Table::new(name, columns, vec![]);
+    if let Some(on) = on {
+        expr::check_cond(&joined, on)?;
+    }
+
+    let mut rows = Vec::new();
+    let mut right_matched = vec![false; right.rows.len()];
+    for left_row in left.rows.iter() {
+        let mut matched = false;
+        for (idx, right_row) in right.rows.iter().enumerate() {
+            let row = concat(&left_row.values, &right_row.values);
+            let is_match = match on {
+                Some(on) => expr::eval_cond(&joined, &row, on)?,
+                None => true,
+            };
+            if is_match {
+                matched = true;
+                right_matched[idx] = true;
+                rows.push(row);
+            }
+        }
+        if !matched && matches!(join_type, JoinType::Left | JoinType::Full) {
+            rows.push(concat(
+                &left_row.values,
+                &vec![Value::Null; right.columns.len()],
+            ));
+        }
+    }
+    if matches!(join_type, JoinType::Right | JoinType::Full) {
+        for (right_row, matched) in right.rows.iter().zip(right_matched) {
+            if !matched {
+                rows.push(concat(
+                    &vec![Value::Null; left.columns.len()],
+                    &right_row.values,
+                ));
+            }
+        }
+    }
+
+    joined.rows = rows;
+    Ok(joined)
+}
+
+fn qualified_columns(table: &Table) -> Vec<(String, super::table::DataType)> {
+    table
+        .columns
+        .iter()
+        .map(|(name, data_type)| {
+            // columns of a nested join are already qualified
+            if name.contains('.') {
+                (name.clone(), *data_type)
+            } else {
+                (format!("{}.{}", table.name, name), *data_type)
+            }
+        })
+        .collect()
+}
+
+fn qualifiers(columns: &[(String, super::table::DataType)]) -> Vec<&str> {
+    let mut names: Vec<&str> = columns
+        .iter()
+        .filter_map(|(name, _)| name.split_once('.').map(|(table_name, _)| table_name))
+        .collect();
+    names.dedup();
+    names
+}
+
+fn concat(left: &[Value], right: &[Value]) -> Record {
+    Record::new(left.iter().chain(right.iter()).cloned().collect())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::table::DataType,
+        query::ast::{BinaryOperator, Expr},
+    };
+
+    use super::*;
+
+    fn user_table() -> Table {
+        Table::new(
+            String::from("user"),
+            vec![
+                (String::from("id"), DataType::Int),
+                (String::from("name"), DataType::VarChar(10)),
+            ],
+            vec![
+                Record::new(vec![Value::Int(1), Value::VarChar(String::from("alice"))]),
+                Record::new(vec![Value::Int(2), Value::VarChar(String::from("bob"))]),
+            ],
+        )
+    }
+
+    fn todo_table() -> Table {
+        Table::new(
+            String::from("todo"),
+            vec![
+                (String::from("id"), DataType::Int),
+                (String::from("user_id"), DataType::Int),
+            ],
+            vec![
+                Record::new(vec![Value::Int(1), Value::Int(1)]),
+                Record::new(vec![Value::Int(2), Value::Int(1)]),
+                Record::new(vec![Value::Int(3), Value::Int(3)]),
+            ],
+        )
+    }
+
+    fn on() -> Expr {
+        Expr::Binary(
+            Box::new(Expr::Column(String::from("user.id"))),
+            BinaryOperator::Eq,
+            Box::new(Expr::Column(String::from("user_id"))),
+        )
+    }
+
+    fn row(values: &[Option<i32>], name: Option<&str>) -> Record {
+        let int = |v: &Option<i32>| v.map_or(Value::Null, Value::Int);
+        Record::new(vec![
+            int(&values[0]),
+            name.map_or(Value::Null, |name| Value::VarChar(String::from(name))),
+            int(&values[1]),
+            int(&values[2]),
+        ])
+    }
+
+    #[test]
+    fn test_inner_join() {
+        let joined = join(user_table(), JoinType::Inner, todo_table(), Some(&on())).unwrap();
+        assert_eq!(
+            joined.columns,
+            vec![
+                (String::from("user.id"), DataType::Int),
+                (String::from("user.name"), DataType::VarChar(10)),
+                (String::from("todo.id"), DataType::Int),
+                (String::from("todo.user_id"), DataType::Int),
+            ]
+        );
+        assert_eq!(
+            joined.rows,
+            vec![
+                row(&[Some(1), Some(1), Some(1)], Some("alice")),
+                row(&[Some(1), Some(2), Some(1)], Some("alice")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_outer_join() {
+        let joined = join(user_table(), JoinType::Left, todo_table(), Some(&on())).unwrap();
+        assert_eq!(
+            joined.rows,
+            vec![
+                row(&[Some(1), Some(1), Some(1)], Some("alice")),
+                row(&[Some(1), Some(2), Some(1)], Some("alice")),
+                row(&[Some(2), None, None], Some("bob")),
+            ]
+        );
+
+        let joined = join(user_table(), JoinType::Right, todo_table(), Some(&on())).unwrap();
+        assert_eq!(
+            joined.rows,
+            vec![
+                row(&[Some(1), Some(1), Some(1)], Some("alice")),
+                row(&[Some(1), Some(2), Some(1)], Some("alice")),
+                row(&[None, Some(3), Some(3)], None),
+            ]
+        );
+
+        let joined = join(user_table(), JoinType::Full, todo_table(), Some(&on())).unwrap();
+        assert_eq!(joined.rows.len(), 4);
+    }
+
+    #[test]
+    fn test_cross_join() {
+        let joined = join(user_table(), JoinType::Cross, todo_table(), None).unwrap();
+        assert_eq!(joined.rows.len(), 6);
+        assert_eq!(
+            joined.rows[1],
+            row(&[Some(1), Some(2), Some(1)], Some("alice"))
+        );
+    }
+
+    #[test]
+    fn test_self_join() {
+        assert!(matches!(
+            join(user_table(), JoinType::Cross, user_table(), None),
+            Err(crate::error::Error::Catalog(CatalogError::DuplicateTable(name))) if name == "user"
+        ));
+
+        let mut manager = user_table();
+        manager.name = String::from("manager");
+        let on = Expr::Binary(
+            Box::new(Expr::Column(String::from("user.id"))),
+            BinaryOperator::Lt,
+            Box::new(Expr::Column(String::from("manager.id"))),
+        );
+        let joined = join(user_table(), JoinType::Inner, manager, Some(&on)).unwrap();
+        assert_eq!(joined.columns[2].0, "manager.id");
+        assert_eq!(
+            joined.rows,
+            vec![Record::new(vec![
+                Value::Int(1),
+                Value::VarChar(String::from("alice")),
+                Value::Int(2),
+                Value::VarChar(String::from("bob")),
+            ])]
+        );
+    }
+}
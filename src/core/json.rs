@@ -0,0 +1,510 @@
+use std::{cmp::Ordering, collections::BTreeMap, fmt::Display};
+
+use super::table::compare_f64;
+
+/// a parsed JSON document
+///
+/// values are totally ordered: first by kind (in declaration order), then by value
+#[derive(Debug, Clone)]
+pub enum Json {
+    Null,
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Array(Vec<Json>),
+    // keys sorted and unique like Postgres' jsonb, the last of duplicated keys wins
+    Object(BTreeMap<String, Json>),
+}
+
+/// a step of a path like `$.items[0].name`
+#[derive(Debug, PartialEq, Clone)]
+pub enum PathStep {
+    Key(String),
+    Index(usize),
+}
+
+impl Json {
+    /// the deepest nesting of arrays and objects accepted
+    pub const MAX_DEPTH: usize = 128;
+
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parser = JsonParser {
+            chars: s.chars().collect(),
+            position: 0,
+        };
+        let json = parser.parse_value(0)?;
+        parser.skip_whitespace();
+        if parser.position != parser.chars.len() {
+            return None;
+        }
+        Some(json)
+    }
+
+    /// the value of `key` in an object
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.get(key),
+            _ => None,
+        }
+    }
+
+    /// the element at `index` of an array, counting from the end when negative
+    pub fn index(&self, index: i64) -> Option<&Json> {
+        let Json::Array(elements) = self else {
+            return None;
+        };
+        let index = if index < 0 {
+            elements.len().checked_sub(index.unsigned_abs() as usize)?
+        } else {
+            index as usize
+        };
+        elements.get(index)
+    }
+
+    /// the value at `path`, None if any step is missing
+    pub fn at(&self, path: &[PathStep]) -> Option<&Json> {
+        path.iter().try_fold(self, |json, step| match step {
+            PathStep::Key(key) => json.get(key),
+            PathStep::Index(index) => match json {
+                Json::Array(elements) => elements.get(*index),
+                _ => None,
+            },
+        })
+    }
+
+    /// a string unquoted and anything else serialized, None for null
+    pub fn to_text(&self) -> Option<String> {
+        match self {
+            Json::Null => None,
+            Json::String(s) => Some(s.clone()),
+            json => Some(json.to_string()),
+        }
+    }
+
+    /// parse `$`, followed by any number of `.key`, `."quoted key"` or `[index]`
+    pub fn parse_path(s: &str) -> Option<Vec<PathStep>> {
+        let mut rest = s.trim().strip_prefix('$')?;
+        let mut path = vec![];
+        while !rest.is_empty() {
+            if let Some(quoted) = rest.strip_prefix(".\"") {
+                let end = quoted.find('"')?;
+                path.push(PathStep::Key(quoted[..end].to_string()));
+                rest = &quoted[end + 1..];
+            } else if let Some(key) = rest.strip_prefix('.') {
+                let end = key.find(['.', '[']).unwrap_or(key.len());
+                if end == 0 {
+                    return None;
+                }
+                path.push(PathStep::Key(key[..end].to_string()));
+                rest = &key[end..];
+            } else if let Some(index) = rest.strip_prefix('[') {
+                let end = index.find(']')?;
+                path.push(PathStep::Index(index[..end].trim().parse().ok()?));
+                rest = &index[end + 1..];
+            } else {
+                return None;
+            }
+        }
+        Some(path)
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Json::Null => 0,
+            Json::String(_) => 1,
+            Json::Number(_) => 2,
+            Json::Bool(_) => 3,
+            Json::Array(_) => 4,
+            Json::Object(_) => 5,
+        }
+    }
+}
+
+impl PartialEq for Json {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Json {}
+
+impl PartialOrd for Json {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Json {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Json::String(l), Json::String(r)) => l.cmp(r),
+            (Json::Number(l), Json::Number(r)) => compare_f64(*l, *r),
+            (Json::Bool(l), Json::Bool(r)) => l.cmp(r),
+            (Json::Array(l), Json::Array(r)) => l.cmp(r),
+            (Json::Object(l), Json::Object(r)) => l.cmp(r),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+/// compact, with a space after each `,` and `:` like Postgres' jsonb
+impl Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::String(s) => write_string(f, s),
+            Json::Number(v) => write!(f, "{}", v),
+            Json::Bool(v) => write!(f, "{}", v),
+            Json::Array(elements) => {
+                write!(f, "[")?;
+                for (idx, element) in elements.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(members) => {
+                write!(f, "{{")?;
+                for (idx, (key, value)) in members.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ": {}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut std::fmt::Formatter<'_>, s: &str) -> std::fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// recursive descent over RFC 8259
+struct JsonParser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl JsonParser {
+    fn parse_value(&mut self, depth: usize) -> Option<Json> {
+        self.skip_whitespace();
+        match self.peek()? {
+            '{' => self.parse_object(depth + 1),
+            '[' => self.parse_array(depth + 1),
+            '"' => self.parse_string().map(Json::String),
+            '-' | '0'..='9' => self.parse_number(),
+            _ => self.parse_literal(),
+        }
+    }
+
+    fn parse_object(&mut self, depth: usize) -> Option<Json> {
+        if depth > Json::MAX_DEPTH {
+            return None;
+        }
+        self.position += 1; // skip {
+        let mut members = BTreeMap::new();
+        self.skip_whitespace();
+        if self.eat('}') {
+            return Some(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            if !self.eat(':') {
+                return None;
+            }
+            members.insert(key, self.parse_value(depth)?);
+            self.skip_whitespace();
+            if self.eat('}') {
+                return Some(Json::Object(members));
+            }
+            if !self.eat(',') {
+                return None;
+            }
+        }
+    }
+
+    fn parse_array(&mut self, depth: usize) -> Option<Json> {
+        if depth > Json::MAX_DEPTH {
+            return None;
+        }
+        self.position += 1; // skip [
+        let mut elements = vec![];
+        self.skip_whitespace();
+        if self.eat(']') {
+            return Some(Json::Array(elements));
+        }
+        loop {
+            elements.push(self.parse_value(depth)?);
+            self.skip_whitespace();
+            if self.eat(']') {
+                return Some(Json::Array(elements));
+            }
+            if !self.eat(',') {
+                return None;
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Option<String> {
+        if !self.eat('"') {
+            return None;
+        }
+        let mut s = String::new();
+        loop {
+            let c = self.next()?;
+            match c {
+                '"' => return Some(s),
+                '\\' => s.push(match self.next()? {
+                    '"' => '"',
+                    '\\' => '\\',
+                    '/' => '/',
+                    'b' => '\u{8}',
+                    'f' => '\u{c}',
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'u' => self.parse_unicode_escape()?,
+                    _ => return None,
+                }),
+                c if (c as u32) < 0x20 => return None,
+                c => s.push(c),
+            }
+        }
+    }
+
+    /// the XXXX of \uXXXX, and the low half that must follow a high surrogate
+    fn parse_unicode_escape(&mut self) -> Option<char> {
+        let high = self.parse_hex4()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return char::from_u32(high);
+        }
+        if !(self.eat('\\') && self.eat('u')) {
+            return None;
+        }
+        let low = self.parse_hex4()?;
+        if !(0xdc00..0xe000).contains(&low) {
+            return None;
+        }
+        char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+    }
+
+    fn parse_hex4(&mut self) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..4 {
+            value = value * 16 + self.next()?.to_digit(16)?;
+        }
+        Some(value)
+    }
+
+    /// `-?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)?`
+    fn parse_number(&mut self) -> Option<Json> {
+        let start = self.position;
+        self.eat('-');
+        if !self.eat('0') && self.digits() == 0 {
+            return None;
+        }
+        if self.eat('.') && self.digits() == 0 {
+            return None;
+        }
+        if self.eat('e') || self.eat('E') {
+            if !self.eat('+') {
+                self.eat('-');
+            }
+            if self.digits() == 0 {
+                return None;
+            }
+        }
+        let number = self.chars[start..self.position]
+            .iter()
+            .collect::<String>()
+            .parse::<f64>()
+            .ok()?;
+        // too large for a double
+        if !number.is_finite() {
+            return None;
+        }
+        Some(Json::Number(number))
+    }
+
+    fn parse_literal(&mut self) -> Option<Json> {
+        for (word, json) in [
+            ("null", Json::Null),
+            ("true", Json::Bool(true)),
+            ("false", Json::Bool(false)),
+        ] {
+            let end = self.position + word.len();
+            if self.chars.len() >= end
+                && self.chars[self.position..end]
+                    .iter()
+                    .copied()
+                    .eq(word.chars())
+            {
+                self.position = end;
+                return Some(json);
+            }
+        }
+        None
+    }
+
+    /// skip digits, returning how many there were
+    fn digits(&mut self) -> usize {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+        {
+            self.position += 1;
+        }
+    }
+
+    /// skip `c` if it is next
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn json(s: &str) -> Json {
+        Json::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!(
+            json(r#" { "b" : [1, 2.5, -3e2, true, null], "a": "x\"\u00e9\ud83d\ude00\n" } "#)
+                .to_string(),
+            r#"{"a": "x\"é😀\n", "b": [1, 2.5, -300, true, null]}"#
+        );
+        assert_eq!(json(r#"{"a": 1, "a": 2}"#).to_string(), r#"{"a": 2}"#);
+        assert_eq!(json("[]").to_string(), "[]");
+        assert_eq!(json("\"\"").to_string(), "\"\"");
+        assert_eq!(json("0.000001").to_string(), "0.000001");
+
+        for invalid in [
+            "",
+            "{",
+            "[1,]",
+            "{\"a\" 1}",
+            "{a: 1}",
+            "01",
+            "1.",
+            "-",
+            "1e",
+            "1e999",
+            "tru",
+            "nul",
+            "\"\\x\"",
+            "\"\\ud83d\"",
+            "\"a",
+            "1 2",
+            "'a'",
+        ] {
+            assert_eq!(Json::parse(invalid), None, "{}", invalid);
+        }
+        let deep = format!("{}{}", "[".repeat(200), "]".repeat(200));
+        assert_eq!(Json::parse(&deep), None);
+    }
+
+    #[test]
+    fn test_access() {
+        let doc = json(r#"{"user": {"name": "alice", "tags": ["a", "b", "c"]}, "n": null}"#);
+        assert_eq!(
+            doc.get("user").unwrap().get("name"),
+            Some(&json("\"alice\""))
+        );
+        assert_eq!(doc.get("missing"), None);
+        let tags = doc.get("user").unwrap().get("tags").unwrap();
+        assert_eq!(tags.index(0), Some(&json("\"a\"")));
+        assert_eq!(tags.index(-1), Some(&json("\"c\"")));
+        assert_eq!(tags.index(3), None);
+        assert_eq!(tags.index(-4), None);
+        assert_eq!(doc.index(0), None);
+
+        assert_eq!(json("\"alice\"").to_text(), Some(String::from("alice")));
+        assert_eq!(json("[1, 2]").to_text(), Some(String::from("[1, 2]")));
+        assert_eq!(doc.get("n").unwrap().to_text(), None);
+
+        let path = Json::parse_path(r#"$.user.tags[1]"#).unwrap();
+        assert_eq!(
+            path,
+            vec![
+                PathStep::Key(String::from("user")),
+                PathStep::Key(String::from("tags")),
+                PathStep::Index(1),
+            ]
+        );
+        assert_eq!(doc.at(&path), Some(&json("\"b\"")));
+        assert_eq!(
+            doc.at(&Json::parse_path(r#"$."user".name"#).unwrap()),
+            Some(&json("\"alice\""))
+        );
+        assert_eq!(doc.at(&Json::parse_path("$").unwrap()), Some(&doc));
+        assert_eq!(doc.at(&Json::parse_path("$.user[0]").unwrap()), None);
+        for invalid in ["", "user", "$.", "$..a", "$[x]", "$[-1]", "$[1", "$a"] {
+            assert_eq!(Json::parse_path(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert_eq!(json(r#"{"a": 1, "b": 2}"#), json(r#"{"b": 2.0, "a": 1}"#));
+        let mut values = [
+            json("{}"),
+            json("[1]"),
+            json("true"),
+            json("2"),
+            json("\"a\""),
+            json("null"),
+            json("[]"),
+            json("1"),
+        ];
+        values.sort();
+        assert_eq!(
+            values.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+            vec!["null", "\"a\"", "1", "2", "true", "[]", "[1]", "{}"]
+        );
+    }
+}
@@ -1,231 +1,2476 @@
-mod buffer;
+mod aggregate;
+pub mod buffer;
+mod catalog;
+mod constraint;
+pub mod datetime;
+pub mod decimal;
+mod expr;
+mod foreign_key;
+mod function;
+mod join;
+pub mod json;
+pub mod result;
 pub mod storage;
 pub mod table;
 
-use super::query::ast::{DataType, QueryStatement, Value};
+use super::query::ast::{
+    AlterTableAction, Constraint, DataType, Expr, ForeignKey, QueryStatement, SelectItem,
+    SelectStatement, TableRef, Value,
+};
 
-use self::{buffer::BufferPool, storage::Storage, table::Table};
+use crate::error::{CatalogError, Error, Result};
+
+use self::{
+    catalog::Catalog,
+    decimal::Decimal,
+    result::{QueryResult, ResultSet},
+    storage::{RecordId, Storage, TableScan},
+    table::{Record, Table},
+};
 
 pub struct Executer {
-    buffer: BufferPool,
     pub storage: Storage,
+    catalog: Catalog,
 }
 
 impl Executer {
-    pub fn new(storage_path: String) -> Self {
-        let storage = Storage::new(storage_path);
-        let buffer = BufferPool::new();
-        Self { buffer, storage }
+    /// recovers the storage from the write-ahead log, if it was left by a crash
+    pub fn new(storage_path: String) -> Result<Self> {
+        Self::with_storage(Storage::new(storage_path))
+    }
+
+    /// an executer caching at most `budget` bytes of table pages
+    pub fn with_buffer_budget(storage_path: String, budget: usize) -> Result<Self> {
+        Self::with_storage(Storage::with_buffer_budget(storage_path, budget))
+    }
+
+    fn with_storage(storage: Storage) -> Result<Self> {
+        storage.recover()?;
+        Ok(Self {
+            storage,
+            catalog: Catalog::default(),
+        })
+    }
+
+    /// every statement is a transaction: its changes are kept together once it returns,
+    /// or none of them if it fails
+    pub fn execute(&mut self, stmt: QueryStatement) -> Result<QueryResult> {
+        self.storage.begin();
+        let result = self.execute_statement(stmt);
+        let ended = match result {
+            Ok(_) => self.storage.commit(),
+            Err(_) => self.storage.rollback(),
+        };
+        if result.is_err() || ended.is_err() {
+            // the definitions may have followed changes that were undone
+            self.catalog.reload();
+        }
+        ended?;
+        result
+    }
+
+    fn execute_statement(&mut self, stmt: QueryStatement) -> Result<QueryResult> {
+        match stmt {
+            QueryStatement::CreateTable(table_name, columns, constraints) => {
+                self.create_table(table_name, columns, constraints)
+            }
+            QueryStatement::Select(select) => self.select(select),
+            QueryStatement::Update(table_name, set, cond) => self.update(table_name, set, cond),
+            QueryStatement::Insert(table_name, columns, values) => {
+                self.insert(table_name, columns, values)
+            }
+            QueryStatement::Delete(table_name, cond) => self.delete(table_name, cond),
+            QueryStatement::AlterTable(table_name, action) => self.alter_table(table_name, action),
+            QueryStatement::DropTable(table_name, if_exists) => {
+                self.drop_table(table_name, if_exists)
+            }
+            QueryStatement::Truncate(table_name) => self.truncate(table_name),
+            QueryStatement::Exit => Ok(QueryResult::Exit),
+        }
+    }
+
+    fn create_table(
+        &mut self,
+        table_name: String,
+        columns: Vec<(String, DataType)>,
+        constraints: Vec<Constraint>,
+    ) -> Result<QueryResult> {
+        if self.catalog.contains(&self.storage, &table_name)? {
+            return Err(CatalogError::TableExists(table_name).into());
+        }
+        for (idx, (name, _)) in columns.iter().enumerate() {
+            if columns[..idx].iter().any(|(other, _)| other == name) {
+                return Err(CatalogError::DuplicateColumn(name.clone()).into());
+            }
+        }
+
+        let columns = columns
+            .iter()
+            .map(|(name, data_type)| (name.clone(), Self::to_table_data_type(data_type)))
+            .collect();
+        let mut table = Table::new(table_name, columns, vec![]);
+        // keys first, so that a foreign key can refer to the table itself
+        let (foreign_keys, constraints): (Vec<_>, Vec<_>) = constraints
+            .iter()
+            .partition(|constraint| matches!(constraint, Constraint::ForeignKey(_)));
+        let constraints = constraints
+            .into_iter()
+            .map(|constraint| self.to_table_constraint(&table, constraint))
+            .collect::<Result<Vec<_>>>()?;
+        let primary_keys = constraints
+            .iter()
+            .filter(|constraint| matches!(constraint, table::Constraint::PrimaryKey(_)))
+            .count();
+        if primary_keys > 1 {
+            return Err(Error::Invalid(format!(
+                "multiple primary keys for table {} are not allowed",
+                table.name
+            )));
+        }
+        table.constraints = constraints;
+        for foreign_key in foreign_keys {
+            let constraint = self.to_table_constraint(&table, foreign_key)?;
+            table.constraints.push(constraint);
+        }
+        self.catalog.create(&self.storage, &table)?;
+        Ok(QueryResult::Affected(0))
+    }
+
+    fn select(&mut self, select: SelectStatement) -> Result<QueryResult> {
+        let SelectStatement {
+            from,
+            projections,
+            cond,
+            group_by,
+            having,
+            order_by,
+            limit,
+            offset,
+        } = select;
+
+        // filter by where
+        if let Some(cond) = cond.as_ref() {
+            Self::check_where(cond)?;
+        }
+        let mut relation = self.relation(&from, cond.as_ref())?;
+        let mut rows = std::mem::take(&mut relation.rows);
+        let table = &relation;
+
+        // output column aliases can be referred to from having and order by
+        let resolve_alias = |expr: Expr| Self::resolve_aliases(table, &projections, expr);
+        let mut having = having.map(resolve_alias);
+        let mut order_by = order_by
+            .into_iter()
+            .map(|(key, order)| (resolve_alias(key), order))
+            .collect::<Vec<_>>();
+        let mut projections = projections;
+
+        // group by, aggregate
+        let is_aggregate = !group_by.is_empty()
+            || having.is_some()
+            || projections.iter().any(|item| match item {
+                SelectItem::Expr(expr, _) => expr.has_aggregate(),
+                SelectItem::Wildcard => false,
+            })
+            || order_by.iter().any(|(key, _)| key.has_aggregate());
+        let aggregated;
+        let table = if is_aggregate {
+            let mut aggregates = Vec::new();
+            for item in projections.iter() {
+                match item {
+                    SelectItem::Expr(expr, _) => {
+                        aggregate::collect_aggregates(expr, &mut aggregates)
+                    }
+                    SelectItem::Wildcard => {
+                        return Err(Error::Invalid(String::from(
+                            "* is not allowed in an aggregate query",
+                        )));
+                    }
+                }
+            }
+            if let Some(having) = having.as_ref() {
+                aggregate::collect_aggregates(having, &mut aggregates);
+            }
+            for (key, _) in order_by.iter() {
+                aggregate::collect_aggregates(key, &mut aggregates);
+            }
+
+            aggregated = aggregate::aggregate(table, &rows, &group_by, &aggregates)?;
+            rows = aggregated.rows.clone();
+
+            // refer to the aggregated columns from here on
+            let rewritten = (|| -> Result<_> {
+                let mut new_projections = Vec::new();
+                for item in projections.iter() {
+                    if let SelectItem::Expr(expr, alias) = item {
+                        // keep the original expression text as the column name
+                        let alias = alias.clone().or_else(|| Some(expr.to_string()));
+                        let expr = aggregate::rewrite(expr, &group_by)?;
+                        new_projections.push(SelectItem::Expr(expr, alias));
+                    }
+                }
+                let having = match having.as_ref() {
+                    Some(having) => Some(aggregate::rewrite(having, &group_by)?),
+                    None => None,
+                };
+                let mut new_order_by = Vec::new();
+                for (key, order) in order_by.iter() {
+                    new_order_by.push((aggregate::rewrite(key, &group_by)?, *order));
+                }
+                Ok((new_projections, having, new_order_by))
+            })();
+            (projections, having, order_by) = rewritten?;
+            &aggregated
+        } else {
+            table
+        };
+
+        // filter by having
+        if let Some(having) = having {
+            rows = expr::filter_rows(table, rows, &having)?;
+        }
+
+        // order by
+        if !order_by.is_empty() {
+            expr::sort_rows(table, &mut rows, &order_by)?;
+        }
+
+        // offset, limit
+        let rows = rows
+            .into_iter()
+            .skip(offset.unwrap_or(0))
+            .take(limit.unwrap_or(usize::MAX));
+
+        // projection
+        let mut columns = Vec::new();
+        for item in projections.iter() {
+            match item {
+                SelectItem::Wildcard => columns.extend(table.columns.iter().cloned()),
+                SelectItem::Expr(expr, alias) => columns.push((
+                    alias.clone().unwrap_or_else(|| expr.to_string()),
+                    expr::data_type_of(table, expr)?,
+                )),
+            }
+        }
+        let mut records = Vec::new();
+        for row in rows {
+            let mut values = Vec::new();
+            for item in projections.iter() {
+                match item {
+                    SelectItem::Wildcard => values.extend(row.values.iter().cloned()),
+                    SelectItem::Expr(expr, _) => values.push(expr::eval_value(table, &row, expr)?),
+                }
+            }
+            records.push(Record::new(values));
+        }
+
+        Ok(QueryResult::Rows(ResultSet::new(columns, records)))
     }
 
-    /// return value means whether to continue the repl
-    /// if return false, then exits
-    pub fn execute(&mut self, query: Vec<QueryStatement>) -> bool {
-        for stmt in query.iter() {
-            match stmt {
-                QueryStatement::CreateTable(table_name, columns) => {
-                    self.create_table(table_name.clone(), columns.clone())
+    /// materialize the from clause into a single table of the rows matching `cond`,
+    /// a table being filtered while it is read
+    fn relation(&self, from: &TableRef, cond: Option<&Expr>) -> Result<Table> {
+        match from {
+            TableRef::Table(table_name, alias) => {
+                let mut table = self.schema(table_name)?;
+                if let Some(alias) = alias {
+                    table.name = alias.clone();
                 }
-                QueryStatement::Select(table_name, is_all, column, cond) => {
-                    self.select(table_name.clone(), *is_all, column.clone(), cond.clone())
+                if let Some(cond) = cond {
+                    // report unknown columns even when there is nothing to filter
+                    expr::check_cond(&table, cond)?;
                 }
-                QueryStatement::Update(table_name, set, cond) => {
-                    self.update(table_name.clone(), set.clone(), cond.clone())
+                for row in self.rows(table_name)? {
+                    let row = row?;
+                    let is_match = match cond {
+                        Some(cond) => expr::eval_cond(&table, &row, cond)?,
+                        None => true,
+                    };
+                    if is_match {
+                        table.rows.push(row);
+                    }
                 }
-                QueryStatement::Exit => {
-                    println!("bye!");
-                    return false;
+                Ok(table)
+            }
+            TableRef::Join(left, join_type, right, on) => {
+                let mut table = join::join(
+                    self.relation(left, None)?,
+                    *join_type,
+                    self.relation(right, None)?,
+                    on.as_ref(),
+                )?;
+                if let Some(cond) = cond {
+                    let rows = std::mem::take(&mut table.rows);
+                    table.rows = expr::filter_rows(&table, rows, cond)?;
                 }
+                Ok(table)
             }
         }
-        true
     }
 
-    fn create_table(&mut self, table_name: String, columns: Vec<(String, DataType)>) {
-        let columns = columns
-            .iter()
-            .map(|(name, data_type)| {
-                (
-                    name.clone(),
-                    match data_type {
-                        DataType::Int => table::DataType::Int,
-                        DataType::VarChar(size) => table::DataType::VarChar(*size),
-                    },
-                )
-            })
-            .collect();
-        let table = Table::new(table_name, columns, vec![]);
-        self.storage.flush(&table);
-        self.buffer.body.push(table);
+    /// replace references to output column aliases with the aliased expressions
+    fn resolve_aliases(table: &Table, projections: &[SelectItem], expr: Expr) -> Expr {
+        match expr {
+            Expr::Column(name) if table.column_index(&name).is_none() => projections
+                .iter()
+                .find_map(|item| match item {
+                    SelectItem::Expr(expr, Some(alias)) if alias == &name => Some(expr.clone()),
+                    _ => None,
+                })
+                .unwrap_or(Expr::Column(name)),
+            Expr::Binary(left, operator, right) => Expr::Binary(
+                Box::new(Self::resolve_aliases(table, projections, *left)),
+                operator,
+                Box::new(Self::resolve_aliases(table, projections, *right)),
+            ),
+            Expr::Not(expr) => {
+                Expr::Not(Box::new(Self::resolve_aliases(table, projections, *expr)))
+            }
+            Expr::Neg(expr) => {
+                Expr::Neg(Box::new(Self::resolve_aliases(table, projections, *expr)))
+            }
+            Expr::IsNull(expr, negated) => Expr::IsNull(
+                Box::new(Self::resolve_aliases(table, projections, *expr)),
+                negated,
+            ),
+            Expr::Function(function, args) => Expr::Function(
+                function,
+                args.into_iter()
+                    .map(|arg| Self::resolve_aliases(table, projections, arg))
+                    .collect(),
+            ),
+            _ => expr,
+        }
+    }
+
+    fn check_where(cond: &Expr) -> Result<()> {
+        if cond.has_aggregate() {
+            return Err(Error::Invalid(String::from(
+                "aggregate functions are not allowed in WHERE",
+            )));
+        }
+        Ok(())
     }
 
-    fn select(
+    fn update(
         &mut self,
         table_name: String,
-        is_all: bool,
-        columns: Vec<String>,
-        cond: Option<(String, Value)>,
-    ) {
-        let binding = self
-            .storage
-            .load(&table_name)
-            .expect("table should be in storage"); // TODO: error handling
-
-        let table = self
-            .buffer
-            .body
-            .iter()
-            .find(|table| table.name == table_name)
-            .unwrap_or(&binding); // TODO: push to buffer
+        set: Vec<(String, Value)>,
+        cond: Option<Expr>,
+    ) -> Result<QueryResult> {
+        let table = self.schema(&table_name)?;
 
-        let mut rows = table.rows.clone();
+        // resolve and type check assignments
+        let mut assignments = Vec::new();
+        for (name, value) in set.iter() {
+            let idx = table.resolve_column(name)?;
+            let data_type = &table.columns[idx].1;
+            assignments.push((idx, Self::to_table_value(name, value, data_type)?));
+        }
 
-        // filter by where
-        if let Some((key_name, value)) = cond {
-            let key_idx = table
-                .columns
-                .iter()
-                .position(|(name, _)| name == &key_name)
-                .expect("key_name should be in columns");
-
-            rows.retain(|row| {
-                let _value = &row.values[key_idx];
-                match &value {
-                    Value::Int(v) => {
-                        if let table::Value::Int(cond_value) = _value {
-                            v == cond_value
-                        } else {
-                            false
-                        }
+        if let Some(cond) = cond.as_ref() {
+            Self::check_where(cond)?;
+            expr::check_cond(&table, cond)?;
+        }
+        // only the matching rows are kept, to be written back in place
+        let mut changes = Vec::new();
+        for row in self.scan(&table_name)? {
+            let (id, row) = row?;
+            let is_match = match cond.as_ref() {
+                Some(cond) => expr::eval_cond(&table, &row, cond)?,
+                None => true,
+            };
+            if is_match {
+                let mut new_row = row.clone();
+                for (idx, value) in assignments.iter() {
+                    new_row.values[*idx] = value.clone();
+                }
+                changes.push((id, row, Some(new_row)));
+            }
+        }
+        let affected = changes.len();
+
+        // sync
+        foreign_key::apply(self, &table, changes)?;
+        Ok(QueryResult::Affected(affected))
+    }
+
+    fn insert(
+        &mut self,
+        table_name: String,
+        columns: Vec<String>,
+        values: Vec<Vec<Value>>,
+    ) -> Result<QueryResult> {
+        let table = self.schema(&table_name)?;
+
+        // resolve target column indexes (all columns in table order when omitted)
+        let mut column_indexes = Vec::new();
+        if columns.is_empty() {
+            column_indexes = (0..table.columns.len()).collect();
+        } else {
+            for name in columns.iter() {
+                match table
+                    .columns
+                    .iter()
+                    .position(|(column_name, _)| column_name == name)
+                {
+                    Some(idx) if column_indexes.contains(&idx) => {
+                        return Err(CatalogError::DuplicateColumn(name.clone()).into());
                     }
-                    Value::VarChar(v) => {
-                        if let table::Value::VarChar(cond_value) = _value {
-                            v == cond_value
-                        } else {
-                            false
-                        }
+                    Some(idx) => column_indexes.push(idx),
+                    None => {
+                        return Err(CatalogError::UnknownColumn(name.clone()).into());
                     }
                 }
-            });
+            }
+        }
+        // type check every row before touching the table
+        let mut records = Vec::new();
+        for row in values.iter() {
+            if row.len() != column_indexes.len() {
+                return Err(Error::Invalid(format!(
+                    "column count mismatch: expected {}, got {}",
+                    column_indexes.len(),
+                    row.len()
+                )));
+            }
+            // omitted columns take their default
+            let mut record_values = (0..table.columns.len())
+                .map(|idx| table.default_value(idx))
+                .collect::<Vec<_>>();
+            for (value, idx) in row.iter().zip(column_indexes.iter()) {
+                let (column_name, data_type) = &table.columns[*idx];
+                record_values[*idx] = Self::to_table_value(column_name, value, data_type)?;
+            }
+            records.push(Record::new(record_values));
         }
 
-        if !is_all {
-            rows = rows
-                .into_iter()
-                .map(|row| {
-                    let mut new_row = row;
-                    let target_indexes = table
-                        .columns
-                        .iter()
-                        .enumerate()
-                        .filter(|(_, (name, _))| columns.contains(name))
-                        .map(|(idx, _)| idx)
-                        .collect::<Vec<_>>();
-                    new_row.values = new_row
-                        .values
-                        .into_iter()
-                        .enumerate()
-                        .filter(|(idx, _)| target_indexes.contains(idx))
-                        .map(|(_, value)| value)
-                        .collect();
-                    new_row
-                })
-                .collect();
+        let affected = records.len();
+        constraint::validate(&table, &records)?;
+
+        // sync, the keys and references being checked against what is stored then
+        self.insert_rows(&table_name, &records)?;
+        constraint::validate_keys(&table, &records, self.rows(&table_name)?)?;
+        foreign_key::check_references(self, &table, &records)?;
+        Ok(QueryResult::Affected(affected))
+    }
+
+    fn delete(&mut self, table_name: String, cond: Option<Expr>) -> Result<QueryResult> {
+        let table = self.schema(&table_name)?;
+
+        if let Some(cond) = cond.as_ref() {
+            Self::check_where(cond)?;
+            expr::check_cond(&table, cond)?;
         }
+        let mut changes = Vec::new();
+        for row in self.scan(&table_name)? {
+            let (id, row) = row?;
+            let is_match = match cond.as_ref() {
+                Some(cond) => expr::eval_cond(&table, &row, cond)?,
+                None => true,
+            };
+            if is_match {
+                changes.push((id, row, None));
+            }
+        }
+        let affected = changes.len();
+
+        // sync
+        foreign_key::apply(self, &table, changes)?;
+        Ok(QueryResult::Affected(affected))
+    }
+
+    fn alter_table(&mut self, table_name: String, action: AlterTableAction) -> Result<QueryResult> {
+        let mut table = self.schema(&table_name)?;
+        let mut referencing = self.referencing_tables(&table_name)?;
 
-        // print
-        if rows.is_empty() {
-            println!("Empty set");
+        // a new column changes every record, while a new name only changes definitions
+        let rewrite = matches!(
+            action,
+            AlterTableAction::AddColumn(..) | AlterTableAction::DropColumn(_)
+        );
+        match action {
+            AlterTableAction::AddColumn(column_name, data_type) => {
+                if table.column_index(&column_name).is_some() {
+                    return Err(CatalogError::ColumnExists(column_name.clone()).into());
+                }
+                table = self.table(&table_name)?;
+                table.add_column(column_name, Self::to_table_data_type(&data_type));
+            }
+            AlterTableAction::DropColumn(column_name) => {
+                let Some(idx) = table.column_index(&column_name) else {
+                    return Err(CatalogError::UnknownColumn(column_name.clone()).into());
+                };
+                if table.columns.len() == 1 {
+                    return Err(Error::Invalid(format!(
+                        "cannot drop the only column of {}",
+                        table.name
+                    )));
+                }
+                if table
+                    .constraints
+                    .iter()
+                    .any(|constraint| constraint.references_column(&column_name))
+                {
+                    return Err(Error::Invalid(format!(
+                        "cannot drop column {} because a constraint depends on it",
+                        column_name
+                    )));
+                }
+                if let Some(other) = referencing
+                    .iter()
+                    .find(|other| other.references(&table_name, Some(&column_name)))
+                {
+                    return Err(Error::Invalid(format!(
+                        "cannot drop column {} because {} references it",
+                        column_name, other.name
+                    )));
+                }
+                table = self.table(&table_name)?;
+                table.drop_column(idx);
+            }
+            AlterTableAction::RenameColumn(from, to) => {
+                let Some(idx) = table.column_index(&from) else {
+                    return Err(CatalogError::UnknownColumn(from.clone()).into());
+                };
+                if table.column_index(&to).is_some() {
+                    return Err(CatalogError::ColumnExists(to.clone()).into());
+                }
+                for other in referencing.iter_mut() {
+                    other.rename_referenced_column(&table_name, &from, &to);
+                }
+                table.rename_column(idx, to);
+            }
+            AlterTableAction::RenameTo(new_table_name) => {
+                self.catalog
+                    .rename(&self.storage, &table.name, &new_table_name)?;
+                for other in referencing.iter_mut() {
+                    other.rename_referenced_table(&table_name, &new_table_name);
+                }
+                table.rename_referenced_table(&table_name, &new_table_name);
+                table.name = new_table_name;
+            }
+        }
+
+        // sync
+        if rewrite {
+            self.save_table(table)?;
         } else {
-            let mut column_names = table
+            self.catalog.save_schema(&self.storage, &table)?;
+        }
+        for other in referencing {
+            self.catalog.save_schema(&self.storage, &other)?;
+        }
+        Ok(QueryResult::Affected(0))
+    }
+
+    fn drop_table(&mut self, table_name: String, if_exists: bool) -> Result<QueryResult> {
+        if let Some(other) = self.referencing_tables(&table_name)?.first() {
+            return Err(Error::Invalid(format!(
+                "cannot drop table {} because {} references it",
+                table_name, other.name
+            )));
+        }
+        if !self.catalog.drop(&self.storage, &table_name)? {
+            if if_exists {
+                return Ok(QueryResult::Message(format!(
+                    "table {} does not exist, skipping",
+                    table_name
+                )));
+            }
+            return Err(CatalogError::UnknownTable(table_name).into());
+        }
+        Ok(QueryResult::Affected(0))
+    }
+
+    fn truncate(&mut self, table_name: String) -> Result<QueryResult> {
+        if let Some(other) = self.referencing_tables(&table_name)?.first() {
+            return Err(Error::Invalid(format!(
+                "cannot truncate table {} because {} references it",
+                table_name, other.name
+            )));
+        }
+        let table = self.schema(&table_name)?;
+
+        // sync
+        self.save_table(table)?;
+        Ok(QueryResult::Affected(0))
+    }
+
+    // every table is read and written through the catalog
+
+    fn table(&self, table_name: &str) -> Result<Table> {
+        self.catalog.table(&self.storage, table_name)
+    }
+
+    /// the table without its rows
+    fn schema(&self, table_name: &str) -> Result<Table> {
+        self.catalog.schema(&self.storage, table_name)
+    }
+
+    /// the rows of the table with where they are kept, read a page at a time
+    fn scan(&self, table_name: &str) -> Result<TableScan<'_>> {
+        self.catalog.scan(&self.storage, table_name)
+    }
+
+    /// the rows of the table, read a page at a time
+    fn rows(&self, table_name: &str) -> Result<impl Iterator<Item = Result<Record>> + '_> {
+        Ok(self.scan(table_name)?.map(|row| row.map(|(_, row)| row)))
+    }
+
+    /// every table without its rows
+    fn schemas(&self) -> Result<Vec<Table>> {
+        self.catalog.schemas(&self.storage)
+    }
+
+    /// the other tables with a foreign key referring to `table_name`, without their rows
+    fn referencing_tables(&self, table_name: &str) -> Result<Vec<Table>> {
+        Ok(self
+            .schemas()?
+            .into_iter()
+            .filter(|schema| schema.name != table_name && schema.references(table_name, None))
+            .collect())
+    }
+
+    /// write the table with all its rows
+    fn save_table(&mut self, table: Table) -> Result<()> {
+        self.catalog.save(&self.storage, &table)
+    }
+
+    fn insert_rows(&mut self, table_name: &str, rows: &[Record]) -> Result<()> {
+        self.catalog.insert(&self.storage, table_name, rows)
+    }
+
+    fn update_rows(&mut self, table_name: &str, rows: &[(RecordId, Record)]) -> Result<()> {
+        self.catalog.update(&self.storage, table_name, rows)
+    }
+
+    fn delete_rows(&mut self, table_name: &str, ids: &[RecordId]) -> Result<()> {
+        self.catalog.delete(&self.storage, table_name, ids)
+    }
+
+    fn to_table_data_type(data_type: &DataType) -> table::DataType {
+        match data_type {
+            DataType::Boolean => table::DataType::Boolean,
+            DataType::Int => table::DataType::Int,
+            DataType::BigInt => table::DataType::BigInt,
+            DataType::Double => table::DataType::Double,
+            DataType::Decimal(precision, scale) => table::DataType::Decimal(*precision, *scale),
+            DataType::VarChar(size) => table::DataType::VarChar(*size),
+            DataType::Date => table::DataType::Date,
+            DataType::Time => table::DataType::Time,
+            DataType::Timestamp => table::DataType::Timestamp,
+            DataType::Interval => table::DataType::Interval,
+            DataType::Text => table::DataType::Text,
+            DataType::Blob => table::DataType::Blob,
+            DataType::Json => table::DataType::Json,
+        }
+    }
+
+    /// resolve the columns a constraint refers to against the table being created
+    fn to_table_constraint(
+        &self,
+        table: &Table,
+        constraint: &Constraint,
+    ) -> Result<table::Constraint> {
+        let resolve = |name: &String| -> Result<String> {
+            if table
                 .columns
                 .iter()
-                .map(|(name, _)| name.clone())
-                .collect::<Vec<_>>();
-            if !is_all {
-                column_names.retain(|name| columns.contains(name));
-            }
-            let column_names = column_names.join(", ");
-            println!("{}", column_names);
-            for row in rows {
-                let values = row
-                    .values
-                    .into_iter()
-                    .map(|value| match value {
-                        table::Value::Int(v) => v.to_string(),
-                        table::Value::VarChar(v) => v,
-                    })
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                println!("{}", values);
-            }
-        }
-    }
-
-    fn update(&mut self, table_name: String, set: Vec<(String, Value)>, cond: (String, Value)) {
-        let mut binding = self
-            .storage
-            .load(&table_name)
-            .expect("table should be in storage"); // TODO: error handling
-
-        let table = self
-            .buffer
-            .body
-            .iter_mut()
-            .find(|table| table.name == table_name)
-            .unwrap_or(&mut binding);
-
-        let rows = table.rows.clone();
-        let mut new_rows = Vec::new();
-        for row in rows {
-            let mut new_row = row.clone();
-            let key_idx = table
-                .columns
+                .any(|(column_name, _)| column_name == name)
+            {
+                Ok(name.clone())
+            } else {
+                Err(CatalogError::UnknownColumn(name.clone()).into())
+            }
+        };
+        let resolve_all = |names: &[String]| -> Result<Vec<String>> {
+            let mut resolved = Vec::new();
+            for name in names.iter() {
+                if resolved.contains(name) {
+                    return Err(CatalogError::DuplicateColumn(name.clone()).into());
+                }
+                resolved.push(resolve(name)?);
+            }
+            Ok(resolved)
+        };
+        match constraint {
+            Constraint::PrimaryKey(names) => Ok(table::Constraint::PrimaryKey(resolve_all(names)?)),
+            Constraint::Unique(names) => Ok(table::Constraint::Unique(resolve_all(names)?)),
+            Constraint::NotNull(name) => Ok(table::Constraint::NotNull(resolve(name)?)),
+            Constraint::Default(name, value) => {
+                let idx = table.resolve_column(name)?;
+                let data_type = &table.columns[idx].1;
+                let value = Self::to_table_value(name, value, data_type)?;
+                Ok(table::Constraint::Default(name.clone(), value))
+            }
+            Constraint::Check(expr) => {
+                if expr.has_aggregate() {
+                    return Err(Error::Invalid(String::from(
+                        "aggregate functions are not allowed in CHECK",
+                    )));
+                }
+                expr::check_cond(table, expr)?;
+                Ok(table::Constraint::Check(expr.clone()))
+            }
+            Constraint::ForeignKey(foreign_key) => {
+                let columns = resolve_all(&foreign_key.columns)?;
+                let referenced = if foreign_key.ref_table == table.name {
+                    table.clone()
+                } else {
+                    self.schema(&foreign_key.ref_table)?
+                };
+                Ok(table::Constraint::ForeignKey(Self::to_foreign_key(
+                    table,
+                    columns,
+                    &referenced,
+                    foreign_key,
+                )?))
+            }
+        }
+    }
+
+    /// the referenced columns must be the primary key or a unique key of the referenced table,
+    /// which is the primary key when they are omitted
+    fn to_foreign_key(
+        table: &Table,
+        columns: Vec<String>,
+        referenced: &Table,
+        foreign_key: &ForeignKey,
+    ) -> Result<ForeignKey> {
+        let keys = referenced
+            .constraints
+            .iter()
+            .filter_map(|constraint| match constraint {
+                table::Constraint::PrimaryKey(names) | table::Constraint::Unique(names) => {
+                    Some(names)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let ref_columns = if foreign_key.ref_columns.is_empty() {
+            referenced
+                .constraints
                 .iter()
-                .position(|(name, _)| name == &cond.0)
-                .expect("key_name should be in columns");
-            let value = &row.values[key_idx];
-
-            match cond.1 {
-                Value::Int(cond_value) => {
-                    if let table::Value::Int(value) = value {
-                        if value == &cond_value {
-                            for (name, value) in set.iter() {
-                                let idx = table
-                                    .columns
-                                    .iter()
-                                    .position(|(column_name, _)| column_name == name)
-                                    .expect("key_name should be in columns");
-                                if let Value::Int(v) = value {
-                                    new_row.values[idx] = table::Value::Int(*v);
-                                }
-                            }
-                        }
-                    }
+                .find_map(|constraint| match constraint {
+                    table::Constraint::PrimaryKey(names) => Some(names.clone()),
+                    _ => None,
+                })
+                .ok_or_else(|| {
+                    Error::Invalid(format!(
+                        "there is no primary key for referenced table {}",
+                        referenced.name
+                    ))
+                })?
+        } else {
+            foreign_key.ref_columns.clone()
+        };
+        if columns.len() != ref_columns.len() {
+            return Err(Error::Invalid(String::from(
+                "number of referencing and referenced columns for foreign key disagree",
+            )));
+        }
+        for (name, ref_name) in columns.iter().zip(ref_columns.iter()) {
+            let data_type = &table.columns[table.resolve_column(name)?].1;
+            let ref_data_type = &referenced.columns[referenced.resolve_column(ref_name)?].1;
+            if std::mem::discriminant(data_type) != std::mem::discriminant(ref_data_type) {
+                return Err(Error::Type(format!(
+                    "foreign key column {} of type {:?} cannot refer to {}.{} of type {:?}",
+                    name, data_type, referenced.name, ref_name, ref_data_type
+                )));
+            }
+        }
+        let is_key = keys.iter().any(|names| {
+            names.len() == ref_columns.len() && names.iter().all(|name| ref_columns.contains(name))
+        });
+        if !is_key {
+            return Err(Error::Invalid(format!(
+                "there is no unique constraint matching the referenced columns of {}",
+                referenced.name
+            )));
+        }
+
+        Ok(ForeignKey {
+            columns,
+            ref_table: referenced.name.clone(),
+            ref_columns,
+            on_delete: foreign_key.on_delete,
+            on_update: foreign_key.on_update,
+        })
+    }
+
+    /// convert a literal written to `column_name`
+    /// numeric literals are accepted by any numeric column that can represent them (a decimal is
+    /// rounded to the scale of a DECIMAL column, but never truncated into an integer column),
+    /// a DATE literal by a TIMESTAMP column as midnight of that day, and a string by a JSON column
+    /// when it is a valid document;
+    /// otherwise literals are never coerced to another type: an integer is not accepted by a
+    /// VARCHAR column, and neither a string is by an INT or a DATE column
+    fn to_table_value(
+        column_name: &str,
+        value: &Value,
+        data_type: &table::DataType,
+    ) -> Result<table::Value> {
+        match (value, data_type) {
+            (Value::Null, _) => Ok(table::Value::Null),
+            (Value::Int(v), table::DataType::Int) => match i32::try_from(*v) {
+                Ok(v) => Ok(table::Value::Int(v)),
+                Err(_) => Err(Error::Type(format!(
+                    "integer {} out of range for column {} of type INT",
+                    v, column_name
+                ))),
+            },
+            (Value::Int(v), table::DataType::BigInt) => Ok(table::Value::BigInt(*v)),
+            (Value::Int(v), table::DataType::Double) => Ok(table::Value::Double(*v as f64)),
+            (Value::Decimal(v), table::DataType::Double) => match v.parse() {
+                Ok(v) => Ok(table::Value::Double(v)),
+                Err(_) => Err(Error::Type(format!("invalid number: {}", v))),
+            },
+            (Value::Int(_) | Value::Decimal(_), table::DataType::Decimal(precision, scale)) => {
+                let decimal = match value {
+                    Value::Int(v) => Some(Decimal::from(*v)),
+                    Value::Decimal(v) => Decimal::parse(v),
+                    _ => unreachable!(),
+                };
+                match decimal.and_then(|decimal| decimal.fit(*precision, *scale)) {
+                    Some(decimal) => Ok(table::Value::Decimal(decimal)),
+                    None => Err(Error::Type(format!(
+                        "numeric {} out of range for column {} of type DECIMAL({}, {})",
+                        value, column_name, precision, scale
+                    ))),
                 }
-                Value::VarChar(ref cond_value) => {
-                    if let table::Value::VarChar(value) = value {
-                        if value == cond_value {
-                            for (name, value) in set.iter() {
-                                let idx = table
-                                    .columns
-                                    .iter()
-                                    .position(|(column_name, _)| column_name == name)
-                                    .expect("key_name should be in columns");
-                                if let Value::VarChar(v) = value {
-                                    new_row.values[idx] = table::Value::VarChar(v.clone());
-                                }
-                            }
-                        }
-                    }
+            }
+            (Value::Boolean(v), table::DataType::Boolean) => Ok(table::Value::Boolean(*v)),
+            (Value::Date(_), table::DataType::Date | table::DataType::Timestamp)
+            | (Value::Time(_), table::DataType::Time)
+            | (Value::Timestamp(_), table::DataType::Timestamp)
+            | (Value::Interval(_), table::DataType::Interval) => match expr::eval_literal(value)? {
+                table::Value::Date(date) if *data_type == table::DataType::Timestamp => {
+                    Ok(table::Value::Timestamp(date.into()))
                 }
-            };
+                value => Ok(value),
+            },
+            (Value::VarChar(v), table::DataType::VarChar(size)) => {
+                if v.chars().count() > *size as usize {
+                    return Err(Error::Type(format!(
+                        "value too long for column {} of type VARCHAR({})",
+                        column_name, size
+                    )));
+                }
+                Ok(table::Value::VarChar(v.clone()))
+            }
+            (Value::VarChar(v), table::DataType::Text) => Ok(table::Value::VarChar(v.clone())),
+            (Value::Blob(v), table::DataType::Blob) => Ok(table::Value::Blob(v.clone())),
+            (Value::VarChar(v), table::DataType::Json) => {
+                expr::eval_literal(&Value::Json(v.clone()))
+            }
+            (Value::Json(_), table::DataType::Json) => expr::eval_literal(value),
+            _ => Err(Error::Type(format!(
+                "type mismatch: column {} is {:?}, got {}",
+                column_name, data_type, value
+            ))),
+        }
+    }
+}
 
-            new_rows.push(new_row);
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::{
+            datetime::{Date, Interval, Time, Timestamp},
+            json::Json,
+        },
+        query::{
+            lex::Lexer,
+            parser::{ParseError, Parser},
+        },
+    };
+
+    fn setup(name: &str) -> Executer {
+        let dir = std::env::temp_dir().join(format!("ubdb-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        Executer::new(dir.to_str().unwrap().to_string()).unwrap()
+    }
+
+    /// execute every statement in `input`, returning the result of the last one
+    fn try_run(executer: &mut Executer, input: &str) -> Result<QueryResult> {
+        let stmts = Parser::new(Lexer::new(input.to_string())).parse()?;
+        let mut result = Ok(QueryResult::Exit);
+        for stmt in stmts {
+            result = Ok(executer.execute(stmt)?);
         }
-        table.rows = new_rows;
+        result
+    }
 
-        // sync
-        self.storage.flush(table);
+    fn run(executer: &mut Executer, input: &str) -> QueryResult {
+        try_run(executer, input).unwrap()
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut executer = setup("insert");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT, name VARCHAR(10)); INSERT INTO user (name, id) VALUES ('alice', 1), ('bob', 2); INSERT INTO user VALUES (3, 'charlie');",
+        );
+
+        let table = executer.storage.load("user").unwrap().unwrap();
+        assert_eq!(
+            table.rows,
+            vec![
+                Record::new(vec![
+                    table::Value::Int(1),
+                    table::Value::VarChar(String::from("alice"))
+                ]),
+                Record::new(vec![
+                    table::Value::Int(2),
+                    table::Value::VarChar(String::from("bob"))
+                ]),
+                Record::new(vec![
+                    table::Value::Int(3),
+                    table::Value::VarChar(String::from("charlie"))
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_delete() {
+        let mut executer = setup("delete");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT, name VARCHAR(10)); INSERT INTO user VALUES (1, 'alice'), (2, 'bob'), (3, 'alice');",
+        );
+
+        run(&mut executer, "DELETE FROM user WHERE name = 'alice';");
+        let table = executer.storage.load("user").unwrap().unwrap();
+        assert_eq!(
+            table.rows,
+            vec![Record::new(vec![
+                table::Value::Int(2),
+                table::Value::VarChar(String::from("bob"))
+            ])]
+        );
+
+        // a statement with something left over is not run at all
+        for input in [
+            "DELETE FROM user oops",
+            "UPDATE user SET id = 9 WHER id = 2;",
+        ] {
+            assert!(matches!(
+                try_run(&mut executer, input),
+                Err(Error::Parse(ParseError::UnexpectedToken(_)))
+            ));
+        }
+        assert_eq!(
+            stored_rows(&executer, "user"),
+            vec![vec![
+                table::Value::Int(2),
+                table::Value::VarChar(String::from("bob"))
+            ]]
+        );
+
+        run(&mut executer, "DELETE FROM user;");
+        let table = executer.storage.load("user").unwrap().unwrap();
+        assert!(table.rows.is_empty());
+    }
+
+    #[test]
+    fn test_drop_table() {
+        let mut executer = setup("drop-table");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT); INSERT INTO user VALUES (1);",
+        );
+        assert!(executer.storage.load("user").unwrap().is_some());
+
+        run(&mut executer, "DROP TABLE user;");
+        assert!(executer.storage.load("user").unwrap().is_none());
+        assert_eq!(executer.storage.buffer_stats().pages, 0);
+
+        // no-op
+        assert_eq!(
+            run(&mut executer, "DROP TABLE IF EXISTS user;"),
+            QueryResult::Message(String::from("table user does not exist, skipping"))
+        );
+        assert!(try_run(&mut executer, "DROP TABLE user;").is_err());
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut executer = setup("truncate");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT); INSERT INTO user VALUES (1), (2); TRUNCATE TABLE user;",
+        );
+
+        let table = executer.storage.load("user").unwrap().unwrap();
+        assert_eq!(
+            table.columns,
+            vec![(String::from("id"), table::DataType::Int)]
+        );
+        assert!(table.rows.is_empty());
+    }
+
+    #[test]
+    fn test_writes_in_place() {
+        let mut executer = setup("in-place");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT PRIMARY KEY, name VARCHAR(100));",
+        );
+        let values = (0..500)
+            .map(|id| format!("({}, '{}')", id, "x".repeat(50)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        run(
+            &mut executer,
+            &format!("INSERT INTO user VALUES {};", values),
+        );
+        let path = format!("{}/user.ubdb", executer.storage.storage_dir);
+        let pages = || {
+            std::fs::read(&path)
+                .unwrap()
+                .chunks(storage::PAGE_SIZE)
+                .map(<[u8]>::to_vec)
+                .collect::<Vec<_>>()
+        };
+        let changed = |before: &[Vec<u8>], after: &[Vec<u8>]| {
+            assert_eq!(before.len(), after.len());
+            (0..before.len())
+                .filter(|idx| before[*idx] != after[*idx])
+                .collect::<Vec<_>>()
+        };
+
+        // only the page of the matching records is written
+        let before = pages();
+        run(
+            &mut executer,
+            "UPDATE user SET name = 'alice' WHERE id = 250;",
+        );
+        let after = pages();
+        assert_eq!(changed(&before, &after).len(), 1);
+        run(&mut executer, "DELETE FROM user WHERE id = 251;");
+        assert_eq!(changed(&after, &pages()), changed(&before, &after));
+
+        // a new record goes to the last page
+        let before = pages();
+        run(&mut executer, "INSERT INTO user VALUES (500, 'bob');");
+        assert_eq!(changed(&before, &pages()), vec![before.len() - 1]);
+
+        // and the keys are still checked against every record
+        assert!(matches!(
+            try_run(&mut executer, "INSERT INTO user VALUES (0, 'carol');"),
+            Err(Error::Constraint(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "UPDATE user SET id = 499 WHERE id = 250;"),
+            Err(Error::Constraint(_))
+        ));
+        assert_eq!(
+            select(&mut executer, "SELECT name FROM user WHERE id = 250;"),
+            vec![vec![table::Value::VarChar(String::from("alice"))]]
+        );
+        assert_eq!(stored_rows(&executer, "user").len(), 500);
+    }
+
+    #[test]
+    fn test_buffer_pool() {
+        let dir =
+            std::env::temp_dir().join(format!("ubdb-test-buffer-pool-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        // room for 4 pages, fewer than the table has
+        let mut executer =
+            Executer::with_buffer_budget(dir.to_str().unwrap().to_string(), storage::PAGE_SIZE * 4)
+                .unwrap();
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT, name VARCHAR(100));",
+        );
+        let values = (0..500)
+            .map(|id| format!("({}, '{}')", id, "x".repeat(50)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        run(
+            &mut executer,
+            &format!("INSERT INTO user VALUES {};", values),
+        );
+        let count = |executer: &mut Executer| match run(executer, "SELECT COUNT(*) FROM user;") {
+            QueryResult::Rows(result_set) => result_set.rows[0].values[0].clone(),
+            other => panic!("expected rows, got {:?}", other),
+        };
+
+        let stats = executer.storage.buffer_stats();
+        assert_eq!(stats.pages, 4);
+        assert!(stats.evictions > 0);
+        assert_eq!(count(&mut executer), table::Value::BigInt(500));
+        assert!(executer.storage.buffer_stats().misses > stats.misses);
+
+        // a small table is served from the pool once read
+        run(
+            &mut executer,
+            "CREATE TABLE todo (id INT); INSERT INTO todo VALUES (1), (2);",
+        );
+        let stats = executer.storage.buffer_stats();
+        run(&mut executer, "SELECT * FROM todo;");
+        let after = executer.storage.buffer_stats();
+        assert!(after.hits > stats.hits);
+        assert_eq!(after.misses, stats.misses);
+
+        // every change was written back
+        let mut executer = Executer::new(executer.storage.storage_dir.clone()).unwrap();
+        assert_eq!(count(&mut executer), table::Value::BigInt(500));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn select(executer: &mut Executer, input: &str) -> Vec<Vec<table::Value>> {
+        match run(executer, input) {
+            QueryResult::Rows(result_set) => {
+                result_set.rows.into_iter().map(|row| row.values).collect()
+            }
+            other => panic!("expected rows, got {:?}", other),
+        }
+    }
+
+    /// a restart reads back what the statements before it wrote
+    fn restart(executer: Executer) -> Executer {
+        Executer::new(executer.storage.storage_dir.clone()).unwrap()
+    }
+
+    #[test]
+    fn test_create_update_select_restart() {
+        use table::Value::{Int, Null, VarChar};
+        let name = |name: &str| VarChar(String::from(name));
+
+        let mut executer = setup("create-update-select-restart");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT PRIMARY KEY, name VARCHAR(10)); INSERT INTO user VALUES (1, 'alice'), (2, 'bob');",
+        );
+        run(
+            &mut executer,
+            "UPDATE user SET name = 'carol' WHERE id = 2;",
+        );
+        let expected = vec![vec![Int(1), name("alice")], vec![Int(2), name("carol")]];
+        assert_eq!(select(&mut executer, "SELECT * FROM user;"), expected);
+        let mut executer = restart(executer);
+        assert_eq!(select(&mut executer, "SELECT * FROM user;"), expected);
+
+        // a table created before the restart is updated and read like one created after it
+        run(
+            &mut executer,
+            "UPDATE user SET name = 'dave' WHERE id = 1; CREATE TABLE todo (id INT, user_id INT REFERENCES user (id)); INSERT INTO todo VALUES (1, 2);",
+        );
+        assert_eq!(
+            select(&mut executer, "SELECT name FROM user ORDER BY id;"),
+            vec![vec![name("dave")], vec![name("carol")]]
+        );
+        assert!(try_run(&mut executer, "DELETE FROM user WHERE id = 2;").is_err());
+        let mut executer = restart(executer);
+        assert_eq!(
+            select(&mut executer, "SELECT name FROM user ORDER BY id;"),
+            vec![vec![name("dave")], vec![name("carol")]]
+        );
+        // the foreign key is still enforced
+        assert!(try_run(&mut executer, "DELETE FROM user WHERE id = 2;").is_err());
+
+        // schema changes are seen at once, and after a restart
+        run(
+            &mut executer,
+            "ALTER TABLE user ADD COLUMN age INT; UPDATE user SET age = 30 WHERE id = 1;",
+        );
+        let expected = vec![
+            vec![Int(1), name("dave"), Int(30)],
+            vec![Int(2), name("carol"), Null],
+        ];
+        assert_eq!(select(&mut executer, "SELECT * FROM user;"), expected);
+        let mut executer = restart(executer);
+        assert_eq!(select(&mut executer, "SELECT * FROM user;"), expected);
+
+        run(&mut executer, "ALTER TABLE todo RENAME TO task;");
+        assert!(try_run(&mut executer, "SELECT * FROM todo;").is_err());
+        assert_eq!(
+            select(&mut executer, "SELECT * FROM task;"),
+            vec![vec![Int(1), Int(2)]]
+        );
+        let mut executer = restart(executer);
+        assert!(try_run(&mut executer, "SELECT * FROM todo;").is_err());
+        assert_eq!(
+            select(&mut executer, "SELECT * FROM task;"),
+            vec![vec![Int(1), Int(2)]]
+        );
+
+        // a dropped table is gone, and its name free for a new one
+        run(
+            &mut executer,
+            "DROP TABLE task; CREATE TABLE task (title VARCHAR(10)); INSERT INTO task VALUES ('write');",
+        );
+        assert_eq!(
+            select(&mut executer, "SELECT * FROM task;"),
+            vec![vec![name("write")]]
+        );
+        run(&mut executer, "DELETE FROM user WHERE id = 2;");
+        let mut executer = restart(executer);
+        assert_eq!(
+            select(&mut executer, "SELECT * FROM task;"),
+            vec![vec![name("write")]]
+        );
+        assert_eq!(
+            select(&mut executer, "SELECT id FROM user;"),
+            vec![vec![Int(1)]]
+        );
+    }
+
+    #[test]
+    fn test_crash_recovery() {
+        use table::Value::Int;
+
+        let mut executer = setup("crash-recovery");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT PRIMARY KEY); CREATE TABLE todo (id INT, user_id INT REFERENCES user (id) ON DELETE CASCADE); INSERT INTO user VALUES (1), (2); INSERT INTO todo VALUES (1, 1), (2, 2);",
+        );
+
+        // killed before the statements commit, which they join the transaction of
+        executer.storage.begin();
+        run(
+            &mut executer,
+            "DELETE FROM user WHERE id = 1; INSERT INTO user VALUES (3); DROP TABLE todo;",
+        );
+        assert_eq!(
+            select(&mut executer, "SELECT id FROM user ORDER BY id;"),
+            vec![vec![Int(2)], vec![Int(3)]]
+        );
+
+        let mut executer = restart(executer);
+        assert_eq!(
+            select(&mut executer, "SELECT id FROM user ORDER BY id;"),
+            vec![vec![Int(1)], vec![Int(2)]]
+        );
+        assert_eq!(
+            select(&mut executer, "SELECT * FROM todo;"),
+            vec![vec![Int(1), Int(1)], vec![Int(2), Int(2)]]
+        );
+    }
+
+    /// a failed statement leaves nothing behind, before and after a restart
+    #[test]
+    fn test_failed_write_restart() {
+        use table::Value::{Int, VarChar};
+
+        let mut executer = setup("failed-write-restart");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT PRIMARY KEY, name VARCHAR(10)); INSERT INTO user VALUES (1, 'alice');",
+        );
+        assert!(try_run(
+            &mut executer,
+            "INSERT INTO user VALUES (2, 'bob'), (1, 'carol');"
+        )
+        .is_err());
+        assert!(try_run(&mut executer, "UPDATE user SET id = NULL;").is_err());
+        assert!(try_run(&mut executer, "CREATE TABLE user (id INT);").is_err());
+        assert!(try_run(&mut executer, "ALTER TABLE user RENAME TO user;").is_err());
+        let expected = vec![vec![Int(1), VarChar(String::from("alice"))]];
+        assert_eq!(select(&mut executer, "SELECT * FROM user;"), expected);
+        let mut executer = restart(executer);
+        assert_eq!(select(&mut executer, "SELECT * FROM user;"), expected);
+    }
+
+    #[test]
+    fn test_alter_table() {
+        let mut executer = setup("alter-table");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT, name VARCHAR(10)); INSERT INTO user VALUES (1, 'alice');",
+        );
+
+        run(
+            &mut executer,
+            "ALTER TABLE user ADD COLUMN age INT; ALTER TABLE user DROP COLUMN name; ALTER TABLE user RENAME COLUMN id TO user_id;",
+        );
+        let table = executer.storage.load("user").unwrap().unwrap();
+        assert_eq!(
+            table.columns,
+            vec![
+                (String::from("user_id"), table::DataType::Int),
+                (String::from("age"), table::DataType::Int),
+            ]
+        );
+        assert_eq!(
+            table.rows,
+            vec![Record::new(vec![table::Value::Int(1), table::Value::Null])]
+        );
+
+        // like CREATE and DROP, no form of ALTER counts rows
+        assert_eq!(
+            run(
+                &mut executer,
+                "ALTER TABLE user ADD COLUMN nick VARCHAR(8);"
+            ),
+            QueryResult::Affected(0)
+        );
+        assert_eq!(
+            run(&mut executer, "ALTER TABLE user DROP COLUMN nick;"),
+            QueryResult::Affected(0)
+        );
+        assert_eq!(
+            run(&mut executer, "ALTER TABLE user RENAME TO member;"),
+            QueryResult::Affected(0)
+        );
+        assert!(executer.storage.load("user").unwrap().is_none());
+        let table = executer.storage.load("member").unwrap().unwrap();
+        assert_eq!(table.name, "member");
+        assert_eq!(table.rows.len(), 1);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut executer = setup("update");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT, name VARCHAR(10)); INSERT INTO user VALUES (1, 'alice'), (2, 'bob'), (3, 'carol');",
+        );
+
+        run(
+            &mut executer,
+            "UPDATE user SET name = 'mike', id = 10 WHERE id = 1 OR (name = 'carol' AND NOT id = 2);",
+        );
+        let table = executer.storage.load("user").unwrap().unwrap();
+        assert_eq!(
+            table.rows,
+            vec![
+                Record::new(vec![
+                    table::Value::Int(10),
+                    table::Value::VarChar(String::from("mike"))
+                ]),
+                Record::new(vec![
+                    table::Value::Int(2),
+                    table::Value::VarChar(String::from("bob"))
+                ]),
+                Record::new(vec![
+                    table::Value::Int(10),
+                    table::Value::VarChar(String::from("mike"))
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_update_comparison() {
+        let mut executer = setup("update-comparison");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT, name VARCHAR(10)); INSERT INTO user VALUES (1, 'alice'), (2, 'bob'), (3, 'carol');",
+        );
+
+        run(
+            &mut executer,
+            "UPDATE user SET name = 'x' WHERE id >= 2 AND name < 'c';",
+        );
+        run(&mut executer, "DELETE FROM user WHERE id <> 2;");
+        let table = executer.storage.load("user").unwrap().unwrap();
+        assert_eq!(
+            table.rows,
+            vec![Record::new(vec![
+                table::Value::Int(2),
+                table::Value::VarChar(String::from("x"))
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_insert_type_mismatch() {
+        let mut executer = setup("insert-type-mismatch");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT, name VARCHAR(10));",
+        );
+        assert!(try_run(
+            &mut executer,
+            "INSERT INTO user VALUES (1, 'alice'), ('bob', 2);"
+        )
+        .is_err());
+
+        // the whole statement is rejected
+        let table = executer.storage.load("user").unwrap().unwrap();
+        assert!(table.rows.is_empty());
+    }
+
+    #[test]
+    fn test_write_type_checks() {
+        let mut executer = setup("write-type-checks");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT, name VARCHAR(5)); INSERT INTO user VALUES (2147483647, 'alice');",
+        );
+        let type_error = |executer: &mut Executer, input| match try_run(executer, input) {
+            Err(Error::Type(message)) => message,
+            other => panic!("expected a type error, got {:?}", other),
+        };
+
+        // length is counted in characters
+        run(&mut executer, "INSERT INTO user VALUES (1, 'éééé');");
+        assert_eq!(
+            type_error(&mut executer, "INSERT INTO user VALUES (2, 'charlie');"),
+            "value too long for column name of type VARCHAR(5)"
+        );
+        assert_eq!(
+            type_error(&mut executer, "UPDATE user SET name = 'charlie';"),
+            "value too long for column name of type VARCHAR(5)"
+        );
+        assert_eq!(
+            type_error(
+                &mut executer,
+                "INSERT INTO user VALUES (2147483648, 'bob');"
+            ),
+            "integer 2147483648 out of range for column id of type INT"
+        );
+        assert_eq!(
+            type_error(&mut executer, "UPDATE user SET id = 4294967296;"),
+            "integer 4294967296 out of range for column id of type INT"
+        );
+        // literals are not coerced
+        assert_eq!(
+            type_error(&mut executer, "INSERT INTO user VALUES (3, 3);"),
+            "type mismatch: column name is VarChar(5), got 3"
+        );
+        assert_eq!(
+            type_error(&mut executer, "UPDATE user SET id = '3';"),
+            "type mismatch: column id is Int, got '3'"
+        );
+        assert!(matches!(
+            try_run(
+                &mut executer,
+                "CREATE TABLE t (name VARCHAR(2) DEFAULT 'abc');"
+            ),
+            Err(Error::Type(_))
+        ));
+        // in expressions, an integer literal too large for INT is a BIGINT
+        assert_eq!(
+            run(&mut executer, "SELECT id FROM user WHERE id < 2147483648;"),
+            QueryResult::Rows(ResultSet::new(
+                vec![(String::from("id"), table::DataType::Int)],
+                vec![
+                    Record::new(vec![table::Value::Int(2147483647)]),
+                    Record::new(vec![table::Value::Int(1)]),
+                ]
+            ))
+        );
+        // conditions do not compare mismatched types and must be boolean
+        assert_eq!(
+            type_error(&mut executer, "SELECT * FROM user WHERE id = 'a';"),
+            "operator = is not defined for Int and VarChar(1)"
+        );
+        assert_eq!(
+            type_error(&mut executer, "DELETE FROM user WHERE NOT (id <> 'x');"),
+            "operator <> is not defined for Int and VarChar(1)"
+        );
+        assert_eq!(
+            type_error(&mut executer, "UPDATE user SET id = 1 WHERE name;"),
+            "a condition must be BOOLEAN or numeric, not VarChar(5)"
+        );
+        assert_eq!(
+            type_error(&mut executer, "SELECT * FROM user WHERE id = 1 AND name;"),
+            "a condition must be BOOLEAN or numeric, not VarChar(5)"
+        );
+
+        assert_eq!(
+            executer.storage.load("user").unwrap().unwrap().rows.len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_numeric_types() {
+        let mut executer = setup("numeric-types");
+        run(
+            &mut executer,
+            "CREATE TABLE item (id BIGINT, price DECIMAL(6, 2), weight DOUBLE, active BOOLEAN);",
+        );
+        run(
+            &mut executer,
+            "INSERT INTO item VALUES (1, 9.995, 1.5, TRUE), (9000000000, -2, 0.25, FALSE), (3, 0.1, -1, NULL);",
+        );
+        let decimal = |s| table::Value::Decimal(Decimal::parse(s).unwrap());
+        let rows = |result| match result {
+            QueryResult::Rows(result_set) => result_set
+                .rows
+                .into_iter()
+                .map(|row| row.values)
+                .collect::<Vec<_>>(),
+            other => panic!("expected rows, got {:?}", other),
+        };
+
+        // literals are rounded to the scale of the column
+        assert_eq!(
+            stored_rows(&executer, "item")[0],
+            vec![
+                table::Value::BigInt(1),
+                decimal("10.00"),
+                table::Value::Double(1.5),
+                table::Value::Boolean(true),
+            ]
+        );
+        assert_eq!(
+            rows(run(
+                &mut executer,
+                "SELECT id + 1, price * 2, weight / 2, id % 2 FROM item WHERE active ORDER BY id;"
+            )),
+            vec![vec![
+                table::Value::BigInt(2),
+                decimal("20.00"),
+                table::Value::Double(0.75),
+                table::Value::BigInt(1),
+            ]]
+        );
+        // prefix minus on columns and expressions, with the types of binary arithmetic
+        assert_eq!(
+            rows(run(
+                &mut executer,
+                "SELECT -id, -price, -weight, -(id - 10) * 2 FROM item WHERE active;"
+            )),
+            vec![vec![
+                table::Value::BigInt(-1),
+                decimal("-10.00"),
+                table::Value::Double(-1.5),
+                table::Value::BigInt(18),
+            ]]
+        );
+        assert!(matches!(
+            try_run(&mut executer, "SELECT -active FROM item;"),
+            Err(Error::Type(_))
+        ));
+        run(
+            &mut executer,
+            "CREATE TABLE n (v INT); INSERT INTO n VALUES (-2147483648), (NULL);",
+        );
+        assert!(matches!(
+            try_run(&mut executer, "SELECT -v FROM n;"),
+            Err(Error::Type(_))
+        ));
+        assert_eq!(
+            rows(run(&mut executer, "SELECT -(v + 1) FROM n;")),
+            vec![
+                vec![table::Value::Int(2147483647)],
+                vec![table::Value::Null]
+            ]
+        );
+        // BIGINT literals reach both ends of the range, and no further
+        run(
+            &mut executer,
+            "CREATE TABLE b (v BIGINT); INSERT INTO b VALUES (-9223372036854775808), (9223372036854775807);",
+        );
+        assert_eq!(
+            rows(run(&mut executer, "SELECT v FROM b;")),
+            vec![
+                vec![table::Value::BigInt(i64::MIN)],
+                vec![table::Value::BigInt(i64::MAX)]
+            ]
+        );
+        assert!(matches!(
+            try_run(&mut executer, "INSERT INTO b VALUES (9223372036854775808);"),
+            Err(Error::Parse(ParseError::InvalidLiteral(_)))
+        ));
+
+        // integers, decimals and doubles compare by value
+        assert_eq!(
+            rows(run(
+                &mut executer,
+                "SELECT id FROM item WHERE price < weight OR price = 10 ORDER BY price;"
+            )),
+            vec![
+                vec![table::Value::BigInt(9000000000)],
+                vec![table::Value::BigInt(1)],
+            ]
+        );
+        assert_eq!(
+            rows(run(
+                &mut executer,
+                "SELECT SUM(price), AVG(price), SUM(weight) FROM item;"
+            )),
+            vec![vec![
+                decimal("8.10"),
+                decimal("2.70"),
+                table::Value::Double(0.75),
+            ]]
+        );
+        assert_eq!(
+            run(
+                &mut executer,
+                "UPDATE item SET price = -3.5 WHERE NOT active;"
+            ),
+            QueryResult::Affected(1)
+        );
+        assert_eq!(stored_rows(&executer, "item")[1][1], decimal("-3.50"));
+
+        assert!(matches!(
+            try_run(&mut executer, "SELECT id / 0 FROM item;"),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "SELECT id * 9000000000 FROM item;"),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            try_run(
+                &mut executer,
+                "INSERT INTO item VALUES (4, 10000, 0, TRUE);"
+            ),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "INSERT INTO item VALUES (4, 1, 0, 1);"),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "SELECT SUM(active) FROM item;"),
+            Err(Error::Type(_))
+        ));
+
+        // values survive a restart
+        let executer = Executer::new(executer.storage.storage_dir.clone()).unwrap();
+        assert_eq!(
+            stored_rows(&executer, "item")[2],
+            vec![
+                table::Value::BigInt(3),
+                decimal("0.10"),
+                table::Value::Double(-1.0),
+                table::Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_date_time_types() {
+        let mut executer = setup("date-time-types");
+        run(
+            &mut executer,
+            "CREATE TABLE event (id INT, day DATE, at TIME, created TIMESTAMP, duration INTERVAL);",
+        );
+        run(
+            &mut executer,
+            "INSERT INTO event VALUES
+                (1, DATE '2026-10-18', TIME '09:30', TIMESTAMP '2026-10-18 09:30:00', INTERVAL '1 hour 30 minutes'),
+                (2, DATE '2024-02-29', TIME '23:15:00.25', DATE '2024-02-29', INTERVAL '1 month'),
+                (3, NULL, NULL, NULL, NULL);",
+        );
+        let rows = |result| match result {
+            QueryResult::Rows(result_set) => result_set
+                .rows
+                .into_iter()
+                .map(|row| row.values)
+                .collect::<Vec<_>>(),
+            other => panic!("expected rows, got {:?}", other),
+        };
+        let date = |s| table::Value::Date(Date::parse(s).unwrap());
+        let timestamp = |s| table::Value::Timestamp(Timestamp::parse(s).unwrap());
+        let interval = |s| table::Value::Interval(Interval::parse(s).unwrap());
+
+        // a date written to a timestamp column is midnight
+        assert_eq!(
+            stored_rows(&executer, "event")[1][3],
+            timestamp("2024-02-29")
+        );
+        assert_eq!(
+            rows(run(
+                &mut executer,
+                "SELECT id FROM event WHERE created >= DATE '2026-01-01' OR day < DATE '2025-01-01' ORDER BY created DESC;"
+            )),
+            vec![vec![table::Value::Int(1)], vec![table::Value::Int(2)]]
+        );
+        assert_eq!(
+            rows(run(
+                &mut executer,
+                "SELECT created + duration, day + 1, day - DATE '2024-01-01', at - INTERVAL '10 hours' FROM event WHERE id = 2;"
+            )),
+            vec![vec![
+                timestamp("2024-03-29"),
+                date("2024-03-01"),
+                table::Value::Int(59),
+                table::Value::Time(Time::parse("13:15:00.25").unwrap()),
+            ]]
+        );
+        assert_eq!(
+            rows(run(
+                &mut executer,
+                "SELECT created - TIMESTAMP '2026-10-17 00:00', duration * 2, DATE_TRUNC('month', created), EXTRACT(HOUR FROM created), EXTRACT(DOW FROM day) FROM event WHERE id = 1;"
+            )),
+            vec![vec![
+                interval("1 day 09:30:00"),
+                interval("3 hours"),
+                timestamp("2026-10-01"),
+                table::Value::BigInt(9),
+                table::Value::BigInt(0),
+            ]]
+        );
+        assert_eq!(
+            rows(run(
+                &mut executer,
+                "SELECT MIN(created), MAX(duration), COUNT(day) FROM event;"
+            )),
+            vec![vec![
+                timestamp("2024-02-29"),
+                interval("1 month"),
+                table::Value::BigInt(2),
+            ]]
+        );
+        let QueryResult::Rows(result_set) = run(
+            &mut executer,
+            "SELECT NOW() - created FROM event WHERE id = 2;",
+        ) else {
+            panic!("expected rows");
+        };
+        assert_eq!(result_set.columns[0].1, table::DataType::Interval);
+        assert!(
+            result_set.rows[0].values[0] > interval("0 days"),
+            "NOW() should be after the event"
+        );
+
+        assert!(matches!(
+            try_run(
+                &mut executer,
+                "INSERT INTO event (id, day) VALUES (4, DATE '2026-02-30');"
+            ),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            try_run(
+                &mut executer,
+                "INSERT INTO event (id, day) VALUES (4, '2026-10-18');"
+            ),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            try_run(
+                &mut executer,
+                "INSERT INTO event (id, at) VALUES (4, DATE '2026-10-18');"
+            ),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "SELECT day + at FROM event;"),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "SELECT EXTRACT(HOUR FROM day) FROM event;"),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            try_run(
+                &mut executer,
+                "SELECT day + INTERVAL '10000 years' FROM event;"
+            ),
+            Err(Error::Type(_))
+        ));
+
+        // type names are also column names
+        run(
+            &mut executer,
+            "CREATE TABLE ev (id INT, date DATE); INSERT INTO ev (id, date) VALUES (1, DATE '2026-10-18');",
+        );
+        assert_eq!(
+            select(
+                &mut executer,
+                "SELECT date + 1 FROM ev WHERE date = DATE '2026-10-18';"
+            ),
+            vec![vec![date("2026-10-19")]]
+        );
+
+        // values survive a restart
+        let executer = Executer::new(executer.storage.storage_dir.clone()).unwrap();
+        assert_eq!(
+            stored_rows(&executer, "event")[0],
+            vec![
+                table::Value::Int(1),
+                date("2026-10-18"),
+                table::Value::Time(Time::parse("09:30").unwrap()),
+                timestamp("2026-10-18 09:30"),
+                interval("01:30:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_type() {
+        let mut executer = setup("json-type");
+        run(
+            &mut executer,
+            r#"CREATE TABLE doc (id INT, body JSON, raw VARCHAR(100));
+            INSERT INTO doc VALUES
+                (1, '{"name": "alice", "age": 30, "tags": ["a", "b"]}', '{"n": 1}'),
+                (2, JSON '{"name": "bob", "age": 25, "tags": []}', NULL),
+                (3, NULL, '[1, 2, 3]');"#,
+        );
+        let rows = |result| match result {
+            QueryResult::Rows(result_set) => result_set
+                .rows
+                .into_iter()
+                .map(|row| row.values)
+                .collect::<Vec<_>>(),
+            other => panic!("expected rows, got {:?}", other),
+        };
+        let json = |s| table::Value::Json(Json::parse(s).unwrap());
+        let text = |s: &str| table::Value::VarChar(s.to_string());
+
+        assert_eq!(
+            rows(run(
+                &mut executer,
+                "SELECT body ->> 'name', body -> 'tags' -> 0, JSON_ARRAY_LENGTH(body, '$.tags') FROM doc WHERE body -> 'age' >= 25 ORDER BY id;"
+            )),
+            vec![
+                vec![text("alice"), json("\"a\""), table::Value::BigInt(2)],
+                vec![text("bob"), table::Value::Null, table::Value::BigInt(0)],
+            ]
+        );
+        assert_eq!(
+            rows(run(
+                &mut executer,
+                "SELECT id FROM doc WHERE body ->> 'name' = 'bob' OR json_extract(raw, '$[2]') = 3 ORDER BY id;"
+            )),
+            vec![vec![table::Value::Int(2)], vec![table::Value::Int(3)]]
+        );
+        let QueryResult::Rows(result_set) = run(
+            &mut executer,
+            "SELECT body -> 'name' AS name, body ->> 'name' FROM doc;",
+        ) else {
+            panic!("expected rows");
+        };
+        assert_eq!(
+            result_set.columns,
+            vec![
+                (String::from("name"), table::DataType::Json),
+                (String::from("body ->> 'name'"), table::DataType::Text),
+            ]
+        );
+
+        run(
+            &mut executer,
+            r#"UPDATE doc SET body = '{"name": "carol"}' WHERE id = 3;"#,
+        );
+        assert!(matches!(
+            try_run(
+                &mut executer,
+                "INSERT INTO doc (id, body) VALUES (4, '{\"a\": }');"
+            ),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "INSERT INTO doc (id, body) VALUES (4, 1);"),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "SELECT id -> 'a' FROM doc;"),
+            Err(Error::Type(_))
+        ));
+
+        // documents survive a restart
+        let executer = Executer::new(executer.storage.storage_dir.clone()).unwrap();
+        assert_eq!(
+            stored_rows(&executer, "doc")
+                .into_iter()
+                .map(|row| row[1].clone())
+                .collect::<Vec<_>>(),
+            vec![
+                json(r#"{"age": 30, "name": "alice", "tags": ["a", "b"]}"#),
+                json(r#"{"name": "bob", "age": 25, "tags": []}"#),
+                json(r#"{"name": "carol"}"#),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_and_blob_types() {
+        let mut executer = setup("text-and-blob-types");
+        // well past what a VARCHAR can hold, so it is stored in overflow pages
+        let text = "lorem ipsum ".repeat(10_000);
+        let blob = (0..70_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let hex = blob
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        run(
+            &mut executer,
+            &format!(
+                "CREATE TABLE file (id INT, name TEXT, data BLOB);
+                INSERT INTO file VALUES (1, '{}', X'{}'), (2, 'b', X'DEAD'), (3, NULL, x'');",
+                text, hex
+            ),
+        );
+
+        assert_eq!(
+            run(&mut executer, "SELECT id FROM file WHERE data = X'dead';"),
+            QueryResult::Rows(ResultSet {
+                columns: vec![(String::from("id"), table::DataType::Int)],
+                rows: vec![Record::new(vec![table::Value::Int(2)])],
+            })
+        );
+        assert_eq!(
+            run(
+                &mut executer,
+                "SELECT id FROM file WHERE name > 'c' ORDER BY data;"
+            ),
+            QueryResult::Rows(ResultSet {
+                columns: vec![(String::from("id"), table::DataType::Int)],
+                rows: vec![Record::new(vec![table::Value::Int(1)])],
+            })
+        );
+        run(
+            &mut executer,
+            "UPDATE file SET data = X'BEEF', name = 'c' WHERE id = 2;",
+        );
+
+        assert!(matches!(
+            try_run(&mut executer, "INSERT INTO file VALUES (4, X'00', NULL);"),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "INSERT INTO file VALUES (4, NULL, 'text');"),
+            Err(Error::Type(_))
+        ));
+
+        // values survive a restart
+        let executer = Executer::new(executer.storage.storage_dir.clone()).unwrap();
+        assert_eq!(
+            stored_rows(&executer, "file"),
+            vec![
+                vec![
+                    table::Value::Int(1),
+                    table::Value::VarChar(text),
+                    table::Value::Blob(blob),
+                ],
+                vec![
+                    table::Value::Int(2),
+                    table::Value::VarChar(String::from("c")),
+                    table::Value::Blob(vec![0xbe, 0xef]),
+                ],
+                vec![
+                    table::Value::Int(3),
+                    table::Value::Null,
+                    table::Value::Blob(vec![]),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select() {
+        let mut executer = setup("select");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT, name VARCHAR(10)); INSERT INTO user VALUES (1, 'alice'), (2, 'bob'), (3, 'carol');",
+        );
+
+        let result = run(
+            &mut executer,
+            "SELECT name AS n, id FROM user WHERE id >= 2 ORDER BY id DESC LIMIT 1;",
+        );
+        assert_eq!(
+            result,
+            QueryResult::Rows(ResultSet::new(
+                vec![
+                    (String::from("n"), table::DataType::VarChar(10)),
+                    (String::from("id"), table::DataType::Int),
+                ],
+                vec![Record::new(vec![
+                    table::Value::VarChar(String::from("carol")),
+                    table::Value::Int(3)
+                ])]
+            ))
+        );
+
+        let QueryResult::Rows(result_set) = run(&mut executer, "SELECT * FROM user WHERE id > 3;")
+        else {
+            panic!("expected rows");
+        };
+        assert_eq!(result_set.column_names(), vec!["id", "name"]);
+        assert!(result_set.rows.is_empty());
+    }
+
+    #[test]
+    fn test_select_aggregate_and_join() {
+        let mut executer = setup("select-aggregate-and-join");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT, name VARCHAR(10)); INSERT INTO user VALUES (1, 'alice'), (2, 'bob');",
+        );
+        run(
+            &mut executer,
+            "CREATE TABLE todo (id INT, user_id INT); INSERT INTO todo VALUES (1, 1), (2, 1), (3, 2);",
+        );
+
+        let QueryResult::Rows(result_set) = run(
+            &mut executer,
+            "SELECT u.name, COUNT(*) AS n FROM user u JOIN todo t ON u.id = t.user_id GROUP BY u.name ORDER BY n DESC;",
+        ) else {
+            panic!("expected rows");
+        };
+        assert_eq!(
+            result_set.columns,
+            vec![
+                (String::from("u.name"), table::DataType::VarChar(10)),
+                (String::from("n"), table::DataType::BigInt),
+            ]
+        );
+        assert_eq!(
+            result_set.rows,
+            vec![
+                Record::new(vec![
+                    table::Value::VarChar(String::from("alice")),
+                    table::Value::BigInt(2)
+                ]),
+                Record::new(vec![
+                    table::Value::VarChar(String::from("bob")),
+                    table::Value::BigInt(1)
+                ]),
+            ]
+        );
+
+        // a table joined with itself needs an alias on one side
+        assert_eq!(
+            select(
+                &mut executer,
+                "SELECT u.name, m.name FROM user u JOIN user m ON u.id < m.id;"
+            ),
+            vec![vec![
+                table::Value::VarChar(String::from("alice")),
+                table::Value::VarChar(String::from("bob")),
+            ]]
+        );
+        for input in [
+            "SELECT * FROM user u JOIN user u ON u.id = u.id;",
+            "SELECT * FROM user JOIN user ON user.id = user.id;",
+            "SELECT * FROM user, todo user;",
+        ] {
+            assert!(
+                matches!(
+                    try_run(&mut executer, input),
+                    Err(Error::Catalog(CatalogError::DuplicateTable(name))) if name == "user" || name == "u"
+                ),
+                "{}",
+                input
+            );
+        }
+
+        assert_eq!(
+            run(&mut executer, "UPDATE todo SET user_id = 2 WHERE id < 3;"),
+            QueryResult::Affected(2)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut executer = setup("errors");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT, name VARCHAR(10)); CREATE TABLE todo (id INT, user_id INT);",
+        );
+
+        let catalog_error = |executer: &mut Executer, input| match try_run(executer, input) {
+            Err(Error::Catalog(err)) => err,
+            other => panic!("expected a catalog error, got {:?}", other),
+        };
+        assert_eq!(
+            catalog_error(&mut executer, "SELECT * FROM users;"),
+            CatalogError::UnknownTable(String::from("users"))
+        );
+        assert_eq!(
+            catalog_error(&mut executer, "UPDATE user SET nmae = 'x';"),
+            CatalogError::UnknownColumn(String::from("nmae"))
+        );
+        assert_eq!(
+            catalog_error(&mut executer, "SELECT id FROM user, todo;"),
+            CatalogError::AmbiguousColumn(String::from("id"))
+        );
+        assert_eq!(
+            catalog_error(&mut executer, "CREATE TABLE user (id INT);"),
+            CatalogError::TableExists(String::from("user"))
+        );
+        assert_eq!(
+            catalog_error(&mut executer, "INSERT INTO nobody VALUES (1);"),
+            CatalogError::UnknownTable(String::from("nobody"))
+        );
+        assert!(matches!(
+            try_run(&mut executer, "SELECT name, COUNT(*) FROM user;"),
+            Err(Error::Invalid(_))
+        ));
+
+        // the executer keeps working after an error
+        assert_eq!(
+            run(&mut executer, "INSERT INTO user VALUES (1, 'alice');"),
+            QueryResult::Affected(1)
+        );
+
+        // aggregates are refused in the WHERE of writes as in that of SELECT
+        assert!(matches!(
+            try_run(&mut executer, "DELETE FROM user WHERE COUNT(*) > 0;"),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            try_run(
+                &mut executer,
+                "UPDATE user SET name = 'x' WHERE MAX(id) = 1;"
+            ),
+            Err(Error::Invalid(_))
+        ));
+        assert_eq!(
+            select(&mut executer, "SELECT name FROM user;"),
+            vec![vec![table::Value::VarChar(String::from("alice"))]]
+        );
+    }
+
+    #[test]
+    fn test_null() {
+        let mut executer = setup("null");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT, name VARCHAR(10)); INSERT INTO user VALUES (1, 'alice'), (2, NULL), (NULL, 'carol');",
+        );
+
+        let rows = |executer: &mut Executer, input| match run(executer, input) {
+            QueryResult::Rows(result_set) => result_set.rows,
+            other => panic!("expected rows, got {:?}", other),
+        };
+        let ids = |executer: &mut Executer, input| {
+            rows(executer, input)
+                .into_iter()
+                .map(|row| row.values[0].clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(&mut executer, "SELECT id FROM user WHERE name = NULL;"),
+            vec![]
+        );
+        assert_eq!(
+            ids(&mut executer, "SELECT id FROM user WHERE name IS NULL;"),
+            vec![table::Value::Int(2)]
+        );
+        assert_eq!(
+            ids(
+                &mut executer,
+                "SELECT id FROM user WHERE NOT name = 'alice';"
+            ),
+            vec![table::Value::Null]
+        );
+        assert_eq!(
+            rows(
+                &mut executer,
+                "SELECT COUNT(*), COUNT(id), SUM(id), MIN(name) FROM user;"
+            ),
+            vec![Record::new(vec![
+                table::Value::BigInt(3),
+                table::Value::BigInt(2),
+                table::Value::BigInt(3),
+                table::Value::VarChar(String::from("alice")),
+            ])]
+        );
+
+        // survives a round trip through the storage
+        run(&mut executer, "UPDATE user SET name = NULL WHERE id = 1;");
+        let table = executer.storage.load("user").unwrap().unwrap();
+        assert_eq!(
+            table.rows,
+            vec![
+                Record::new(vec![table::Value::Int(1), table::Value::Null]),
+                Record::new(vec![table::Value::Int(2), table::Value::Null]),
+                Record::new(vec![
+                    table::Value::Null,
+                    table::Value::VarChar(String::from("carol"))
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_constraints() {
+        let mut executer = setup("constraints");
+        run(
+            &mut executer,
+            "CREATE TABLE todo (id INT PRIMARY KEY, user_id INT NOT NULL CHECK (user_id > 0), title VARCHAR(10) DEFAULT 'untitled', UNIQUE (user_id, title));",
+        );
+
+        assert_eq!(
+            run(
+                &mut executer,
+                "INSERT INTO todo (id, user_id) VALUES (1, 1); INSERT INTO todo VALUES (2, 1, 'laundry');"
+            ),
+            QueryResult::Affected(1)
+        );
+        let constraint_error = |executer: &mut Executer, input| match try_run(executer, input) {
+            Err(Error::Constraint(message)) => message,
+            other => panic!("expected a constraint violation, got {:?}", other),
+        };
+        assert_eq!(
+            constraint_error(&mut executer, "INSERT INTO todo VALUES (1, 2, 'dishes');"),
+            "duplicate key (id)=(1) violates PRIMARY KEY constraint of todo"
+        );
+        assert_eq!(
+            constraint_error(
+                &mut executer,
+                "INSERT INTO todo (id, title) VALUES (3, 'x');"
+            ),
+            "null value in column user_id of todo violates NOT NULL constraint"
+        );
+        assert_eq!(
+            constraint_error(&mut executer, "UPDATE todo SET user_id = 0 WHERE id = 2;"),
+            "new row for todo violates CHECK (user_id > 0)"
+        );
+        assert_eq!(
+            constraint_error(&mut executer, "UPDATE todo SET title = 'untitled';"),
+            "duplicate key (user_id, title)=(1, 'untitled') violates UNIQUE constraint of todo"
+        );
+        // a violation in any row rejects the whole statement
+        assert!(try_run(
+            &mut executer,
+            "INSERT INTO todo VALUES (3, 2, 'a'), (4, 2, 'a');"
+        )
+        .is_err());
+
+        // the constraints survive a restart
+        let table = executer.storage.load("todo").unwrap().unwrap();
+        assert_eq!(
+            table.rows,
+            vec![
+                Record::new(vec![
+                    table::Value::Int(1),
+                    table::Value::Int(1),
+                    table::Value::VarChar(String::from("untitled"))
+                ]),
+                Record::new(vec![
+                    table::Value::Int(2),
+                    table::Value::Int(1),
+                    table::Value::VarChar(String::from("laundry"))
+                ]),
+            ]
+        );
+
+        let mut executer = Executer::new(executer.storage.storage_dir.clone()).unwrap();
+        assert!(try_run(&mut executer, "INSERT INTO todo VALUES (2, 3, 'x');").is_err());
+
+        // constraints follow renamed columns and keep their columns from being dropped
+        run(
+            &mut executer,
+            "ALTER TABLE todo RENAME COLUMN user_id TO owner;",
+        );
+        assert_eq!(
+            constraint_error(&mut executer, "UPDATE todo SET owner = 0;"),
+            "new row for todo violates CHECK (owner > 0)"
+        );
+        assert!(matches!(
+            try_run(&mut executer, "ALTER TABLE todo DROP COLUMN owner;"),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    fn stored_rows(executer: &Executer, table_name: &str) -> Vec<Vec<table::Value>> {
+        let table = executer.storage.load(table_name).unwrap().unwrap();
+        table.rows.into_iter().map(|row| row.values).collect()
+    }
+
+    #[test]
+    fn test_foreign_keys() {
+        use table::Value::{Int, Null};
+
+        let mut executer = setup("foreign_keys");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT PRIMARY KEY, name VARCHAR(10));
+             CREATE TABLE todo (id INT PRIMARY KEY, user_id INT REFERENCES user ON DELETE CASCADE ON UPDATE CASCADE);
+             INSERT INTO user VALUES (1, 'alice'), (2, 'bob'), (3, 'carol');
+             INSERT INTO todo VALUES (1, 1), (2, 1), (3, 2), (4, NULL);",
+        );
+        let constraint_error = |executer: &mut Executer, input| match try_run(executer, input) {
+            Err(Error::Constraint(message)) => message,
+            other => panic!("expected a constraint violation, got {:?}", other),
+        };
+        assert_eq!(
+            constraint_error(&mut executer, "INSERT INTO todo VALUES (5, 9);"),
+            "insert or update on todo violates foreign key constraint: key (user_id)=(9) is not present in user"
+        );
+        assert!(try_run(&mut executer, "UPDATE todo SET user_id = 9 WHERE id = 1;").is_err());
+
+        // cascade
+        run(&mut executer, "UPDATE user SET id = 10 WHERE id = 1;");
+        assert_eq!(
+            stored_rows(&executer, "todo"),
+            vec![
+                vec![Int(1), Int(10)],
+                vec![Int(2), Int(10)],
+                vec![Int(3), Int(2)],
+                vec![Int(4), Null],
+            ]
+        );
+        assert_eq!(
+            run(&mut executer, "DELETE FROM user WHERE id = 10;"),
+            QueryResult::Affected(1)
+        );
+        assert_eq!(
+            stored_rows(&executer, "todo"),
+            vec![vec![Int(3), Int(2)], vec![Int(4), Null]]
+        );
+
+        // restrict, set null and set default
+        run(
+            &mut executer,
+            "CREATE TABLE tag (id INT PRIMARY KEY, user_id INT REFERENCES user (id));
+             CREATE TABLE note (id INT, user_id INT DEFAULT 3 REFERENCES user ON DELETE SET DEFAULT ON UPDATE SET NULL);
+             INSERT INTO tag VALUES (1, 2);
+             INSERT INTO note VALUES (1, 2), (2, 3);",
+        );
+        assert_eq!(
+            constraint_error(&mut executer, "DELETE FROM user WHERE id = 2;"),
+            "delete on user violates foreign key constraint of tag: key (id)=(2) is still referenced"
+        );
+        assert_eq!(stored_rows(&executer, "user").len(), 2);
+        assert_eq!(stored_rows(&executer, "todo").len(), 2);
+        run(
+            &mut executer,
+            "DELETE FROM tag; UPDATE user SET id = 20 WHERE id = 2;",
+        );
+        assert_eq!(
+            stored_rows(&executer, "todo"),
+            vec![vec![Int(3), Int(20)], vec![Int(4), Null]]
+        );
+        assert_eq!(
+            stored_rows(&executer, "note"),
+            vec![vec![Int(1), Null], vec![Int(2), Int(3)]]
+        );
+        run(&mut executer, "UPDATE note SET user_id = 20 WHERE id = 1;");
+        run(&mut executer, "DELETE FROM user WHERE id = 20;");
+        assert_eq!(
+            stored_rows(&executer, "note"),
+            vec![vec![Int(1), Int(3)], vec![Int(2), Int(3)]]
+        );
+        // the default itself must be present in the referenced table
+        assert_eq!(
+            constraint_error(&mut executer, "DELETE FROM user;"),
+            "insert or update on note violates foreign key constraint: key (user_id)=(3) is not present in user"
+        );
+
+        // referenced tables can be neither dropped nor truncated, and renames are followed
+        assert!(matches!(
+            try_run(&mut executer, "DROP TABLE user;"),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "TRUNCATE TABLE user;"),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "ALTER TABLE user DROP COLUMN id;"),
+            Err(Error::Invalid(_))
+        ));
+        run(
+            &mut executer,
+            "ALTER TABLE user RENAME COLUMN id TO uid; ALTER TABLE user RENAME TO member;",
+        );
+        let mut executer = Executer::new(executer.storage.storage_dir.clone()).unwrap();
+        assert_eq!(
+            constraint_error(&mut executer, "INSERT INTO todo VALUES (5, 9);"),
+            "insert or update on todo violates foreign key constraint: key (user_id)=(9) is not present in member"
+        );
+        run(
+            &mut executer,
+            "INSERT INTO member VALUES (9, 'dave'); INSERT INTO todo VALUES (5, 9);",
+        );
+        run(&mut executer, "DELETE FROM member WHERE uid = 9;");
+        assert_eq!(stored_rows(&executer, "todo"), vec![vec![Int(4), Null]]);
+    }
+
+    #[test]
+    fn test_foreign_keys_self_reference() {
+        use table::Value::{Int, Null};
+
+        let mut executer = setup("foreign_keys_self_reference");
+        run(
+            &mut executer,
+            "CREATE TABLE category (id INT PRIMARY KEY, parent INT, FOREIGN KEY (parent) REFERENCES category (id) ON DELETE CASCADE);
+             INSERT INTO category VALUES (1, NULL), (2, 1), (3, 2), (4, NULL), (5, 5);",
+        );
+        assert!(try_run(&mut executer, "INSERT INTO category VALUES (6, 7);").is_err());
+
+        // cascades down the whole subtree
+        assert_eq!(
+            run(&mut executer, "DELETE FROM category WHERE id = 1;"),
+            QueryResult::Affected(1)
+        );
+        assert_eq!(
+            stored_rows(&executer, "category"),
+            vec![vec![Int(4), Null], vec![Int(5), Int(5)]]
+        );
+        run(&mut executer, "DELETE FROM category WHERE id = 5;");
+        assert_eq!(stored_rows(&executer, "category"), vec![vec![Int(4), Null]]);
+    }
+
+    #[test]
+    fn test_create_table_foreign_key_errors() {
+        let mut executer = setup("create_table_foreign_key_errors");
+        run(
+            &mut executer,
+            "CREATE TABLE user (id INT PRIMARY KEY, name VARCHAR(10), age INT);",
+        );
+        assert!(matches!(
+            try_run(&mut executer, "CREATE TABLE t (a INT REFERENCES nothing);"),
+            Err(Error::Catalog(CatalogError::UnknownTable(_)))
+        ));
+        assert!(matches!(
+            try_run(
+                &mut executer,
+                "CREATE TABLE t (a INT REFERENCES user (uid));"
+            ),
+            Err(Error::Catalog(CatalogError::UnknownColumn(_)))
+        ));
+        assert!(matches!(
+            try_run(
+                &mut executer,
+                "CREATE TABLE t (a INT REFERENCES user (age));"
+            ),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            try_run(
+                &mut executer,
+                "CREATE TABLE t (a VARCHAR(10) REFERENCES user);"
+            ),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            try_run(
+                &mut executer,
+                "CREATE TABLE t (a INT, b INT, FOREIGN KEY (a, b) REFERENCES user (id));"
+            ),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "CREATE TABLE t (a INT REFERENCES t);"),
+            Err(Error::Invalid(_))
+        ));
+        assert!(executer.storage.load("t").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_create_table_constraint_errors() {
+        let mut executer = setup("create-table-constraint-errors");
+        assert!(matches!(
+            try_run(
+                &mut executer,
+                "CREATE TABLE t (id INT PRIMARY KEY, PRIMARY KEY (id));"
+            ),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "CREATE TABLE t (id INT, UNIQUE (name));"),
+            Err(Error::Catalog(CatalogError::UnknownColumn(_)))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "CREATE TABLE t (id INT DEFAULT 'zero');"),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            try_run(&mut executer, "CREATE TABLE t (id INT CHECK (age > 0));"),
+            Err(Error::Catalog(CatalogError::UnknownColumn(_)))
+        ));
+        assert!(executer.storage.load("t").unwrap().is_none());
     }
 }
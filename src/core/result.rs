@@ -0,0 +1,31 @@
+use super::table::{DataType, Record};
+
+/// result of executing a single statement
+#[derive(Debug, PartialEq, Clone)]
+pub enum QueryResult {
+    // rows returned by a query
+    Rows(ResultSet),
+    // number of rows affected by a write or ddl
+    Affected(usize),
+    // status message for statements that have nothing else to report
+    Message(String),
+    // the session should end
+    Exit,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ResultSet {
+    // output column names and types
+    pub columns: Vec<(String, DataType)>,
+    pub rows: Vec<Record>,
+}
+
+impl ResultSet {
+    pub fn new(columns: Vec<(String, DataType)>, rows: Vec<Record>) -> Self {
+        Self { columns, rows }
+    }
+
+    pub fn column_names(&self) -> Vec<&str> {
+        self.columns.iter().map(|(name, _)| name.as_str()).collect()
+    }
+}
@@ -1,73 +1,432 @@
-use crate::core::table::{DataType, Table, Value};
+use std::borrow::Cow;
 
-use super::{DataTypeByteMap, Storage};
+use crate::{
+    core::{
+        json::Json,
+        table::{Constraint, DataType, Record, Table, Value},
+    },
+    error::{Error, Result},
+    query::ast::ReferentialAction,
+};
+
+use super::{
+    heap::{HeapFile, RecordId},
+    overflow::OVERFLOW_THRESHOLD,
+    page::PageId,
+    ConstraintByteMap, DataTypeByteMap, JsonByteMap, ReferentialActionByteMap, Storage,
+    ValueStorageByteMap,
+};
 
 impl Storage {
-    pub fn flush(&self, table: &Table) {
-        let path = self.get_table_storage_path(&table.name);
-        let bytes = Self::table_to_bytes(table);
+    /// write the table with all its rows to a new file, which replaces the old one if any
+    pub fn flush(&self, table: &Table) -> Result<()> {
+        self.write_heap(&table.name, || {
+            let mut heap = HeapFile::create(self, &table.name, &Self::schema_to_bytes(table)?)?;
+            for record in table.rows.iter() {
+                let bytes = Self::record_to_bytes(record, &table.columns, &mut |data| {
+                    heap.write_chain(data)
+                })?;
+                heap.insert(&bytes)?;
+            }
+            Ok(heap)
+        })
+    }
+
+    /// replace the definition of the table, whose columns keep their types
+    /// so that the records stay as they are
+    pub fn write_schema(&self, table: &Table) -> Result<()> {
+        let schema = Self::schema_to_bytes(table)?;
+        self.write_records(&table.name, |heap| heap.set_schema(&schema))
+    }
+
+    /// store the records in the table, whose definition is `table`,
+    /// returning where they are kept
+    pub(crate) fn insert(&self, table: &Table, records: &[Record]) -> Result<Vec<RecordId>> {
+        self.write_records(&table.name, |heap| {
+            let mut ids = vec![];
+            for record in records.iter() {
+                let bytes = Self::record_to_bytes(record, &table.columns, &mut |data| {
+                    heap.write_chain(data)
+                })?;
+                ids.push(heap.insert(&bytes)?);
+            }
+            Ok(ids)
+        })
+    }
 
-        let dir = std::path::Path::new(&path).parent().unwrap();
-        std::fs::create_dir_all(dir).unwrap();
+    /// replace the records kept at the ids, each in its page if that has room for it,
+    /// returning where they are kept now
+    pub(crate) fn update(
+        &self,
+        table: &Table,
+        records: &[(RecordId, Record)],
+    ) -> Result<Vec<RecordId>> {
+        self.write_records(&table.name, |heap| {
+            let mut ids = vec![];
+            for (id, record) in records.iter() {
+                Self::free_values(heap, *id, &table.columns)?;
+                let bytes = Self::record_to_bytes(record, &table.columns, &mut |data| {
+                    heap.write_chain(data)
+                })?;
+                ids.push(heap.update(*id, &bytes)?);
+            }
+            Ok(ids)
+        })
+    }
 
-        std::fs::write(path, bytes).unwrap();
+    pub(crate) fn delete(&self, table: &Table, ids: &[RecordId]) -> Result<()> {
+        self.write_records(&table.name, |heap| {
+            for id in ids.iter() {
+                Self::free_values(heap, *id, &table.columns)?;
+                heap.delete(*id)?;
+            }
+            Ok(())
+        })
     }
 
-    fn table_to_bytes(table: &Table) -> Vec<u8> {
+    /// change the records of an existing table file in place,
+    /// so that only the pages they are kept in are written
+    fn write_records<T>(
+        &self,
+        table_name: &str,
+        write: impl FnOnce(&mut HeapFile) -> Result<T>,
+    ) -> Result<T> {
+        let mut value = None;
+        self.write_heap(table_name, || {
+            let mut heap = self.open_heap(table_name)?.ok_or_else(|| {
+                Error::Invalid(format!("table {} has no storage file", table_name))
+            })?;
+            value = Some(write(&mut heap)?);
+            Ok(heap)
+        })?;
+        Ok(value.unwrap())
+    }
+
+    /// sync the file `write` returns, in a transaction
+    fn write_heap<'a>(
+        &'a self,
+        table_name: &str,
+        write: impl FnOnce() -> Result<HeapFile<'a>>,
+    ) -> Result<()> {
+        let result = self.in_transaction(|| write()?.sync());
+        if result.is_err() {
+            // the pages changed before the failure are not to be read back
+            self.buffer.borrow_mut().discard_table(table_name);
+        }
+        result
+    }
+
+    /// return the overflow pages of the values of the record to the free list
+    fn free_values(
+        heap: &mut HeapFile,
+        id: RecordId,
+        columns: &[(String, DataType)],
+    ) -> Result<()> {
+        for first_page in Self::record_chains(&heap.record(id)?, columns)? {
+            heap.free_chain(first_page)?;
+        }
+        Ok(())
+    }
+
+    /// the name, columns and constraints of the table
+    pub(super) fn schema_to_bytes(table: &Table) -> Result<Vec<u8>> {
         let mut b = vec![];
 
         // name
-        b.extend_from_slice(&(table.name.len() as u8).to_be_bytes());
+        b.extend_from_slice(&Self::len_u16(table.name.len(), "table name")?.to_be_bytes());
         b.extend_from_slice(table.name.as_bytes());
 
         // columns
-        b.extend_from_slice(&(table.columns.len() as u16).to_be_bytes());
+        b.extend_from_slice(&Self::len_u16(table.columns.len(), "column count")?.to_be_bytes());
         for (column_name, data_type) in table.columns.iter() {
-            b.extend_from_slice(&(column_name.len() as u16).to_be_bytes());
+            b.extend_from_slice(&Self::len_u16(column_name.len(), "column name")?.to_be_bytes());
             b.extend_from_slice(column_name.as_bytes());
             b.extend_from_slice(&Self::data_type_to_bytes(data_type));
         }
 
-        // records
-        b.extend_from_slice(&(table.rows.len() as u16).to_be_bytes());
-        for record in table.rows.iter() {
-            for (idx, value) in record.values.iter().enumerate() {
-                b.extend_from_slice(&Self::test_value_as_bytes(value, &table.columns[idx].1));
+        b.extend_from_slice(
+            &Self::len_u16(table.constraints.len(), "constraint count")?.to_be_bytes(),
+        );
+        for constraint in table.constraints.iter() {
+            b.extend_from_slice(&Self::constraint_to_bytes(table, constraint)?);
+        }
+        Ok(b)
+    }
+
+    /// the null bitmap and the values, those too large for the record handed to `overflow`
+    /// which stores them and returns their first page
+    pub(super) fn record_to_bytes(
+        record: &Record,
+        columns: &[(String, DataType)],
+        overflow: &mut dyn FnMut(&[u8]) -> Result<PageId>,
+    ) -> Result<Vec<u8>> {
+        let mut b = Self::null_bitmap(record);
+        for (value, (_, data_type)) in record.values.iter().zip(columns) {
+            if *value == Value::Null {
+                continue;
             }
+            b.extend_from_slice(&Self::record_value_to_bytes(value, data_type, overflow)?);
+        }
+        Ok(b)
+    }
+
+    /// a length that must fit the u16 it is written as, refused rather than wrapped
+    pub(super) fn len_u16(len: usize, what: &str) -> Result<u16> {
+        u16::try_from(len).map_err(|_| Error::Invalid(format!("{} too long: {}", what, len)))
+    }
+
+    /// one bit per column, set when the value is NULL (which then takes no space)
+    fn null_bitmap(record: &Record) -> Vec<u8> {
+        let mut bitmap = vec![0u8; Self::null_bitmap_len(record.values.len())];
+        for (idx, value) in record.values.iter().enumerate() {
+            if *value == Value::Null {
+                bitmap[idx / 8] |= 1 << (idx % 8);
+            }
+        }
+        bitmap
+    }
+
+    fn constraint_to_bytes(table: &Table, constraint: &Constraint) -> Result<Vec<u8>> {
+        let string = |b: &mut Vec<u8>, s: &str| -> Result<()> {
+            b.extend_from_slice(&Self::len_u16(s.len(), "constraint string")?.to_be_bytes());
+            b.extend_from_slice(s.as_bytes());
+            Ok(())
+        };
+        let string_list = |b: &mut Vec<u8>, names: &[String]| -> Result<()> {
+            b.extend_from_slice(
+                &Self::len_u16(names.len(), "constraint column list")?.to_be_bytes(),
+            );
+            for name in names.iter() {
+                string(b, name)?;
+            }
+            Ok(())
+        };
+        let mut b = vec![];
+        match constraint {
+            Constraint::PrimaryKey(names) | Constraint::Unique(names) => {
+                b.push(match constraint {
+                    Constraint::PrimaryKey(_) => ConstraintByteMap::PRIMARY_KEY,
+                    _ => ConstraintByteMap::UNIQUE,
+                });
+                string_list(&mut b, names)?;
+            }
+            Constraint::NotNull(name) => {
+                b.push(ConstraintByteMap::NOT_NULL);
+                string(&mut b, name)?;
+            }
+            Constraint::Default(name, value) => {
+                b.push(ConstraintByteMap::DEFAULT);
+                string(&mut b, name)?;
+                // null flag, followed by the value encoded as the column type
+                if *value == Value::Null {
+                    b.push(1);
+                } else {
+                    b.push(0);
+                    let data_type = table
+                        .columns
+                        .iter()
+                        .find(|(column_name, _)| column_name == name)
+                        .map(|(_, data_type)| data_type)
+                        .ok_or_else(|| {
+                            Error::Invalid(format!("default refers to unknown column {}", name))
+                        })?;
+                    b.extend_from_slice(&Self::value_to_bytes(value, data_type)?);
+                }
+            }
+            Constraint::Check(expr) => {
+                // kept as sql text, parsed back on load
+                b.push(ConstraintByteMap::CHECK);
+                string(&mut b, &expr.to_string())?;
+            }
+            Constraint::ForeignKey(foreign_key) => {
+                b.push(ConstraintByteMap::FOREIGN_KEY);
+                string_list(&mut b, &foreign_key.columns)?;
+                string(&mut b, &foreign_key.ref_table)?;
+                string_list(&mut b, &foreign_key.ref_columns)?;
+                b.push(Self::referential_action_to_byte(foreign_key.on_delete));
+                b.push(Self::referential_action_to_byte(foreign_key.on_update));
+            }
+        }
+        Ok(b)
+    }
+
+    fn referential_action_to_byte(action: ReferentialAction) -> u8 {
+        match action {
+            ReferentialAction::Restrict => ReferentialActionByteMap::RESTRICT,
+            ReferentialAction::Cascade => ReferentialActionByteMap::CASCADE,
+            ReferentialAction::SetNull => ReferentialActionByteMap::SET_NULL,
+            ReferentialAction::SetDefault => ReferentialActionByteMap::SET_DEFAULT,
         }
-        b
     }
 
     fn data_type_to_bytes(data_type: &DataType) -> Vec<u8> {
         match data_type {
             DataType::Int => vec![DataTypeByteMap::INT],
+            DataType::BigInt => vec![DataTypeByteMap::BIGINT],
+            DataType::Boolean => vec![DataTypeByteMap::BOOLEAN],
+            DataType::Double => vec![DataTypeByteMap::DOUBLE],
+            DataType::Decimal(precision, scale) => {
+                vec![DataTypeByteMap::DECIMAL, *precision, *scale]
+            }
             DataType::VarChar(size) => {
                 let mut b = vec![DataTypeByteMap::VARCHAR];
                 b.extend_from_slice(&size.to_be_bytes());
                 b
             }
+            DataType::Date => vec![DataTypeByteMap::DATE],
+            DataType::Time => vec![DataTypeByteMap::TIME],
+            DataType::Timestamp => vec![DataTypeByteMap::TIMESTAMP],
+            DataType::Interval => vec![DataTypeByteMap::INTERVAL],
+            DataType::Text => vec![DataTypeByteMap::TEXT],
+            DataType::Blob => vec![DataTypeByteMap::BLOB],
+            DataType::Json => vec![DataTypeByteMap::JSON],
         }
     }
 
-    fn test_value_as_bytes(value: &Value, data_type: &DataType) -> Vec<u8> {
-        match value {
-            Value::Int(value) => value.to_be_bytes().to_vec(),
-            Value::VarChar(value) => match data_type {
-                DataType::VarChar(_) => {
-                    let mut b = vec![];
-                    b.extend_from_slice(&(value.len() as u16).to_be_bytes());
-                    b.extend_from_slice(value.as_bytes());
-                    b
+    /// like `value_to_bytes`, but a TEXT, BLOB or JSON value over the threshold is moved to
+    /// overflow pages, leaving only its length and first page in the record
+    fn record_value_to_bytes(
+        value: &Value,
+        data_type: &DataType,
+        overflow: &mut dyn FnMut(&[u8]) -> Result<PageId>,
+    ) -> Result<Vec<u8>> {
+        let data = match (value, data_type) {
+            (Value::VarChar(value), DataType::Text) => Cow::Borrowed(value.as_bytes()),
+            (Value::Blob(value), DataType::Blob) => Cow::Borrowed(value.as_slice()),
+            (Value::Json(value), DataType::Json) => Cow::Owned(Self::json_to_bytes(value)),
+            _ => return Self::value_to_bytes(value, data_type),
+        };
+        if data.len() <= OVERFLOW_THRESHOLD {
+            return Self::inline_bytes(&data);
+        }
+        let first_page = overflow(&data)?;
+        let mut b = vec![ValueStorageByteMap::OVERFLOW];
+        b.extend_from_slice(&(data.len() as u64).to_be_bytes());
+        b.extend_from_slice(&first_page.to_be_bytes());
+        Ok(b)
+    }
+
+    /// values are checked by the executor before they reach storage,
+    /// but one that does not fit its column is refused rather than written corrupted
+    fn value_to_bytes(value: &Value, data_type: &DataType) -> Result<Vec<u8>> {
+        match (value, data_type) {
+            (Value::Int(value), DataType::Int) => Ok(value.to_be_bytes().to_vec()),
+            (Value::BigInt(value), DataType::BigInt) => Ok(value.to_be_bytes().to_vec()),
+            (Value::Boolean(value), DataType::Boolean) => Ok(vec![*value as u8]),
+            (Value::Double(value), DataType::Double) => Ok(value.to_be_bytes().to_vec()),
+            // the scale is that of the column, only the mantissa is stored
+            (Value::Decimal(value), DataType::Decimal(precision, scale)) => {
+                if value.scale() != *scale || value.precision() > *precision {
+                    return Err(Error::Type(format!(
+                        "numeric {} does not fit DECIMAL({}, {})",
+                        value, precision, scale
+                    )));
                 }
-                _ => panic!("data type mismatch"),
-            },
+                Ok(value.mantissa().to_be_bytes().to_vec())
+            }
+            (Value::VarChar(value), DataType::VarChar(size)) => {
+                let len = u16::try_from(value.len()).map_err(|_| {
+                    Error::Type(format!("value too long for type VARCHAR({})", size))
+                })?;
+                if value.chars().count() > *size as usize {
+                    return Err(Error::Type(format!(
+                        "value too long for type VARCHAR({})",
+                        size
+                    )));
+                }
+                let mut b = vec![];
+                b.extend_from_slice(&len.to_be_bytes());
+                b.extend_from_slice(value.as_bytes());
+                Ok(b)
+            }
+            // days since 1970-01-01
+            (Value::Date(value), DataType::Date) => {
+                Ok((value.days() as i32).to_be_bytes().to_vec())
+            }
+            // microseconds since midnight and since 1970-01-01 00:00:00
+            (Value::Time(value), DataType::Time) => Ok(value.micros().to_be_bytes().to_vec()),
+            (Value::Timestamp(value), DataType::Timestamp) => {
+                Ok(value.micros().to_be_bytes().to_vec())
+            }
+            // months, days and microseconds
+            (Value::Interval(value), DataType::Interval) => {
+                let mut b = vec![];
+                b.extend_from_slice(&value.months().to_be_bytes());
+                b.extend_from_slice(&value.days().to_be_bytes());
+                b.extend_from_slice(&value.micros().to_be_bytes());
+                Ok(b)
+            }
+            (Value::VarChar(value), DataType::Text) => Self::inline_bytes(value.as_bytes()),
+            (Value::Blob(value), DataType::Blob) => Self::inline_bytes(value),
+            (Value::Json(value), DataType::Json) => Self::inline_bytes(&Self::json_to_bytes(value)),
+            (Value::Null, _) => unreachable!("NULL is recorded in the null bitmap"),
+            (value, data_type) => Err(Error::Type(format!(
+                "data type mismatch: cannot store {:?} as {:?}",
+                value, data_type
+            ))),
         }
     }
+
+    fn json_to_bytes(json: &Json) -> Vec<u8> {
+        let mut b = vec![];
+        Self::write_json(&mut b, json);
+        b
+    }
+
+    fn write_json(b: &mut Vec<u8>, json: &Json) {
+        let string = |b: &mut Vec<u8>, s: &str| {
+            b.extend_from_slice(&(s.len() as u32).to_be_bytes());
+            b.extend_from_slice(s.as_bytes());
+        };
+        match json {
+            Json::Null => b.push(JsonByteMap::NULL),
+            Json::Bool(false) => b.push(JsonByteMap::FALSE),
+            Json::Bool(true) => b.push(JsonByteMap::TRUE),
+            Json::Number(v) => {
+                b.push(JsonByteMap::NUMBER);
+                b.extend_from_slice(&v.to_be_bytes());
+            }
+            Json::String(s) => {
+                b.push(JsonByteMap::STRING);
+                string(b, s);
+            }
+            Json::Array(elements) => {
+                b.push(JsonByteMap::ARRAY);
+                b.extend_from_slice(&(elements.len() as u32).to_be_bytes());
+                for element in elements {
+                    Self::write_json(b, element);
+                }
+            }
+            Json::Object(members) => {
+                b.push(JsonByteMap::OBJECT);
+                b.extend_from_slice(&(members.len() as u32).to_be_bytes());
+                for (key, value) in members {
+                    string(b, key);
+                    Self::write_json(b, value);
+                }
+            }
+        }
+    }
+
+    fn inline_bytes(data: &[u8]) -> Result<Vec<u8>> {
+        let len = u32::try_from(data.len())
+            .map_err(|_| Error::Type(format!("value too large: {} bytes", data.len())))?;
+        let mut b = vec![ValueStorageByteMap::INLINE];
+        b.extend_from_slice(&len.to_be_bytes());
+        b.extend_from_slice(data);
+        Ok(b)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::core::table::Record;
+    use std::io::Read;
+
+    use crate::core::{
+        datetime::{Date, Interval},
+        decimal::Decimal,
+        storage::page::PAGE_SIZE,
+    };
 
     use super::*;
 
@@ -89,22 +448,174 @@ mod test {
             Storage::data_type_to_bytes(&DataType::VarChar(0xffff)),
             vec![DataTypeByteMap::VARCHAR, 0xff, 0xff]
         );
+        assert_eq!(
+            Storage::data_type_to_bytes(&DataType::Decimal(10, 2)),
+            vec![DataTypeByteMap::DECIMAL, 0x0a, 0x02]
+        );
+        assert_eq!(
+            Storage::data_type_to_bytes(&DataType::Boolean),
+            vec![DataTypeByteMap::BOOLEAN]
+        );
+        assert_eq!(
+            Storage::data_type_to_bytes(&DataType::Timestamp),
+            vec![DataTypeByteMap::TIMESTAMP]
+        );
     }
 
     #[test]
     fn test_value_as_bytes() {
         assert_eq!(
-            Storage::test_value_as_bytes(&Value::Int(1), &DataType::Int),
+            Storage::value_to_bytes(&Value::Int(1), &DataType::Int).unwrap(),
             vec![0x00, 0x00, 0x00, 0x01]
         );
 
         assert_eq!(
-            Storage::test_value_as_bytes(
+            Storage::value_to_bytes(
                 &Value::VarChar(String::from("hello")),
                 &DataType::VarChar(10)
-            ),
+            )
+            .unwrap(),
             vec![0, 0x05, 0x68, 0x65, 0x6c, 0x6c, 0x6f]
         );
+
+        assert_eq!(
+            Storage::value_to_bytes(&Value::BigInt(-2), &DataType::BigInt).unwrap(),
+            vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]
+        );
+        assert_eq!(
+            Storage::value_to_bytes(&Value::Boolean(true), &DataType::Boolean).unwrap(),
+            vec![0x01]
+        );
+        assert_eq!(
+            Storage::value_to_bytes(&Value::Double(1.5), &DataType::Double).unwrap(),
+            1.5f64.to_be_bytes().to_vec()
+        );
+        let mut decimal = vec![0x00; 15];
+        decimal.push(0x7b);
+        assert_eq!(
+            Storage::value_to_bytes(
+                &Value::Decimal(Decimal::new(123, 2)),
+                &DataType::Decimal(5, 2)
+            )
+            .unwrap(),
+            decimal
+        );
+        assert_eq!(
+            Storage::value_to_bytes(
+                &Value::Date(Date::parse("1970-01-02").unwrap()),
+                &DataType::Date
+            )
+            .unwrap(),
+            vec![0x00, 0x00, 0x00, 0x01]
+        );
+        assert_eq!(
+            Storage::value_to_bytes(
+                &Value::Interval(Interval::new(1, -1, 2)),
+                &DataType::Interval
+            )
+            .unwrap(),
+            vec![
+                0x00, 0x00, 0x00, 0x01, // months
+                0xff, 0xff, 0xff, 0xff, // days
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, // microseconds
+            ]
+        );
+        assert_eq!(
+            Storage::value_to_bytes(&Value::VarChar(String::from("a")), &DataType::Text).unwrap(),
+            vec![ValueStorageByteMap::INLINE, 0x00, 0x00, 0x00, 0x01, 0x61]
+        );
+        assert_eq!(
+            Storage::value_to_bytes(&Value::Blob(vec![0xde, 0xad]), &DataType::Blob).unwrap(),
+            vec![
+                ValueStorageByteMap::INLINE,
+                0x00,
+                0x00,
+                0x00,
+                0x02,
+                0xde,
+                0xad
+            ]
+        );
+        assert!(matches!(
+            Storage::value_to_bytes(&Value::VarChar(String::from("a")), &DataType::Blob),
+            Err(Error::Type(_))
+        ));
+        assert_eq!(
+            Storage::value_to_bytes(
+                &Value::Json(Json::parse(r#"{"a": [1]}"#).unwrap()),
+                &DataType::Json
+            )
+            .unwrap(),
+            vec![
+                ValueStorageByteMap::INLINE,
+                0x00,
+                0x00,
+                0x00,
+                0x18, // length
+                JsonByteMap::OBJECT,
+                0x00,
+                0x00,
+                0x00,
+                0x01, // members
+                0x00,
+                0x00,
+                0x00,
+                0x01,
+                0x61, // "a"
+                JsonByteMap::ARRAY,
+                0x00,
+                0x00,
+                0x00,
+                0x01, // elements
+                JsonByteMap::NUMBER,
+                0x3f,
+                0xf0,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00, // 1.0
+            ]
+        );
+        assert!(matches!(
+            Storage::value_to_bytes(
+                &Value::Date(Date::parse("1970-01-02").unwrap()),
+                &DataType::Timestamp
+            ),
+            Err(Error::Type(_))
+        ));
+        // the scale must already match the column
+        assert!(matches!(
+            Storage::value_to_bytes(
+                &Value::Decimal(Decimal::new(123, 1)),
+                &DataType::Decimal(5, 2)
+            ),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            Storage::value_to_bytes(
+                &Value::Decimal(Decimal::new(123456, 2)),
+                &DataType::Decimal(5, 2)
+            ),
+            Err(Error::Type(_))
+        ));
+
+        assert!(matches!(
+            Storage::value_to_bytes(&Value::VarChar(String::from("hello")), &DataType::Int),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            Storage::value_to_bytes(&Value::Int(1), &DataType::VarChar(10)),
+            Err(Error::Type(_))
+        ));
+        assert!(matches!(
+            Storage::value_to_bytes(
+                &Value::VarChar(String::from("hello")),
+                &DataType::VarChar(4)
+            ),
+            Err(Error::Type(_))
+        ));
     }
 
     #[test]
@@ -129,9 +640,9 @@ mod test {
         ]));
 
         assert_eq!(
-            Storage::table_to_bytes(&user_table),
+            Storage::schema_to_bytes(&user_table).unwrap(),
             vec![
-                0x04, // name length
+                0x00, 0x04, // name length
                 0x75, 0x73, 0x65, 0x72, // user
                 0x00, 0x02, // 2 columns
                 0x00, 0x02, // column name length
@@ -140,14 +651,303 @@ mod test {
                 0x00, 0x04, // column name length
                 0x6e, 0x61, 0x6d, 0x65, // name
                 0x0a, 0x00, 0x0a, // varchar(10)
-                0x00, 0x02, // records length
-                0x00, 0x00, 0x00, 0x01, // 1
-                0x00, 0x05, // alice length
-                0x61, 0x6c, 0x69, 0x63, 0x65, // alice
-                0x00, 0x00, 0x00, 0x02, // 2
-                0x00, 0x03, // bob length
-                0x62, 0x6f, 0x62, // bob
+                0x00, 0x00, // no constraints
+            ]
+        );
+        let records = user_table
+            .rows
+            .iter()
+            .map(|record| {
+                Storage::record_to_bytes(record, &user_table.columns, &mut |_| {
+                    unreachable!("no value should overflow")
+                })
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            vec![
+                vec![
+                    0x00, // null bitmap
+                    0x00, 0x00, 0x00, 0x01, // 1
+                    0x00, 0x05, // alice length
+                    0x61, 0x6c, 0x69, 0x63, 0x65, // alice
+                ],
+                vec![
+                    0x00, // null bitmap
+                    0x00, 0x00, 0x00, 0x02, // 2
+                    0x00, 0x03, // bob length
+                    0x62, 0x6f, 0x62, // bob
+                ],
+            ]
+        );
+
+        let long_name = Table::new("t".repeat(0x10000), vec![], vec![]);
+        assert!(matches!(
+            Storage::schema_to_bytes(&long_name),
+            Err(Error::Invalid(_))
+        ));
+        let long_key = Table::new(String::from("t"), vec![], vec![])
+            .with_constraints(vec![Constraint::Unique(vec![String::from("id"); 0x10000])]);
+        assert!(matches!(
+            Storage::schema_to_bytes(&long_key),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_records() {
+        let dir = std::env::temp_dir().join(format!("ubdb-test-pages-{}", std::process::id()));
+        let storage = Storage::new(dir.to_str().unwrap().to_string());
+        let path = storage.get_table_storage_path("user");
+        let pages = || {
+            std::fs::read(&path)
+                .unwrap()
+                .chunks(PAGE_SIZE)
+                .map(<[u8]>::to_vec)
+                .collect::<Vec<_>>()
+        };
+        let mut table = Table::new(
+            String::from("user"),
+            vec![
+                (String::from("id"), DataType::Int),
+                (String::from("name"), DataType::VarChar(20)),
+            ],
+            (0..1000)
+                .map(|id| Record::new(vec![Value::Int(id), Value::VarChar(format!("user{}", id))]))
+                .collect(),
+        );
+        storage.flush(&table).unwrap();
+        let ids = storage
+            .scan(&table)
+            .unwrap()
+            .unwrap()
+            .map(|row| row.unwrap().0)
+            .collect::<Vec<_>>();
+        let before = pages();
+
+        // the updated record takes the slot of the old one
+        table.rows[500].values[1] = Value::VarChar(String::from("updated"));
+        let moved = storage
+            .update(&table, &[(ids[500], table.rows[500].clone())])
+            .unwrap();
+        assert_eq!(moved, vec![ids[500]]);
+        let after = pages();
+        assert_eq!(before.len(), after.len());
+        assert_eq!(
+            before
+                .iter()
+                .zip(after.iter())
+                .filter(|(b, a)| b != a)
+                .count(),
+            1
+        );
+        assert_eq!(storage.load("user").unwrap().as_ref(), Some(&table));
+
+        // inserted records are appended
+        let before = after;
+        table.insert(Record::new(vec![Value::Int(1000), Value::Null]));
+        storage.insert(&table, &table.rows[1000..]).unwrap();
+        let after = pages();
+        assert_eq!(before[..before.len() - 1], after[..before.len() - 1]);
+        assert_eq!(storage.load("user").unwrap().as_ref(), Some(&table));
+
+        table.rows.remove(3);
+        storage.delete(&table, &ids[3..4]).unwrap();
+        assert_eq!(storage.load("user").unwrap().as_ref(), Some(&table));
+
+        // a new name for a column leaves the records where they are
+        let before = pages();
+        table.columns[1].0 = String::from("user_name");
+        storage.write_schema(&table).unwrap();
+        let after = pages();
+        assert_eq!(before[2..], after[2..]);
+        assert_eq!(storage.load("user").unwrap(), Some(table));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_flush_replaces_file() {
+        let dir = std::env::temp_dir().join(format!("ubdb-test-replace-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let storage = Storage::new(dir.to_str().unwrap().to_string());
+        let path = storage.get_table_storage_path("user");
+        let mut table = Table::new(
+            String::from("user"),
+            vec![(String::from("id"), DataType::Int)],
+            (0..1000)
+                .map(|id| Record::new(vec![Value::Int(id)]))
+                .collect(),
+        );
+        storage.flush(&table).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        let mut old = std::fs::File::open(&path).unwrap();
+
+        // a new definition is written to another file, which takes the place of the old one
+        table.columns[0].0 = String::from("user_id");
+        storage.flush(&table).unwrap();
+        let mut old_bytes = vec![];
+        old.read_to_end(&mut old_bytes).unwrap();
+        assert_eq!(old_bytes, bytes);
+        assert_eq!(storage.load("user").unwrap(), Some(table));
+        // nothing is left of the temporary file, nor of the old one
+        let mut names = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["ubdb.wal", "user.ubdb"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_flush_large_table() {
+        let dir = std::env::temp_dir().join(format!("ubdb-test-large-{}", std::process::id()));
+        let storage = Storage::new(dir.to_str().unwrap().to_string());
+        let mut table = Table::new(
+            String::from("t"),
+            vec![
+                (String::from("id"), DataType::Int),
+                (String::from("note"), DataType::VarChar(10_000)),
+            ],
+            (0..10_000)
+                .map(|id| Record::new(vec![Value::Int(id), Value::Null]))
+                .collect(),
+        );
+        // and a record larger than a page
+        table.rows[100].values[1] = Value::VarChar("a".repeat(10_000));
+        storage.flush(&table).unwrap();
+        assert_eq!(storage.load(&table.name).unwrap().as_ref(), Some(&table));
+
+        table.rows[100].values[1] = Value::Null;
+        table.rows.truncate(1);
+        storage.flush(&table).unwrap();
+        assert_eq!(storage.load(&table.name).unwrap(), Some(table));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_record_value_to_bytes() {
+        let mut overflowed = vec![];
+        let mut overflow = |data: &[u8]| {
+            overflowed.push(data.to_vec());
+            Ok(7)
+        };
+        let small = Value::VarChar("a".repeat(OVERFLOW_THRESHOLD));
+        assert_eq!(
+            Storage::record_value_to_bytes(&small, &DataType::Text, &mut overflow).unwrap(),
+            Storage::value_to_bytes(&small, &DataType::Text).unwrap()
+        );
+
+        let large = Value::Blob(vec![0xff; OVERFLOW_THRESHOLD + 1]);
+        let mut expected = vec![ValueStorageByteMap::OVERFLOW];
+        expected.extend_from_slice(&(OVERFLOW_THRESHOLD as u64 + 1).to_be_bytes());
+        expected.extend_from_slice(&7u32.to_be_bytes());
+        assert_eq!(
+            Storage::record_value_to_bytes(&large, &DataType::Blob, &mut overflow).unwrap(),
+            expected
+        );
+        assert_eq!(
+            Storage::record_value_to_bytes(&Value::Int(1), &DataType::Int, &mut overflow).unwrap(),
+            vec![0x00, 0x00, 0x00, 0x01]
+        );
+        assert_eq!(overflowed, vec![vec![0xff; OVERFLOW_THRESHOLD + 1]]);
+    }
+
+    #[test]
+    fn test_constraint_to_bytes() {
+        let table = Table::new(
+            String::from("t"),
+            vec![(String::from("id"), DataType::Int)],
+            vec![],
+        );
+        assert_eq!(
+            Storage::constraint_to_bytes(&table, &Constraint::PrimaryKey(vec![String::from("id")]))
+                .unwrap(),
+            vec![
+                ConstraintByteMap::PRIMARY_KEY,
+                0x00,
+                0x01,
+                0x00,
+                0x02,
+                0x69,
+                0x64
+            ]
+        );
+        assert_eq!(
+            Storage::constraint_to_bytes(
+                &table,
+                &Constraint::Default(String::from("id"), Value::Int(7))
+            )
+            .unwrap(),
+            vec![
+                ConstraintByteMap::DEFAULT,
+                0x00,
+                0x02,
+                0x69,
+                0x64, // id
+                0x00, // not null
+                0x00,
+                0x00,
+                0x00,
+                0x07, // 7
+            ]
+        );
+        assert_eq!(
+            Storage::constraint_to_bytes(
+                &table,
+                &Constraint::Default(String::from("id"), Value::Null)
+            )
+            .unwrap(),
+            vec![ConstraintByteMap::DEFAULT, 0x00, 0x02, 0x69, 0x64, 0x01]
+        );
+        assert_eq!(
+            Storage::constraint_to_bytes(
+                &table,
+                &Constraint::ForeignKey(crate::query::ast::ForeignKey {
+                    columns: vec![String::from("id")],
+                    ref_table: String::from("u"),
+                    ref_columns: vec![String::from("id")],
+                    on_delete: ReferentialAction::Cascade,
+                    on_update: ReferentialAction::SetNull,
+                })
+            )
+            .unwrap(),
+            vec![
+                ConstraintByteMap::FOREIGN_KEY,
+                0x00,
+                0x01,
+                0x00,
+                0x02,
+                0x69,
+                0x64, // (id)
+                0x00,
+                0x01,
+                0x75, // u
+                0x00,
+                0x01,
+                0x00,
+                0x02,
+                0x69,
+                0x64, // (id)
+                ReferentialActionByteMap::CASCADE,
+                ReferentialActionByteMap::SET_NULL,
             ]
-        )
+        );
+    }
+
+    #[test]
+    fn test_null_bitmap() {
+        let mut values = vec![Value::Int(1); 9];
+        values[1] = Value::Null;
+        values[8] = Value::Null;
+        assert_eq!(
+            Storage::null_bitmap(&Record::new(values)),
+            vec![0b0000_0010, 0b0000_0001]
+        );
+        assert_eq!(
+            Storage::null_bitmap(&Record::new(vec![Value::Int(1)])),
+            vec![0]
+        );
     }
 }
@@ -0,0 +1,558 @@
+use crate::error::{Error, Result};
+
+use super::{
+    page::{PageId, SlottedPage, NO_PAGE, PAGE_SIZE},
+    PageKindByteMap, Storage, ValueStorageByteMap,
+};
+
+/// where a record is kept, until it is deleted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct RecordId {
+    pub page: PageId,
+    pub slot: u16,
+}
+
+/// a table file of fixed-size pages
+///
+/// the meta page comes first, followed by the data pages holding the records,
+/// the overflow pages holding what does not fit them and the pages freed for reuse, in any order;
+/// pages are read and written through the buffer pool of the storage,
+/// the changed ones reaching the file on `sync` if they were not evicted before,
+/// while a new file is kept in memory until it replaces the old one whole on `sync`
+pub(crate) struct HeapFile<'a> {
+    storage: &'a Storage,
+    table_name: String,
+    pages_len: u32,
+    // head of the list of free pages
+    free_page: PageId,
+    // length and first page of the chain holding the table definition
+    schema: (u64, PageId),
+    // data pages that had records deleted, tried first for new ones
+    insert_pages: Vec<PageId>,
+    // the pages of a new file, not written yet
+    new_pages: Option<Vec<Vec<u8>>>,
+}
+
+impl<'a> HeapFile<'a> {
+    // magic, version (u16), first free page (u32), schema length (u64) and first page (u32)
+    const META_SIZE: usize = Storage::FILE_MAGIC.len() + 2 + 4 + 8 + 4;
+    // kind (u8), next page (u32), data length (u16)
+    const CHAIN_HEADER_SIZE: usize = 7;
+    const CHAIN_DATA_SIZE: usize = PAGE_SIZE - Self::CHAIN_HEADER_SIZE;
+    // tag (u8), length (u64), first page (u32) of a record moved to overflow pages
+    const RECORD_STUB_SIZE: usize = 13;
+
+    /// a new file holding only the table definition, to replace any existing one on `sync`
+    pub fn create(storage: &'a Storage, table_name: &str, schema: &[u8]) -> Result<Self> {
+        let mut heap = Self {
+            storage,
+            table_name: table_name.to_string(),
+            pages_len: 1,
+            free_page: NO_PAGE,
+            schema: (0, NO_PAGE),
+            insert_pages: vec![],
+            new_pages: Some(vec![]),
+        };
+        heap.schema = (schema.len() as u64, heap.write_chain(schema)?);
+        heap.write_meta()?;
+        Ok(heap)
+    }
+
+    /// the file must have been checked to start with the header of the current format
+    pub fn open(storage: &'a Storage, table_name: &str) -> Result<Self> {
+        let len = std::fs::metadata(storage.get_table_storage_path(table_name))?.len();
+        if len == 0 || !len.is_multiple_of(PAGE_SIZE as u64) {
+            return Err(Error::Corruption(String::from(
+                "table file is not page aligned",
+            )));
+        }
+        let pages_len = u32::try_from(len / PAGE_SIZE as u64)
+            .ok()
+            .filter(|pages_len| *pages_len < NO_PAGE)
+            .ok_or_else(|| Error::Corruption(String::from("too many pages")))?;
+        let mut heap = Self {
+            storage,
+            table_name: table_name.to_string(),
+            pages_len,
+            free_page: NO_PAGE,
+            schema: (0, NO_PAGE),
+            insert_pages: vec![],
+            new_pages: None,
+        };
+        let meta = heap.page(0)?;
+        let at = Storage::FILE_MAGIC.len() + 2;
+        heap.free_page = u32_at(&meta, at);
+        heap.schema = (u64_at(&meta, at + 4), u32_at(&meta, at + 12));
+        Ok(heap)
+    }
+
+    /// number of pages, including the meta page
+    pub fn pages_len(&self) -> u32 {
+        self.pages_len
+    }
+
+    /// the table definition
+    pub fn schema(&self) -> Result<Vec<u8>> {
+        self.read_chain(self.schema.1, self.schema.0)
+    }
+
+    /// replace the table definition, the pages of the old one going to the free list
+    pub fn set_schema(&mut self, schema: &[u8]) -> Result<()> {
+        self.free_chain(self.schema.1)?;
+        self.schema = (schema.len() as u64, self.write_chain(schema)?);
+        self.write_meta()
+    }
+
+    /// write the changed pages back to the file, or the new file in place of the old one
+    pub fn sync(&mut self) -> Result<()> {
+        if let Some(pages) = self.new_pages.take() {
+            return self.storage.replace_file(&self.table_name, &pages);
+        }
+        self.storage
+            .buffer
+            .borrow_mut()
+            .flush_table(self.storage, &self.table_name)
+    }
+
+    /// the live records of the page, none if it is not a data page
+    pub fn records(&self, page_id: PageId) -> Result<Vec<(RecordId, Vec<u8>)>> {
+        let page = self.page(page_id)?;
+        if page[0] != PageKindByteMap::DATA {
+            return Ok(vec![]);
+        }
+        SlottedPage::from_bytes(page)?
+            .records()
+            .map(|(slot, stored)| {
+                let id = RecordId {
+                    page: page_id,
+                    slot,
+                };
+                Ok((id, self.unwrap_record(stored)?))
+            })
+            .collect()
+    }
+
+    /// the record kept at `id`
+    pub fn record(&self, id: RecordId) -> Result<Vec<u8>> {
+        let page = SlottedPage::from_bytes(self.page(id.page)?)?;
+        self.unwrap_record(Self::stored(&page, id)?)
+    }
+
+    /// store the record in the first data page with room for it,
+    /// trying the pages records were deleted from, then the last page, then a new one
+    pub fn insert(&mut self, record: &[u8]) -> Result<RecordId> {
+        let stored = self.wrap_record(record)?;
+        self.insert_stored(&stored)
+    }
+
+    /// replace the record kept at `id`, in its page if that has room for the new one
+    /// and wherever `insert` puts it otherwise, returning where it is kept now
+    /// the overflow pages of the values of the old one are the caller's to free
+    pub fn update(&mut self, id: RecordId, record: &[u8]) -> Result<RecordId> {
+        let mut page = SlottedPage::from_bytes(self.page(id.page)?)?;
+        self.free_record_chain(Self::stored(&page, id)?)?;
+        let stored = self.wrap_record(record)?;
+        if page.update(id.slot, &stored) {
+            self.write_page(id.page, page.as_bytes().to_vec())?;
+            return Ok(id);
+        }
+        // not empty, or the record would have fit
+        page.delete(id.slot);
+        self.write_page(id.page, page.as_bytes().to_vec())?;
+        self.insert_stored(&stored)
+    }
+
+    /// delete the record, freeing its page once it has no other
+    /// the overflow pages of its values are the caller's to free
+    pub fn delete(&mut self, id: RecordId) -> Result<()> {
+        let mut page = SlottedPage::from_bytes(self.page(id.page)?)?;
+        self.free_record_chain(Self::stored(&page, id)?)?;
+        page.delete(id.slot);
+        if page.is_empty() {
+            self.free(id.page)?;
+        } else {
+            self.write_page(id.page, page.as_bytes().to_vec())?;
+            if !self.insert_pages.contains(&id.page) {
+                self.insert_pages.push(id.page);
+            }
+        }
+        Ok(())
+    }
+
+    /// store `data` in a chain of overflow pages, returning the first page
+    pub fn write_chain(&mut self, data: &[u8]) -> Result<PageId> {
+        let mut chunks = data.chunks(Self::CHAIN_DATA_SIZE).collect::<Vec<_>>();
+        if chunks.is_empty() {
+            chunks.push(&[]);
+        }
+        let pages = chunks
+            .iter()
+            .map(|_| self.allocate())
+            .collect::<Result<Vec<_>>>()?;
+        for (idx, chunk) in chunks.iter().enumerate() {
+            let next_page = pages.get(idx + 1).copied().unwrap_or(NO_PAGE);
+            let mut page = vec![0; PAGE_SIZE];
+            page[0] = PageKindByteMap::OVERFLOW;
+            page[1..5].copy_from_slice(&next_page.to_be_bytes());
+            page[5..7].copy_from_slice(&(chunk.len() as u16).to_be_bytes());
+            page[Self::CHAIN_HEADER_SIZE..Self::CHAIN_HEADER_SIZE + chunk.len()]
+                .copy_from_slice(chunk);
+            self.write_page(pages[idx], page)?;
+        }
+        Ok(pages[0])
+    }
+
+    /// the `len` bytes of the chain starting at `first_page`
+    pub fn read_chain(&self, first_page: PageId, len: u64) -> Result<Vec<u8>> {
+        let mut data =
+            Vec::with_capacity(len.min(self.pages_len as u64 * PAGE_SIZE as u64) as usize);
+        let mut page_id = first_page;
+        // a chain visits each page at most once, so a longer one is a cycle
+        for _ in 0..self.pages_len {
+            let page = self.chain_page(page_id)?;
+            let used = u16::from_be_bytes([page[5], page[6]]) as usize;
+            if used > Self::CHAIN_DATA_SIZE {
+                return Err(Error::Corruption(format!(
+                    "invalid overflow page {}",
+                    page_id
+                )));
+            }
+            data.extend_from_slice(&page[Self::CHAIN_HEADER_SIZE..Self::CHAIN_HEADER_SIZE + used]);
+            page_id = u32_at(&page, 1);
+            if page_id == NO_PAGE {
+                if data.len() as u64 != len {
+                    return Err(Error::Corruption(format!(
+                        "overflow value has the wrong length ({})",
+                        first_page
+                    )));
+                }
+                return Ok(data);
+            }
+        }
+        Err(Error::Corruption(format!(
+            "overflow chain does not end ({})",
+            first_page
+        )))
+    }
+
+    /// return the pages of the chain starting at `first_page` to the free list
+    pub fn free_chain(&mut self, first_page: PageId) -> Result<()> {
+        let mut page_id = first_page;
+        // a freed page is no overflow page anymore, so a cycle ends with an error
+        loop {
+            let next_page = u32_at(&self.chain_page(page_id)?, 1);
+            self.free(page_id)?;
+            if next_page == NO_PAGE {
+                return Ok(());
+            }
+            page_id = next_page;
+        }
+    }
+
+    fn insert_stored(&mut self, stored: &[u8]) -> Result<RecordId> {
+        while let Some(&page_id) = self.insert_pages.first() {
+            if let Some(id) = self.insert_into(page_id, stored)? {
+                return Ok(id);
+            }
+            // full for this record, and most likely for the next ones as well
+            self.insert_pages.remove(0);
+        }
+        let last_page = self.pages_len - 1;
+        if self.page(last_page)?[0] == PageKindByteMap::DATA {
+            if let Some(id) = self.insert_into(last_page, stored)? {
+                return Ok(id);
+            }
+        }
+        let page_id = self.allocate()?;
+        self.write_page(page_id, SlottedPage::new().as_bytes().to_vec())?;
+        self.insert_pages.push(page_id);
+        Ok(self
+            .insert_into(page_id, stored)?
+            .expect("a record should fit an empty page"))
+    }
+
+    fn insert_into(&mut self, page_id: PageId, stored: &[u8]) -> Result<Option<RecordId>> {
+        let mut page = SlottedPage::from_bytes(self.page(page_id)?)?;
+        let Some(slot) = page.insert(stored) else {
+            return Ok(None);
+        };
+        self.write_page(page_id, page.as_bytes().to_vec())?;
+        Ok(Some(RecordId {
+            page: page_id,
+            slot,
+        }))
+    }
+
+    /// a record as kept in its slot: tagged inline, or moved to overflow pages
+    /// when it is too large for a page
+    fn wrap_record(&mut self, record: &[u8]) -> Result<Vec<u8>> {
+        let mut b = vec![];
+        // the tag takes a byte of the slot
+        if record.len() < SlottedPage::MAX_RECORD_SIZE {
+            b.push(ValueStorageByteMap::INLINE);
+            b.extend_from_slice(record);
+        } else {
+            let first_page = self.write_chain(record)?;
+            b.push(ValueStorageByteMap::OVERFLOW);
+            b.extend_from_slice(&(record.len() as u64).to_be_bytes());
+            b.extend_from_slice(&first_page.to_be_bytes());
+        }
+        Ok(b)
+    }
+
+    fn unwrap_record(&self, stored: &[u8]) -> Result<Vec<u8>> {
+        match stored.first() {
+            Some(&ValueStorageByteMap::INLINE) => Ok(stored[1..].to_vec()),
+            Some(&ValueStorageByteMap::OVERFLOW) if stored.len() == Self::RECORD_STUB_SIZE => {
+                self.read_chain(u32_at(stored, 9), u64_at(stored, 1))
+            }
+            _ => Err(Error::Corruption(String::from("invalid record storage"))),
+        }
+    }
+
+    /// the record as kept in the slot of `id`
+    fn stored(page: &SlottedPage, id: RecordId) -> Result<&[u8]> {
+        page.get(id.slot).ok_or_else(|| {
+            Error::Corruption(format!("no record at slot {} of page {}", id.slot, id.page))
+        })
+    }
+
+    /// free the overflow pages of a record moved there whole
+    fn free_record_chain(&mut self, stored: &[u8]) -> Result<()> {
+        if stored[0] == ValueStorageByteMap::OVERFLOW && stored.len() == Self::RECORD_STUB_SIZE {
+            self.free_chain(u32_at(stored, 9))?;
+        }
+        Ok(())
+    }
+
+    fn chain_page(&self, page_id: PageId) -> Result<Vec<u8>> {
+        let page = self.page(page_id)?;
+        if page[0] != PageKindByteMap::OVERFLOW {
+            return Err(Error::Corruption(format!(
+                "page {} is not an overflow page",
+                page_id
+            )));
+        }
+        Ok(page)
+    }
+
+    /// a page from the free list, or else a new one at the end of the file
+    /// the caller is responsible for writing it
+    fn allocate(&mut self) -> Result<PageId> {
+        if self.free_page != NO_PAGE {
+            let page_id = self.free_page;
+            let page = self.page(page_id)?;
+            if page[0] != PageKindByteMap::FREE {
+                return Err(Error::Corruption(format!(
+                    "free page {} is in use",
+                    page_id
+                )));
+            }
+            self.free_page = u32_at(&page, 1);
+            self.write_meta()?;
+            return Ok(page_id);
+        }
+        if self.pages_len == NO_PAGE - 1 {
+            return Err(Error::Invalid(String::from("table file is full")));
+        }
+        self.pages_len += 1;
+        Ok(self.pages_len - 1)
+    }
+
+    fn free(&mut self, page_id: PageId) -> Result<()> {
+        let mut page = vec![0; PAGE_SIZE];
+        page[0] = PageKindByteMap::FREE;
+        page[1..5].copy_from_slice(&self.free_page.to_be_bytes());
+        self.write_page(page_id, page)?;
+        self.free_page = page_id;
+        self.insert_pages
+            .retain(|insert_page| *insert_page != page_id);
+        self.write_meta()
+    }
+
+    fn write_meta(&mut self) -> Result<()> {
+        let mut page = vec![0; PAGE_SIZE];
+        let mut at = Storage::FILE_MAGIC.len();
+        page[..at].copy_from_slice(Storage::FILE_MAGIC);
+        for field in [
+            &Storage::FORMAT_VERSION.to_be_bytes()[..],
+            &self.free_page.to_be_bytes(),
+            &self.schema.0.to_be_bytes(),
+            &self.schema.1.to_be_bytes(),
+        ] {
+            page[at..at + field.len()].copy_from_slice(field);
+            at += field.len();
+        }
+        debug_assert_eq!(at, Self::META_SIZE);
+        self.write_page(0, page)
+    }
+
+    fn page(&self, page_id: PageId) -> Result<Vec<u8>> {
+        if page_id >= self.pages_len {
+            return Err(Error::Corruption(format!("page {} out of range", page_id)));
+        }
+        if let Some(pages) = self.new_pages.as_ref() {
+            // allocated, but not written yet
+            return Ok(pages
+                .get(page_id as usize)
+                .cloned()
+                .unwrap_or_else(|| vec![0; PAGE_SIZE]));
+        }
+        let mut buffer = self.storage.buffer.borrow_mut();
+        let frame_id = buffer.pin(self.storage, &self.table_name, page_id)?;
+        let page = buffer.page(frame_id).to_vec();
+        buffer.unpin(frame_id);
+        Ok(page)
+    }
+
+    fn write_page(&mut self, page_id: PageId, page: Vec<u8>) -> Result<()> {
+        if let Some(pages) = self.new_pages.as_mut() {
+            let idx = page_id as usize;
+            if idx >= pages.len() {
+                pages.resize(idx + 1, vec![0; PAGE_SIZE]);
+            }
+            pages[idx] = page;
+            return Ok(());
+        }
+        let mut buffer = self.storage.buffer.borrow_mut();
+        let frame_id = buffer.pin_new(self.storage, &self.table_name, page_id)?;
+        buffer.page_mut(frame_id).copy_from_slice(&page);
+        buffer.unpin(frame_id);
+        Ok(())
+    }
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_be_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> u64 {
+    u64::from_be_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// a storage whose buffer pool holds `pages` pages
+    fn storage(name: &str, pages: usize) -> Storage {
+        let dir =
+            std::env::temp_dir().join(format!("ubdb-test-heap-{}-{}", name, std::process::id()));
+        Storage::with_buffer_budget(dir.to_str().unwrap().to_string(), pages * PAGE_SIZE)
+    }
+
+    #[test]
+    fn test_chain() {
+        let storage = storage("chain", 64);
+        let mut heap = HeapFile::create(&storage, "t", b"schema").unwrap();
+        let large = (0..10_000).map(|i| i as u8).collect::<Vec<_>>();
+        let first_page = heap.write_chain(&large).unwrap();
+        let empty = heap.write_chain(&[]).unwrap();
+        assert_eq!(heap.pages_len(), 6);
+        heap.sync().unwrap();
+
+        let storage = Storage::new(storage.storage_dir.clone());
+        let mut heap = HeapFile::open(&storage, "t").unwrap();
+        assert_eq!(heap.schema().unwrap(), b"schema");
+        assert_eq!(heap.read_chain(first_page, 10_000).unwrap(), large);
+        assert_eq!(heap.read_chain(empty, 0).unwrap(), Vec::<u8>::new());
+        assert!(matches!(
+            heap.read_chain(first_page, 9_999),
+            Err(Error::Corruption(_))
+        ));
+
+        // freed pages are reused before the file grows
+        heap.free_chain(first_page).unwrap();
+        assert!(matches!(
+            heap.read_chain(first_page, 10_000),
+            Err(Error::Corruption(_))
+        ));
+        heap.write_chain(&large).unwrap();
+        assert_eq!(heap.pages_len(), 6);
+        std::fs::remove_dir_all(&storage.storage_dir).unwrap();
+    }
+
+    #[test]
+    fn test_insert_and_delete() {
+        // fewer pages than the file has, so that some are evicted along the way
+        let storage = storage("records", 2);
+        let mut heap = HeapFile::create(&storage, "t", b"schema").unwrap();
+        let ids = (0..1000u32)
+            .map(|i| heap.insert(&i.to_be_bytes()).unwrap())
+            .collect::<Vec<_>>();
+        let large = vec![0xab; PAGE_SIZE * 2];
+        let large_id = heap.insert(&large).unwrap();
+        heap.sync().unwrap();
+
+        let storage = self::storage("records", 2);
+        let mut heap = HeapFile::open(&storage, "t").unwrap();
+        let records = (1..heap.pages_len())
+            .flat_map(|page| heap.records(page).unwrap())
+            .collect::<Vec<_>>();
+        assert!(storage.buffer_stats().evictions > 0);
+        assert_eq!(records.len(), 1001);
+        assert_eq!(records[0], (ids[0], 0u32.to_be_bytes().to_vec()));
+        assert_eq!(records[1000], (large_id, large.clone()));
+
+        // a deleted record makes room in its page for the next one
+        heap.delete(ids[10]).unwrap();
+        assert_eq!(heap.insert(b"new").unwrap(), ids[10]);
+        assert!(matches!(
+            heap.delete(RecordId {
+                page: ids[10].page,
+                slot: 999
+            }),
+            Err(Error::Corruption(_))
+        ));
+
+        // as do the pages of a record moved to overflow pages, and a page without records
+        let pages_len = heap.pages_len();
+        heap.delete(large_id).unwrap();
+        for id in ids.iter().filter(|id| id.page == ids[999].page) {
+            heap.delete(*id).unwrap();
+        }
+        heap.insert(&large).unwrap();
+        heap.insert(&[0; 100]).unwrap();
+        assert_eq!(heap.pages_len(), pages_len);
+        std::fs::remove_dir_all(&storage.storage_dir).unwrap();
+    }
+
+    #[test]
+    fn test_update() {
+        let storage = storage("update", 64);
+        let mut heap = HeapFile::create(&storage, "t", b"schema").unwrap();
+        let ids = (0..4)
+            .map(|_| heap.insert(&[0xab; 1000]).unwrap())
+            .collect::<Vec<_>>();
+        assert!(ids.iter().all(|id| id.page == ids[0].page));
+        heap.sync().unwrap();
+
+        let mut heap = HeapFile::open(&storage, "t").unwrap();
+        // a record that still fits its page keeps its id
+        assert_eq!(heap.update(ids[1], b"small").unwrap(), ids[1]);
+        assert_eq!(heap.record(ids[1]).unwrap(), b"small");
+        assert_eq!(heap.update(ids[1], &[0xcd; 1000]).unwrap(), ids[1]);
+
+        // while one that does not moves to another page
+        let moved = heap.update(ids[2], &[0xef; 2000]).unwrap();
+        assert_ne!(moved.page, ids[2].page);
+        assert_eq!(heap.record(moved).unwrap(), vec![0xef; 2000]);
+        assert!(matches!(heap.record(ids[2]), Err(Error::Corruption(_))));
+
+        // a record larger than a page is moved to overflow pages, freed when it is replaced
+        let pages_len = heap.pages_len();
+        let large = vec![0x12; PAGE_SIZE * 2];
+        assert_eq!(heap.update(ids[0], &large).unwrap(), ids[0]);
+        assert_eq!(heap.record(ids[0]).unwrap(), large);
+        heap.update(ids[0], b"small").unwrap();
+        heap.update(ids[3], &large).unwrap();
+        assert_eq!(heap.pages_len(), pages_len + 3);
+
+        heap.set_schema(b"new schema").unwrap();
+        heap.sync().unwrap();
+        let heap = HeapFile::open(&storage, "t").unwrap();
+        assert_eq!(heap.schema().unwrap(), b"new schema");
+        assert_eq!(heap.record(ids[3]).unwrap(), large);
+        std::fs::remove_dir_all(&storage.storage_dir).unwrap();
+    }
+}
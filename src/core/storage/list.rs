@@ -0,0 +1,52 @@
+use crate::error::Result;
+
+use super::Storage;
+
+impl Storage {
+    /// names of all tables in the storage directory, sorted
+    pub fn list(&self) -> Result<Vec<String>> {
+        let entries = match std::fs::read_dir(&self.storage_dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+        let mut names = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(Self::STORAGE_FILE_EXT) {
+                continue;
+            }
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::core::table::{DataType, Table};
+
+    use super::*;
+
+    #[test]
+    fn test_list() {
+        let dir = std::env::temp_dir().join(format!("ubdb-test-list-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let storage = Storage::new(dir.to_str().unwrap().to_string());
+        assert!(storage.list().unwrap().is_empty());
+
+        for name in ["user", "todo"] {
+            let table = Table::new(
+                String::from(name),
+                vec![(String::from("id"), DataType::Int)],
+                vec![],
+            );
+            storage.flush(&table).unwrap();
+        }
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+        assert_eq!(storage.list().unwrap(), vec!["todo", "user"]);
+    }
+}
@@ -1,113 +1,925 @@
-use crate::core::table::{DataType, Record, Table, Value};
+use std::{
+    fs::File,
+    io::{Read, Seek},
+};
 
-use super::{DataTypeByteMap, Storage};
+use crate::{
+    core::{
+        datetime::{Date, Interval, Time, Timestamp},
+        decimal::Decimal,
+        json::Json,
+        table::{Constraint, DataType, Record, Table, Value},
+    },
+    error::{Error, Result},
+    query::{
+        ast::{ForeignKey, ReferentialAction},
+        lex::Lexer,
+        parser::Parser,
+    },
+};
+
+use super::{
+    heap::{HeapFile, RecordId},
+    overflow::OverflowPages,
+    page::PageId,
+    ConstraintByteMap, DataTypeByteMap, JsonByteMap, ReferentialActionByteMap, Storage,
+    ValueStorageByteMap,
+};
 
 impl Storage {
-    pub fn load(&self, table_name: &str) -> Option<Table> {
+    /// returns None if the table has no storage file
+    pub fn load(&self, table_name: &str) -> Result<Option<Table>> {
         let path = self.get_table_storage_path(table_name);
-        let bytes = std::fs::read(path).unwrap_or(vec![]);
-        if bytes.is_empty() {
-            return None;
+        let Some((mut file, version)) = Self::open_table_file(&path)? else {
+            return Ok(None);
+        };
+        if version < Self::FORMAT_VERSION {
+            let mut bytes = vec![];
+            file.read_to_end(&mut bytes)?;
+            return Self::bytes_to_table(&bytes).map(Some);
+        }
+
+        let heap = HeapFile::open(self, table_name)?;
+        let (mut table, _) = Self::bytes_to_schema(&heap.schema()?, 0, Layout::VERSIONED)?;
+        for row in TableScan::new(heap, table.columns.clone()) {
+            table.rows.push(row?.1);
         }
-        Some(Self::bytes_to_table(&bytes)) // TODO: error handling
+        Ok(Some(table))
     }
 
-    fn bytes_to_table(bytes: &[u8]) -> Table {
-        let mut offset = 0;
+    /// the records of the table, whose definition is `table`, read a page at a time,
+    /// None if the table has no storage file
+    pub(crate) fn scan(&self, table: &Table) -> Result<Option<TableScan<'_>>> {
+        Ok(self
+            .open_heap(&table.name)?
+            .map(|heap| TableScan::new(heap, table.columns.clone())))
+    }
 
-        // name
-        let name_len = u8::from_be_bytes([bytes[offset]]);
-        offset += 1;
-        let name = String::from_utf8(bytes[offset..offset + name_len as usize].to_vec()).unwrap();
-        offset += name_len as usize;
+    /// the table file, converted to the current format first if it is older,
+    /// None if there is none
+    pub(super) fn open_heap(&self, table_name: &str) -> Result<Option<HeapFile<'_>>> {
+        let path = self.get_table_storage_path(table_name);
+        match Self::open_table_file(&path)? {
+            None => return Ok(None),
+            Some((_, Self::FORMAT_VERSION)) => {}
+            // written whole, so small enough to be read whole once more
+            Some(_) => {
+                if let Some(mut table) = self.load(table_name)? {
+                    // the name in the file is that of before any rename
+                    table.name = table_name.to_string();
+                    self.flush(&table)?;
+                }
+            }
+        }
+        HeapFile::open(self, table_name).map(Some)
+    }
+
+    /// the columns and constraints of the table without reading its rows,
+    /// None if the table has no storage file
+    pub fn load_schema(&self, table_name: &str) -> Result<Option<Table>> {
+        let path = self.get_table_storage_path(table_name);
+        let Some((mut file, version)) = Self::open_table_file(&path)? else {
+            return Ok(None);
+        };
+        if version < Self::FORMAT_VERSION {
+            // the layout of a file without a header is only known once it is read whole
+            let mut bytes = vec![];
+            file.read_to_end(&mut bytes)?;
+            let mut table = Self::bytes_to_table(&bytes)?;
+            table.rows.clear();
+            return Ok(Some(table));
+        }
+
+        let heap = HeapFile::open(self, table_name)?;
+        Ok(Some(
+            Self::bytes_to_schema(&heap.schema()?, 0, Layout::VERSIONED)?.0,
+        ))
+    }
+
+    /// the table file and its format version, None if there is none
+    pub(super) fn open_table_file(path: &str) -> Result<Option<(File, u16)>> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut header = vec![];
+        (&mut file)
+            .take(Self::FILE_MAGIC.len() as u64 + 2)
+            .read_to_end(&mut header)?;
+        file.rewind()?;
+        let (version, _) = Self::read_header(&header)?;
+        Ok(Some((file, version)))
+    }
+
+    /// a record of a paged table file with the values in overflow pages read back
+    fn read_record(
+        heap: &HeapFile,
+        bytes: &[u8],
+        columns: &[(String, DataType)],
+    ) -> Result<Record> {
+        let mut overflowed = vec![];
+        let (mut record, record_size) =
+            Self::bytes_to_record(bytes, columns, Layout::VERSIONED, &mut overflowed)?;
+        if record_size != bytes.len() {
+            return Err(Error::Corruption(String::from("invalid record")));
+        }
+        for (idx, len, first_page) in overflowed {
+            let data = heap.read_chain(first_page, len)?;
+            record.values[idx] = Self::large_value(data, &columns[idx].1)?;
+        }
+        Ok(record)
+    }
+
+    /// the first pages of the values of a record of a paged table file kept in overflow pages
+    pub(super) fn record_chains(
+        bytes: &[u8],
+        columns: &[(String, DataType)],
+    ) -> Result<Vec<PageId>> {
+        let mut overflowed = vec![];
+        Self::bytes_to_record(bytes, columns, Layout::VERSIONED, &mut overflowed)?;
+        Ok(overflowed
+            .into_iter()
+            .map(|(_, _, first_page)| first_page)
+            .collect())
+    }
+
+    /// a table file of the formats before pages, v0 and v1
+    fn bytes_to_table(bytes: &[u8]) -> Result<Table> {
+        let (version, offset) = Self::read_header(bytes)?;
+        if version > 0 {
+            return Self::bytes_to_table_in(bytes, offset, Layout::VERSIONED);
+        }
+        let mut error = None;
+        for layout in Layout::HEADERLESS {
+            match Self::bytes_to_table_in(bytes, offset, layout) {
+                Ok(table) => return Ok(table),
+                Err(err) => error = Some(err),
+            }
+        }
+        // that of the newest layout
+        Err(error.unwrap())
+    }
+
+    /// a table file of the formats before pages read with `layout`, which must reach its end
+    fn bytes_to_table_in(bytes: &[u8], offset: usize, layout: Layout) -> Result<Table> {
+        let (mut table, mut offset) = Self::bytes_to_schema(bytes, offset, layout)?;
+
+        let records_len = match layout.records_len_size {
+            2 => read_u16(bytes, offset)? as u64,
+            _ => read_u64(bytes, offset)?,
+        };
+        offset += layout.records_len_size;
+        // (row, column, length, first page) of the values in overflow pages
+        let mut overflowed = vec![];
+        for row in 0..records_len as usize {
+            let mut record_overflowed = vec![];
+            let (record, record_size) = Self::bytes_to_record(
+                &bytes[offset..],
+                &table.columns,
+                layout,
+                &mut record_overflowed,
+            )?;
+            offset += record_size;
+            overflowed.extend(
+                record_overflowed
+                    .into_iter()
+                    .map(|(idx, len, first_page)| (row, idx, len, first_page)),
+            );
+            table.rows.push(record);
+        }
 
-        // columns
-        let columns_len = u16::from_be_bytes([bytes[offset], bytes[offset + 1]]);
+        // overflow pages follow the records when some value did not fit in its record
+        let overflow = if offset < bytes.len() {
+            let pages_len = read_u32(bytes, offset)? as usize;
+            offset += 4;
+            let pages_size = pages_len
+                .checked_mul(OverflowPages::PAGE_SIZE)
+                .ok_or_else(|| Error::Corruption(String::from("too many overflow pages")))?;
+            let overflow = OverflowPages::from_bytes(read(bytes, offset, pages_size)?.to_vec())?;
+            offset += pages_size;
+            overflow
+        } else {
+            OverflowPages::default()
+        };
+        if offset != bytes.len() {
+            return Err(Error::Corruption(String::from("trailing bytes")));
+        }
+        for (row, idx, len, first_page) in overflowed {
+            let data = overflow.read(first_page, len)?;
+            table.rows[row].values[idx] = Self::large_value(data, &table.columns[idx].1)?;
+        }
+
+        Ok(table)
+    }
+
+    /// the table without its records, and the offset after its definition
+    fn bytes_to_schema(bytes: &[u8], mut offset: usize, layout: Layout) -> Result<(Table, usize)> {
+        let name_len = match layout.name_len_size {
+            1 => read(bytes, offset, 1)?[0] as usize,
+            _ => read_u16(bytes, offset)? as usize,
+        };
+        offset += layout.name_len_size;
+        let name = read_string(bytes, offset, name_len)?;
+        offset += name_len;
+
+        let columns_len = read_u16(bytes, offset)?;
         offset += 2;
         let mut columns = vec![];
         for _ in 0..columns_len {
-            let column_name_len = u16::from_be_bytes([bytes[offset], bytes[offset + 1]]);
+            let column_name_len = read_u16(bytes, offset)?;
             offset += 2;
-            let column_name =
-                String::from_utf8(bytes[offset..offset + column_name_len as usize].to_vec())
-                    .unwrap();
+            let column_name = read_string(bytes, offset, column_name_len as usize)?;
             offset += column_name_len as usize;
-            let (data_type, data_type_size) = Self::bytes_to_data_type(&bytes[offset..]);
+            let (data_type, data_type_size) = Self::bytes_to_data_type(&bytes[offset..])?;
             offset += data_type_size;
             columns.push((column_name, data_type));
         }
 
-        // records
-        let records_len = u16::from_be_bytes([bytes[offset], bytes[offset + 1]]);
-        offset += 2;
-        let mut records = vec![];
-        for _ in 0..records_len {
-            let mut values = vec![];
-            for column in columns.iter() {
-                let value = Self::bytes_to_value(&bytes[offset..], &column.1);
-                offset += value.1;
-                values.push(value.0);
+        let mut constraints = vec![];
+        if layout.constraints {
+            let constraints_len = read_u16(bytes, offset)?;
+            offset += 2;
+            for _ in 0..constraints_len {
+                let (constraint, constraint_size) =
+                    Self::bytes_to_constraint(&bytes[offset..], &columns)?;
+                offset += constraint_size;
+                constraints.push(constraint);
+            }
+        }
+
+        let table = Table::new(name, columns, vec![]).with_constraints(constraints);
+        Ok((table, offset))
+    }
+
+    /// a record, its null bitmap if `layout` has one followed by the values,
+    /// those in overflow pages left NULL and added to `overflowed` as (column, length, first page)
+    fn bytes_to_record(
+        bytes: &[u8],
+        columns: &[(String, DataType)],
+        layout: Layout,
+        overflowed: &mut Vec<(usize, u64, PageId)>,
+    ) -> Result<(Record, usize)> {
+        let bitmap_len = if layout.null_bitmap {
+            Self::null_bitmap_len(columns.len())
+        } else {
+            0
+        };
+        let bitmap = read(bytes, 0, bitmap_len)?;
+        let mut offset = bitmap_len;
+        let mut values = vec![];
+        for (idx, column) in columns.iter().enumerate() {
+            if layout.null_bitmap && bitmap[idx / 8] & (1 << (idx % 8)) != 0 {
+                values.push(Value::Null);
+                continue;
+            }
+            if matches!(column.1, DataType::Text | DataType::Blob | DataType::Json)
+                && read(bytes, offset, 1)?[0] == ValueStorageByteMap::OVERFLOW
+            {
+                let len = read_u64(bytes, offset + 1)?;
+                let first_page = read_u32(bytes, offset + 9)?;
+                offset += 13;
+                overflowed.push((idx, len, first_page));
+                // filled in once the overflow pages are read
+                values.push(Value::Null);
+                continue;
             }
-            records.push(Record::new(values));
+            let value = Self::bytes_to_value(&bytes[offset..], &column.1)?;
+            offset += value.1;
+            values.push(value.0);
         }
+        Ok((Record::new(values), offset))
+    }
+
+    /// the format version and the offset after the header,
+    /// files without the magic being v0 ones which have no header
+    fn read_header(bytes: &[u8]) -> Result<(u16, usize)> {
+        if !bytes.starts_with(Self::FILE_MAGIC) {
+            return Ok((0, 0));
+        }
+        let version = read_u16(bytes, Self::FILE_MAGIC.len())?;
+        if !(1..=Self::FORMAT_VERSION).contains(&version) {
+            return Err(Error::Corruption(format!(
+                "unsupported format version: {}",
+                version
+            )));
+        }
+        Ok((version, Self::FILE_MAGIC.len() + 2))
+    }
+
+    fn bytes_to_constraint(
+        bytes: &[u8],
+        columns: &[(String, DataType)],
+    ) -> Result<(Constraint, usize)> {
+        let mut offset = 1;
+        let string = |offset: &mut usize| -> Result<String> {
+            let len = read_u16(bytes, *offset)? as usize;
+            let s = read_string(bytes, *offset + 2, len)?;
+            *offset += 2 + len;
+            Ok(s)
+        };
+        let string_list = |offset: &mut usize| -> Result<Vec<String>> {
+            let names_len = read_u16(bytes, *offset)?;
+            *offset += 2;
+            let mut names = vec![];
+            for _ in 0..names_len {
+                names.push(string(offset)?);
+            }
+            Ok(names)
+        };
+        let constraint = match read(bytes, 0, 1)?[0] {
+            tag @ (ConstraintByteMap::PRIMARY_KEY | ConstraintByteMap::UNIQUE) => {
+                let names = string_list(&mut offset)?;
+                if tag == ConstraintByteMap::PRIMARY_KEY {
+                    Constraint::PrimaryKey(names)
+                } else {
+                    Constraint::Unique(names)
+                }
+            }
+            ConstraintByteMap::NOT_NULL => Constraint::NotNull(string(&mut offset)?),
+            ConstraintByteMap::DEFAULT => {
+                let name = string(&mut offset)?;
+                let is_null = read(bytes, offset, 1)?[0] == 1;
+                offset += 1;
+                let value = if is_null {
+                    Value::Null
+                } else {
+                    let data_type = columns
+                        .iter()
+                        .find(|(column_name, _)| *column_name == name)
+                        .map(|(_, data_type)| data_type)
+                        .ok_or_else(|| {
+                            Error::Corruption(format!("default for unknown column {}", name))
+                        })?;
+                    let (value, value_size) = Self::bytes_to_value(&bytes[offset..], data_type)?;
+                    offset += value_size;
+                    value
+                };
+                Constraint::Default(name, value)
+            }
+            ConstraintByteMap::CHECK => {
+                let sql = string(&mut offset)?;
+                let expr = Parser::new(Lexer::new(sql.clone()))
+                    .parse_standalone_expr()
+                    .map_err(|_| Error::Corruption(format!("invalid check constraint: {}", sql)))?;
+                Constraint::Check(expr)
+            }
+            ConstraintByteMap::FOREIGN_KEY => {
+                let columns = string_list(&mut offset)?;
+                let ref_table = string(&mut offset)?;
+                let ref_columns = string_list(&mut offset)?;
+                let actions = read(bytes, offset, 2)?;
+                offset += 2;
+                Constraint::ForeignKey(ForeignKey {
+                    columns,
+                    ref_table,
+                    ref_columns,
+                    on_delete: Self::byte_to_referential_action(actions[0])?,
+                    on_update: Self::byte_to_referential_action(actions[1])?,
+                })
+            }
+            tag => return Err(Error::Corruption(format!("invalid constraint: {}", tag))),
+        };
+        Ok((constraint, offset))
+    }
 
-        Table::new(name, columns, records)
+    fn byte_to_referential_action(byte: u8) -> Result<ReferentialAction> {
+        match byte {
+            ReferentialActionByteMap::RESTRICT => Ok(ReferentialAction::Restrict),
+            ReferentialActionByteMap::CASCADE => Ok(ReferentialAction::Cascade),
+            ReferentialActionByteMap::SET_NULL => Ok(ReferentialAction::SetNull),
+            ReferentialActionByteMap::SET_DEFAULT => Ok(ReferentialAction::SetDefault),
+            byte => Err(Error::Corruption(format!(
+                "invalid referential action: {}",
+                byte
+            ))),
+        }
     }
 
-    fn bytes_to_data_type(bytes: &[u8]) -> (DataType, usize) {
-        match bytes[0] {
-            DataTypeByteMap::INT => (DataType::Int, 1),
+    fn bytes_to_data_type(bytes: &[u8]) -> Result<(DataType, usize)> {
+        match read(bytes, 0, 1)?[0] {
+            DataTypeByteMap::INT => Ok((DataType::Int, 1)),
+            DataTypeByteMap::BIGINT => Ok((DataType::BigInt, 1)),
+            DataTypeByteMap::BOOLEAN => Ok((DataType::Boolean, 1)),
+            DataTypeByteMap::DOUBLE => Ok((DataType::Double, 1)),
+            DataTypeByteMap::DECIMAL => {
+                let b = read(bytes, 1, 2)?;
+                let (precision, scale) = (b[0], b[1]);
+                if !(1..=Decimal::MAX_PRECISION).contains(&precision) || scale > precision {
+                    return Err(Error::Corruption(format!(
+                        "invalid decimal type: DECIMAL({}, {})",
+                        precision, scale
+                    )));
+                }
+                Ok((DataType::Decimal(precision, scale), 3))
+            }
             DataTypeByteMap::VARCHAR => {
-                let size = u16::from_be_bytes([bytes[1], bytes[2]]);
-                (DataType::VarChar(size), 3)
+                let size = read_u16(bytes, 1)?;
+                Ok((DataType::VarChar(size), 3))
             }
-            _ => panic!("invalid data type"),
+            DataTypeByteMap::DATE => Ok((DataType::Date, 1)),
+            DataTypeByteMap::TIME => Ok((DataType::Time, 1)),
+            DataTypeByteMap::TIMESTAMP => Ok((DataType::Timestamp, 1)),
+            DataTypeByteMap::INTERVAL => Ok((DataType::Interval, 1)),
+            DataTypeByteMap::TEXT => Ok((DataType::Text, 1)),
+            DataTypeByteMap::BLOB => Ok((DataType::Blob, 1)),
+            DataTypeByteMap::JSON => Ok((DataType::Json, 1)),
+            tag => Err(Error::Corruption(format!("invalid data type: {}", tag))),
         }
     }
 
-    fn bytes_to_value(bytes: &[u8], data_type: &DataType) -> (Value, usize) {
+    fn bytes_to_value(bytes: &[u8], data_type: &DataType) -> Result<(Value, usize)> {
         match data_type {
-            DataType::Int => (
-                Value::Int(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
-                4,
-            ),
+            DataType::Int => {
+                let b = read(bytes, 0, 4)?;
+                Ok((Value::Int(i32::from_be_bytes([b[0], b[1], b[2], b[3]])), 4))
+            }
+            DataType::BigInt => {
+                let b = read(bytes, 0, 8)?;
+                Ok((Value::BigInt(i64::from_be_bytes(b.try_into().unwrap())), 8))
+            }
+            DataType::Boolean => match read(bytes, 0, 1)?[0] {
+                0 => Ok((Value::Boolean(false), 1)),
+                1 => Ok((Value::Boolean(true), 1)),
+                b => Err(Error::Corruption(format!("invalid boolean: {}", b))),
+            },
+            DataType::Double => {
+                let b = read(bytes, 0, 8)?;
+                Ok((Value::Double(f64::from_be_bytes(b.try_into().unwrap())), 8))
+            }
+            DataType::Decimal(_, scale) => {
+                let b = read(bytes, 0, 16)?;
+                let mantissa = i128::from_be_bytes(b.try_into().unwrap());
+                Ok((Value::Decimal(Decimal::new(mantissa, *scale)), 16))
+            }
             DataType::VarChar(_size) => {
-                let value_len = u16::from_be_bytes([bytes[0], bytes[1]]);
-                let value = String::from_utf8(bytes[2..2 + value_len as usize].to_vec()).unwrap();
-                (Value::VarChar(value), 2 + value_len as usize)
+                let value_len = read_u16(bytes, 0)?;
+                let value = read_string(bytes, 2, value_len as usize)?;
+                Ok((Value::VarChar(value), 2 + value_len as usize))
+            }
+            DataType::Date => {
+                let b = read(bytes, 0, 4)?;
+                let days = i32::from_be_bytes(b.try_into().unwrap());
+                match Date::from_days(days as i64) {
+                    Some(date) => Ok((Value::Date(date), 4)),
+                    None => Err(Error::Corruption(format!("date out of range: {}", days))),
+                }
+            }
+            DataType::Time => {
+                let micros = read_i64(bytes, 0)?;
+                match Time::from_micros(micros) {
+                    Some(time) => Ok((Value::Time(time), 8)),
+                    None => Err(Error::Corruption(format!("time out of range: {}", micros))),
+                }
+            }
+            DataType::Timestamp => {
+                let micros = read_i64(bytes, 0)?;
+                match Timestamp::from_micros(micros) {
+                    Some(timestamp) => Ok((Value::Timestamp(timestamp), 8)),
+                    None => Err(Error::Corruption(format!(
+                        "timestamp out of range: {}",
+                        micros
+                    ))),
+                }
             }
+            DataType::Interval => {
+                let b = read(bytes, 0, 8)?;
+                let months = i32::from_be_bytes(b[..4].try_into().unwrap());
+                let days = i32::from_be_bytes(b[4..].try_into().unwrap());
+                let micros = read_i64(bytes, 8)?;
+                Ok((Value::Interval(Interval::new(months, days, micros)), 16))
+            }
+            DataType::Text | DataType::Blob | DataType::Json => match read(bytes, 0, 1)?[0] {
+                ValueStorageByteMap::INLINE => {
+                    let len = read_u32(bytes, 1)? as usize;
+                    let data = read(bytes, 5, len)?.to_vec();
+                    Ok((Self::large_value(data, data_type)?, 5 + len))
+                }
+                // only records refer to overflow pages
+                ValueStorageByteMap::OVERFLOW => Err(Error::Corruption(String::from(
+                    "overflow value outside of a record",
+                ))),
+                tag => Err(Error::Corruption(format!("invalid value storage: {}", tag))),
+            },
         }
     }
+
+    /// the value of a TEXT, BLOB or JSON column from its bytes
+    fn large_value(data: Vec<u8>, data_type: &DataType) -> Result<Value> {
+        match data_type {
+            DataType::Text => String::from_utf8(data)
+                .map(Value::VarChar)
+                .map_err(|_| Error::Corruption(String::from("invalid utf-8 string"))),
+            DataType::Json => {
+                let (json, json_size) = Self::bytes_to_json(&data, 0)?;
+                if json_size != data.len() {
+                    return Err(Error::Corruption(String::from("invalid json")));
+                }
+                Ok(Value::Json(json))
+            }
+            _ => Ok(Value::Blob(data)),
+        }
+    }
+
+    fn bytes_to_json(bytes: &[u8], depth: usize) -> Result<(Json, usize)> {
+        let string = |offset: usize| -> Result<(String, usize)> {
+            let len = read_u32(bytes, offset)? as usize;
+            Ok((read_string(bytes, offset + 4, len)?, 4 + len))
+        };
+        match read(bytes, 0, 1)?[0] {
+            JsonByteMap::NULL => Ok((Json::Null, 1)),
+            JsonByteMap::FALSE => Ok((Json::Bool(false), 1)),
+            JsonByteMap::TRUE => Ok((Json::Bool(true), 1)),
+            JsonByteMap::NUMBER => {
+                let b = read(bytes, 1, 8)?;
+                let v = f64::from_be_bytes(b.try_into().unwrap());
+                if !v.is_finite() {
+                    return Err(Error::Corruption(format!("invalid json number: {}", v)));
+                }
+                Ok((Json::Number(v), 9))
+            }
+            JsonByteMap::STRING => {
+                let (s, size) = string(1)?;
+                Ok((Json::String(s), 1 + size))
+            }
+            tag @ (JsonByteMap::ARRAY | JsonByteMap::OBJECT) => {
+                if depth >= Json::MAX_DEPTH {
+                    return Err(Error::Corruption(String::from("json nested too deeply")));
+                }
+                let len = read_u32(bytes, 1)?;
+                let mut offset = 5;
+                let mut elements = vec![];
+                let mut members = std::collections::BTreeMap::new();
+                for _ in 0..len {
+                    let key = if tag == JsonByteMap::OBJECT {
+                        let (key, size) = string(offset)?;
+                        offset += size;
+                        Some(key)
+                    } else {
+                        None
+                    };
+                    let (value, size) = Self::bytes_to_json(&bytes[offset..], depth + 1)?;
+                    offset += size;
+                    match key {
+                        Some(key) => {
+                            members.insert(key, value);
+                        }
+                        None => elements.push(value),
+                    }
+                }
+                if tag == JsonByteMap::OBJECT {
+                    Ok((Json::Object(members), offset))
+                } else {
+                    Ok((Json::Array(elements), offset))
+                }
+            }
+            tag => Err(Error::Corruption(format!("invalid json value: {}", tag))),
+        }
+    }
+}
+
+/// the records of a table file with where they are kept, read a page at a time
+pub(crate) struct TableScan<'a> {
+    heap: HeapFile<'a>,
+    columns: Vec<(String, DataType)>,
+    // the page to read once the records of the last one are returned
+    next_page: PageId,
+    records: std::vec::IntoIter<(RecordId, Vec<u8>)>,
+}
+
+impl<'a> TableScan<'a> {
+    fn new(heap: HeapFile<'a>, columns: Vec<(String, DataType)>) -> Self {
+        Self {
+            heap,
+            columns,
+            // past the meta page
+            next_page: 1,
+            records: vec![].into_iter(),
+        }
+    }
+}
+
+impl Iterator for TableScan<'_> {
+    type Item = Result<(RecordId, Record)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((id, bytes)) = self.records.next() {
+                let record = Storage::read_record(&self.heap, &bytes, &self.columns);
+                return Some(record.map(|record| (id, record)));
+            }
+            if self.next_page >= self.heap.pages_len() {
+                return None;
+            }
+            let records = self.heap.records(self.next_page);
+            self.next_page += 1;
+            match records {
+                Ok(records) => self.records = records.into_iter(),
+                Err(err) => {
+                    // nothing is read after an error
+                    self.next_page = self.heap.pages_len();
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// where the sections of a table file are, which changed before files had a header
+#[derive(Debug, Clone, Copy)]
+struct Layout {
+    // bytes of the length of the table name
+    name_len_size: usize,
+    // bytes of the number of records
+    records_len_size: usize,
+    // whether every record starts with a null bitmap
+    null_bitmap: bool,
+    // whether the constraints follow the columns
+    constraints: bool,
+}
+
+impl Layout {
+    /// the layouts of v0 files, oldest first; nothing in a file tells them apart,
+    /// so it is read with the first one that reads it to its end
+    const HEADERLESS: [Layout; 3] = [
+        // the first one
+        Layout {
+            name_len_size: 1,
+            records_len_size: 2,
+            null_bitmap: false,
+            constraints: false,
+        },
+        // NULL
+        Layout {
+            name_len_size: 1,
+            records_len_size: 2,
+            null_bitmap: true,
+            constraints: false,
+        },
+        // constraints
+        Layout {
+            name_len_size: 1,
+            records_len_size: 2,
+            null_bitmap: true,
+            constraints: true,
+        },
+    ];
+
+    /// v1 and later
+    const VERSIONED: Layout = Layout {
+        name_len_size: 2,
+        records_len_size: 8,
+        null_bitmap: true,
+        constraints: true,
+    };
+}
+
+/// `len` bytes at `offset`, failing instead of panicking on a truncated file
+fn read(bytes: &[u8], offset: usize, len: usize) -> Result<&[u8]> {
+    bytes
+        .get(offset..offset + len)
+        .ok_or_else(|| Error::Corruption(String::from("unexpected end of file")))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16> {
+    let b = read(bytes, offset, 2)?;
+    Ok(u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+    let b = read(bytes, offset, 4)?;
+    Ok(u32::from_be_bytes(b.try_into().unwrap()))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64> {
+    let b = read(bytes, offset, 8)?;
+    Ok(u64::from_be_bytes(b.try_into().unwrap()))
+}
+
+fn read_i64(bytes: &[u8], offset: usize) -> Result<i64> {
+    let b = read(bytes, offset, 8)?;
+    Ok(i64::from_be_bytes(b.try_into().unwrap()))
+}
+
+fn read_string(bytes: &[u8], offset: usize, len: usize) -> Result<String> {
+    String::from_utf8(read(bytes, offset, len)?.to_vec())
+        .map_err(|_| Error::Corruption(String::from("invalid utf-8 string")))
 }
 
 #[cfg(test)]
 mod test {
+    use crate::query::ast::{self, BinaryOperator, Expr};
+
     use super::*;
 
     #[test]
     fn test_bytes_to_value() {
         assert_eq!(
-            Storage::bytes_to_value(&[0x000, 0x000, 0x000, 0x01], &DataType::Int),
+            Storage::bytes_to_value(&[0x000, 0x000, 0x000, 0x01], &DataType::Int).unwrap(),
             (Value::Int(1), 4)
         );
         assert_eq!(
-            Storage::bytes_to_value(&[0x00, 0x01, 0x61], &DataType::VarChar(8)),
+            Storage::bytes_to_value(&[0x00, 0x01, 0x61], &DataType::VarChar(8)).unwrap(),
             (Value::VarChar(String::from("a")), 3)
         );
+        assert_eq!(
+            Storage::bytes_to_value(&[0x00, 0x00, 0x00, 0x00, 0x01, 0x61], &DataType::Text)
+                .unwrap(),
+            (Value::VarChar(String::from("a")), 6)
+        );
+        assert_eq!(
+            Storage::bytes_to_value(&[0x00, 0x00, 0x00, 0x00, 0x01, 0xff], &DataType::Blob)
+                .unwrap(),
+            (Value::Blob(vec![0xff]), 6)
+        );
+        assert!(matches!(
+            Storage::bytes_to_value(&[0x00, 0x00, 0x00, 0x00, 0x01, 0xff], &DataType::Text),
+            Err(Error::Corruption(_))
+        ));
+        assert!(matches!(
+            Storage::bytes_to_value(&[0x01, 0x00], &DataType::Blob),
+            Err(Error::Corruption(_))
+        ));
+        assert!(matches!(
+            Storage::bytes_to_value(
+                &[0x00, 0x00, 0x00, 0x00, 0x02, JsonByteMap::ARRAY, 0x00],
+                &DataType::Json
+            ),
+            Err(Error::Corruption(_))
+        ));
+        assert_eq!(
+            Storage::bytes_to_value(
+                &[
+                    0x00,
+                    0x00,
+                    0x00,
+                    0x00,
+                    0x06, // inline, 6 bytes
+                    JsonByteMap::ARRAY,
+                    0x00,
+                    0x00,
+                    0x00,
+                    0x01,
+                    JsonByteMap::TRUE,
+                ],
+                &DataType::Json
+            )
+            .unwrap(),
+            (Value::Json(Json::parse("[true]").unwrap()), 11)
+        );
+        // trailing bytes after the document
+        assert!(matches!(
+            Storage::bytes_to_value(
+                &[
+                    0x00,
+                    0x00,
+                    0x00,
+                    0x00,
+                    0x02,
+                    JsonByteMap::NULL,
+                    JsonByteMap::NULL
+                ],
+                &DataType::Json
+            ),
+            Err(Error::Corruption(_))
+        ));
+        assert_eq!(
+            Storage::bytes_to_value(&[0x01], &DataType::Boolean).unwrap(),
+            (Value::Boolean(true), 1)
+        );
+        assert_eq!(
+            Storage::bytes_to_value(&(-1.5f64).to_be_bytes(), &DataType::Double).unwrap(),
+            (Value::Double(-1.5), 8)
+        );
+        assert_eq!(
+            Storage::bytes_to_value(&(-7i128).to_be_bytes(), &DataType::Decimal(4, 1)).unwrap(),
+            (Value::Decimal(Decimal::new(-7, 1)), 16)
+        );
+        assert!(matches!(
+            Storage::bytes_to_value(&[0x02], &DataType::Boolean),
+            Err(Error::Corruption(_))
+        ));
+        assert_eq!(
+            Storage::bytes_to_value(&[0xff, 0xff, 0xff, 0xff], &DataType::Date).unwrap(),
+            (Value::Date(Date::parse("1969-12-31").unwrap()), 4)
+        );
+        assert_eq!(
+            Storage::bytes_to_value(&60_000_000i64.to_be_bytes(), &DataType::Time).unwrap(),
+            (Value::Time(Time::parse("00:01").unwrap()), 8)
+        );
+        assert_eq!(
+            Storage::bytes_to_value(
+                &[0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 4],
+                &DataType::Interval
+            )
+            .unwrap(),
+            (Value::Interval(Interval::new(2, 3, 4)), 16)
+        );
+        // beyond 9999-12-31
+        assert!(matches!(
+            Storage::bytes_to_value(&i64::MAX.to_be_bytes(), &DataType::Timestamp),
+            Err(Error::Corruption(_))
+        ));
+        assert!(matches!(
+            Storage::bytes_to_value(&(-1i64).to_be_bytes(), &DataType::Time),
+            Err(Error::Corruption(_))
+        ));
     }
 
     #[test]
     fn test_bytes_to_data_type() {
         assert_eq!(
-            Storage::bytes_to_data_type(&[DataTypeByteMap::INT]),
+            Storage::bytes_to_data_type(&[DataTypeByteMap::INT]).unwrap(),
             (DataType::Int, 1)
         );
         assert_eq!(
-            Storage::bytes_to_data_type(&[DataTypeByteMap::VARCHAR, 0x00, 0x01]),
+            Storage::bytes_to_data_type(&[DataTypeByteMap::VARCHAR, 0x00, 0x01]).unwrap(),
             (DataType::VarChar(1), 3)
         );
+        assert_eq!(
+            Storage::bytes_to_data_type(&[DataTypeByteMap::BIGINT]).unwrap(),
+            (DataType::BigInt, 1)
+        );
+        assert_eq!(
+            Storage::bytes_to_data_type(&[DataTypeByteMap::DECIMAL, 0x26, 0x04]).unwrap(),
+            (DataType::Decimal(38, 4), 3)
+        );
+        assert!(matches!(
+            Storage::bytes_to_data_type(&[DataTypeByteMap::DECIMAL, 0x02, 0x03]),
+            Err(Error::Corruption(_))
+        ));
+    }
+
+    /// a v1 file, as written before table files were paged
+    fn v1_bytes(table: &Table) -> Vec<u8> {
+        let mut b = Storage::FILE_MAGIC.to_vec();
+        b.extend_from_slice(&1u16.to_be_bytes());
+        b.extend_from_slice(&Storage::schema_to_bytes(table).unwrap());
+        b.extend_from_slice(&(table.rows.len() as u64).to_be_bytes());
+        let mut overflow = OverflowPages::default();
+        for record in table.rows.iter() {
+            b.extend_from_slice(
+                &Storage::record_to_bytes(record, &table.columns, &mut |data| overflow.write(data))
+                    .unwrap(),
+            );
+        }
+        if !overflow.is_empty() {
+            b.extend_from_slice(&(overflow.len() as u32).to_be_bytes());
+            b.extend_from_slice(overflow.as_bytes());
+        }
+        b
     }
 
+    #[test]
+    fn test_load_v1() {
+        // more records than a u16 can count, and a name longer than a u8 can measure
+        let table = Table::new(
+            "t".repeat(300),
+            vec![(String::from("id"), DataType::Int)],
+            (0..70_000)
+                .map(|id| Record::new(vec![Value::Int(id)]))
+                .collect(),
+        );
+        let bytes = v1_bytes(&table);
+        assert_eq!(Storage::bytes_to_table(&bytes).unwrap(), table);
+
+        // a v1 file is read whole, and replaced by a paged one on the next flush
+        let mut table = table;
+        table.name = String::from("t");
+        table.rows.truncate(1000);
+        let bytes = v1_bytes(&table);
+        let dir = std::env::temp_dir().join(format!("ubdb-test-v1-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let storage = Storage::new(dir.to_str().unwrap().to_string());
+        let path = storage.get_table_storage_path(&table.name);
+        std::fs::write(&path, &bytes).unwrap();
+        assert_eq!(storage.load(&table.name).unwrap().as_ref(), Some(&table));
+        let schema = Table::new(table.name.clone(), table.columns.clone(), vec![]);
+        assert_eq!(
+            storage.load_schema(&table.name).unwrap().as_ref(),
+            Some(&schema)
+        );
+        storage.flush(&table).unwrap();
+        assert!(std::fs::read(&path).unwrap().len() > bytes.len());
+        assert_eq!(storage.load_schema(&table.name).unwrap(), Some(schema));
+        assert_eq!(storage.load(&table.name).unwrap(), Some(table));
+        assert_eq!(storage.load_schema("none").unwrap(), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut newer = bytes.clone();
+        newer[Storage::FILE_MAGIC.len()..Storage::FILE_MAGIC.len() + 2]
+            .copy_from_slice(&(Storage::FORMAT_VERSION + 1).to_be_bytes());
+        assert!(matches!(
+            Storage::bytes_to_table(&newer),
+            Err(Error::Corruption(_))
+        ));
+        assert!(matches!(
+            Storage::bytes_to_table(&bytes[..Storage::FILE_MAGIC.len() + 1]),
+            Err(Error::Corruption(_))
+        ));
+    }
+
+    /// a v0 file, without a header
     #[test]
     fn test_load() {
         let mut user_table = Table::new(
@@ -147,8 +959,335 @@ mod test {
                 0x00, 0x00, 0x00, 0x02, // 2
                 0x00, 0x03, // bob length
                 0x62, 0x6f, 0x62, // bob
-            ]),
+            ])
+            .unwrap(),
             user_table
         );
     }
+
+    #[test]
+    fn test_bytes_to_constraint() {
+        let columns = vec![(String::from("id"), DataType::Int)];
+        assert_eq!(
+            Storage::bytes_to_constraint(
+                &[
+                    ConstraintByteMap::UNIQUE,
+                    0x00,
+                    0x01,
+                    0x00,
+                    0x02,
+                    0x69,
+                    0x64
+                ],
+                &columns
+            )
+            .unwrap(),
+            (Constraint::Unique(vec![String::from("id")]), 7)
+        );
+        assert_eq!(
+            Storage::bytes_to_constraint(
+                &[
+                    ConstraintByteMap::DEFAULT,
+                    0x00,
+                    0x02,
+                    0x69,
+                    0x64, // id
+                    0x00, // not null
+                    0x00,
+                    0x00,
+                    0x00,
+                    0x07, // 7
+                ],
+                &columns
+            )
+            .unwrap(),
+            (Constraint::Default(String::from("id"), Value::Int(7)), 10)
+        );
+        let mut check = vec![ConstraintByteMap::CHECK, 0x00, 0x06];
+        check.extend_from_slice(b"id > 0");
+        assert_eq!(
+            Storage::bytes_to_constraint(&check, &columns).unwrap(),
+            (
+                Constraint::Check(Expr::Binary(
+                    Box::new(Expr::Column(String::from("id"))),
+                    BinaryOperator::Gt,
+                    Box::new(Expr::Value(ast::Value::Int(0))),
+                )),
+                9
+            )
+        );
+        assert!(matches!(
+            Storage::bytes_to_constraint(&[ConstraintByteMap::CHECK, 0x00, 0x01, b'('], &columns),
+            Err(Error::Corruption(_))
+        ));
+
+        let mut foreign_key = vec![ConstraintByteMap::FOREIGN_KEY, 0x00, 0x01, 0x00, 0x02];
+        foreign_key.extend_from_slice(b"id");
+        foreign_key.extend_from_slice(&[0x00, 0x01, b'u', 0x00, 0x01, 0x00, 0x02]);
+        foreign_key.extend_from_slice(b"id");
+        foreign_key.extend_from_slice(&[
+            ReferentialActionByteMap::RESTRICT,
+            ReferentialActionByteMap::SET_DEFAULT,
+        ]);
+        assert_eq!(
+            Storage::bytes_to_constraint(&foreign_key, &columns).unwrap(),
+            (
+                Constraint::ForeignKey(ForeignKey {
+                    columns: vec![String::from("id")],
+                    ref_table: String::from("u"),
+                    ref_columns: vec![String::from("id")],
+                    on_delete: ReferentialAction::Restrict,
+                    on_update: ReferentialAction::SetDefault,
+                }),
+                18
+            )
+        );
+        *foreign_key.last_mut().unwrap() = 9;
+        assert!(matches!(
+            Storage::bytes_to_constraint(&foreign_key, &columns),
+            Err(Error::Corruption(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_overflow() {
+        let text = "ü".repeat(40_000);
+        let blob = (0..100_000).map(|i| i as u8).collect::<Vec<_>>();
+        let table = Table::new(
+            String::from("file"),
+            vec![
+                (String::from("name"), DataType::Text),
+                (String::from("data"), DataType::Blob),
+            ],
+            vec![
+                Record::new(vec![
+                    Value::VarChar(text.clone()),
+                    Value::Blob(blob.clone()),
+                ]),
+                Record::new(vec![Value::VarChar(String::from("small")), Value::Null]),
+                Record::new(vec![Value::Null, Value::Blob(blob[..5000].to_vec())]),
+            ],
+        );
+        let dir = std::env::temp_dir().join(format!("ubdb-test-overflow-{}", std::process::id()));
+        let storage = Storage::new(dir.to_str().unwrap().to_string());
+        storage.flush(&table).unwrap();
+        assert_eq!(storage.load("file").unwrap().as_ref(), Some(&table));
+
+        // a truncated page
+        let path = storage.get_table_storage_path("file");
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(matches!(storage.load("file"), Err(Error::Corruption(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // a truncated overflow section of a v1 file
+        let bytes = v1_bytes(&table);
+        assert_eq!(Storage::bytes_to_table(&bytes).unwrap(), table);
+        assert!(matches!(
+            Storage::bytes_to_table(&bytes[..bytes.len() - 1]),
+            Err(Error::Corruption(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_json() {
+        let doc = Json::parse(
+            r#"{"name": "ü", "tags": ["a", 1.5, -2, true, false, null, {}], "nested": {"x": [[]]}}"#,
+        )
+        .unwrap();
+        let large = Json::Array(vec![doc.clone(); 200]);
+        let table = Table::new(
+            String::from("doc"),
+            vec![(String::from("body"), DataType::Json)],
+            vec![
+                Record::new(vec![Value::Json(doc)]),
+                Record::new(vec![Value::Json(large)]),
+                Record::new(vec![Value::Null]),
+            ],
+        );
+        let dir = std::env::temp_dir().join(format!("ubdb-test-json-{}", std::process::id()));
+        let storage = Storage::new(dir.to_str().unwrap().to_string());
+        storage.flush(&table).unwrap();
+        assert_eq!(storage.load("doc").unwrap(), Some(table));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// the sample data as shipped before files had a header, byte for byte
+    #[test]
+    fn test_load_v0_files() {
+        let user_bytes = include_bytes!("testdata/user.v0.ubdb");
+        let todo_bytes = include_bytes!("testdata/todo.v0.ubdb");
+        let user = Storage::bytes_to_table(user_bytes).unwrap();
+        assert_eq!(
+            user.columns,
+            vec![
+                (String::from("id"), DataType::Int),
+                (String::from("name"), DataType::VarChar(10)),
+            ]
+        );
+        assert_eq!(user.rows.len(), 10);
+        assert_eq!(
+            user.rows[9],
+            Record::new(vec![Value::Int(10), Value::VarChar(String::from("judy"))])
+        );
+        let todo = Storage::bytes_to_table(todo_bytes).unwrap();
+        assert_eq!(todo.columns.len(), 3);
+        assert_eq!(todo.rows.len(), 15);
+        assert_eq!(
+            todo.rows[0],
+            Record::new(vec![
+                Value::Int(1),
+                Value::Int(1),
+                Value::VarChar(String::from("alice 1"))
+            ])
+        );
+
+        // read from the disk, and written in the current format by the next flush
+        let dir = std::env::temp_dir().join(format!("ubdb-test-v0-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let storage = Storage::new(dir.to_str().unwrap().to_string());
+        std::fs::write(storage.get_table_storage_path("user"), user_bytes).unwrap();
+        std::fs::write(storage.get_table_storage_path("todo"), todo_bytes).unwrap();
+        assert_eq!(
+            storage.load_schema("todo").unwrap(),
+            Some(Table::new(todo.name.clone(), todo.columns.clone(), vec![]))
+        );
+        assert_eq!(storage.load("todo").unwrap(), Some(todo.clone()));
+        // or by the first scan, whose records then have a place
+        let rows = storage
+            .scan(&todo)
+            .unwrap()
+            .unwrap()
+            .map(|row| row.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(rows, todo.rows);
+        let path = storage.get_table_storage_path("todo");
+        let (_, version) = Storage::open_table_file(&path).unwrap().unwrap();
+        assert_eq!(version, Storage::FORMAT_VERSION);
+        storage.flush(&user).unwrap();
+        let path = storage.get_table_storage_path("user");
+        let (_, version) = Storage::open_table_file(&path).unwrap().unwrap();
+        assert_eq!(version, Storage::FORMAT_VERSION);
+        assert_eq!(storage.load("user").unwrap(), Some(user));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// a v0 file written once values could be NULL
+    #[test]
+    fn test_load_null() {
+        let table = Table::new(
+            String::from("user"),
+            vec![
+                (String::from("id"), DataType::Int),
+                (String::from("name"), DataType::VarChar(10)),
+            ],
+            vec![
+                Record::new(vec![Value::Null, Value::VarChar(String::from("bob"))]),
+                Record::new(vec![Value::Int(2), Value::Null]),
+            ],
+        );
+        assert_eq!(
+            Storage::bytes_to_table(&[
+                0x04, // name length
+                0x75, 0x73, 0x65, 0x72, // user
+                0x00, 0x02, // 2 columns
+                0x00, 0x02, // column name length
+                0x69, 0x64, // id
+                0x00, // int
+                0x00, 0x04, // column name length
+                0x6e, 0x61, 0x6d, 0x65, // name
+                0x0a, 0x00, 0x0a, // varchar(10)
+                0x00, 0x02, // records length
+                0x01, // null bitmap (id)
+                0x00, 0x03, // bob length
+                0x62, 0x6f, 0x62, // bob
+                0x02, // null bitmap (name)
+                0x00, 0x00, 0x00, 0x02, // 2
+            ])
+            .unwrap(),
+            table
+        );
+    }
+
+    /// a v0 file written once tables had constraints
+    #[test]
+    fn test_load_constraints() {
+        let table = Table::new(
+            String::from("user"),
+            vec![(String::from("id"), DataType::Int)],
+            vec![
+                Record::new(vec![Value::Int(1)]),
+                Record::new(vec![Value::Int(2)]),
+            ],
+        )
+        .with_constraints(vec![Constraint::NotNull(String::from("id"))]);
+        let bytes = [
+            0x04, // name length
+            0x75,
+            0x73,
+            0x65,
+            0x72, // user
+            0x00,
+            0x01, // 1 column
+            0x00,
+            0x02, // column name length
+            0x69,
+            0x64, // id
+            0x00, // int
+            0x00,
+            0x01, // 1 constraint
+            ConstraintByteMap::NOT_NULL,
+            0x00,
+            0x02, // column name length
+            0x69,
+            0x64, // id
+            0x00,
+            0x02, // records length
+            0x00, // null bitmap
+            0x00,
+            0x00,
+            0x00,
+            0x01, // 1
+            0x00, // null bitmap
+            0x00,
+            0x00,
+            0x00,
+            0x02, // 2
+        ];
+        assert_eq!(Storage::bytes_to_table(&bytes).unwrap(), table);
+
+        // without any, the section still takes its count
+        let mut bytes = bytes.to_vec();
+        bytes.splice(12..19, [0x00, 0x00]);
+        assert_eq!(
+            Storage::bytes_to_table(&bytes).unwrap(),
+            table.clone().with_constraints(vec![])
+        );
+    }
+
+    #[test]
+    fn test_bytes_to_table_corrupted() {
+        let truncated = [
+            0x04, // name length
+            0x75, 0x73, 0x65, 0x72, // user
+            0x00, 0x01, // 1 column
+            0x00, 0x02, // column name length
+            0x69, 0x64, // id
+            0x00, // int
+            0x00, 0x01, // records length
+            0x00, 0x00, // truncated int
+        ];
+        assert!(matches!(
+            Storage::bytes_to_table(&truncated),
+            Err(Error::Corruption(_))
+        ));
+        assert!(matches!(
+            Storage::bytes_to_data_type(&[0xff]),
+            Err(Error::Corruption(_))
+        ));
+        assert!(matches!(
+            Storage::bytes_to_table(&[]),
+            Err(Error::Corruption(_))
+        ));
+    }
 }
@@ -1,19 +1,61 @@
 mod flush;
+mod heap;
+mod list;
 mod load;
+mod overflow;
+mod page;
+mod page_io;
+mod remove;
+mod rename;
+mod wal;
+
+use std::cell::RefCell;
+
+use super::buffer::{BufferPool, BufferStats};
+
+use self::wal::Wal;
+
+pub(crate) use heap::RecordId;
+pub(crate) use load::TableScan;
+pub(crate) use page::{PageId, PAGE_SIZE};
 
 pub struct Storage {
     pub storage_dir: String,
+    // every page of the table files is read and written through it
+    buffer: RefCell<BufferPool>,
+    // every change to the table files is logged to it first
+    wal: RefCell<Wal>,
 }
 
 impl Storage {
     const STORAGE_FILE_EXT: &'static str = "ubdb";
+    const TEMP_FILE_EXT: &'static str = "tmp";
+    /// starts every table file since v1; a v0 file starts with the length of the table name,
+    /// which is never 0
+    const FILE_MAGIC: &'static [u8] = b"\0UBDB";
+    /// v2: pages, the first holding the magic and the version (see `HeapFile`)
+    /// v1: the magic and a u16 version, u16 table name length and u64 record count
+    /// (v0 had no header, a u8 name length and a u16 record count, and gained a null bitmap
+    /// per record and then constraints after the columns without telling so)
+    const FORMAT_VERSION: u16 = 2;
 
     pub fn new(storage_path: String) -> Self {
+        Self::with_buffer_budget(storage_path, BufferPool::DEFAULT_BUDGET)
+    }
+
+    /// a storage caching at most `budget` bytes of pages
+    pub fn with_buffer_budget(storage_path: String, budget: usize) -> Self {
         Self {
             storage_dir: storage_path,
+            buffer: RefCell::new(BufferPool::new(budget)),
+            wal: RefCell::new(Wal::default()),
         }
     }
 
+    pub fn buffer_stats(&self) -> BufferStats {
+        self.buffer.borrow().stats()
+    }
+
     fn get_table_storage_path(&self, table_name: &str) -> String {
         format!(
             "{}/{}.{}",
@@ -22,12 +64,106 @@ impl Storage {
             Self::STORAGE_FILE_EXT
         )
     }
+
+    /// where a new table file is written before it replaces the old one
+    fn get_temp_storage_path(&self, table_name: &str) -> String {
+        format!(
+            "{}.{}",
+            self.get_table_storage_path(table_name),
+            Self::TEMP_FILE_EXT
+        )
+    }
 }
 
-impl Storage {}
+impl Storage {
+    fn null_bitmap_len(columns_len: usize) -> usize {
+        columns_len.div_ceil(8)
+    }
+}
 
 #[allow(non_snake_case)]
 pub(crate) mod DataTypeByteMap {
     pub const INT: u8 = 0;
+    pub const BIGINT: u8 = 1;
+    pub const BOOLEAN: u8 = 2;
+    pub const DOUBLE: u8 = 3;
+    pub const DECIMAL: u8 = 4;
+    pub const DATE: u8 = 5;
+    pub const TIME: u8 = 6;
+    pub const TIMESTAMP: u8 = 7;
+    pub const INTERVAL: u8 = 8;
     pub const VARCHAR: u8 = 10;
+    pub const TEXT: u8 = 11;
+    pub const BLOB: u8 = 12;
+    pub const JSON: u8 = 13;
+}
+
+/// where a TEXT, BLOB or JSON value, or a record in its slot, is kept, the first byte of its encoding
+#[allow(non_snake_case)]
+pub(crate) mod ValueStorageByteMap {
+    // a u32 length and the bytes (a record: the bytes alone)
+    pub const INLINE: u8 = 0;
+    // a u64 length and the first of the overflow pages holding the bytes
+    pub const OVERFLOW: u8 = 1;
+}
+
+/// the first byte of every page but the meta page
+#[allow(non_snake_case)]
+pub(crate) mod PageKindByteMap {
+    // the next free page (u32)
+    pub const FREE: u8 = 0;
+    // a slotted page of records
+    pub const DATA: u8 = 1;
+    // the next page (u32), the data length (u16) and the data
+    pub const OVERFLOW: u8 = 2;
+}
+
+/// the first byte of every record of the write-ahead log
+#[allow(non_snake_case)]
+pub(crate) mod WalRecordByteMap {
+    // the table name, page (u32), file length (u64), whether the page was inside the file (u8),
+    // the page before if it was, and after
+    pub const PAGE: u8 = 0;
+    // the table name
+    pub const CREATE: u8 = 1;
+    // the table name and the name of its file moved aside
+    pub const DROP: u8 = 2;
+    // the old and new table names
+    pub const RENAME: u8 = 3;
+    // whether the table files were synced before it (u8)
+    pub const COMMIT: u8 = 4;
+}
+
+/// the binary form of JSON, each value starting with its kind
+#[allow(non_snake_case)]
+pub(crate) mod JsonByteMap {
+    pub const NULL: u8 = 0;
+    pub const FALSE: u8 = 1;
+    pub const TRUE: u8 = 2;
+    // f64
+    pub const NUMBER: u8 = 3;
+    // u32 length and utf-8 bytes
+    pub const STRING: u8 = 4;
+    // u32 count and the elements
+    pub const ARRAY: u8 = 5;
+    // u32 count and the members, each a u32 length, the key's utf-8 bytes and the value
+    pub const OBJECT: u8 = 6;
+}
+
+#[allow(non_snake_case)]
+pub(crate) mod ConstraintByteMap {
+    pub const PRIMARY_KEY: u8 = 0;
+    pub const UNIQUE: u8 = 1;
+    pub const NOT_NULL: u8 = 2;
+    pub const DEFAULT: u8 = 3;
+    pub const CHECK: u8 = 4;
+    pub const FOREIGN_KEY: u8 = 5;
+}
+
+#[allow(non_snake_case)]
+pub(crate) mod ReferentialActionByteMap {
+    pub const RESTRICT: u8 = 0;
+    pub const CASCADE: u8 = 1;
+    pub const SET_NULL: u8 = 2;
+    pub const SET_DEFAULT: u8 = 3;
 }
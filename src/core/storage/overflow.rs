@@ -0,0 +1,147 @@
+use crate::error::{Error, Result};
+
+/// TEXT, BLOB and JSON values longer than this many bytes are stored out of line
+pub(crate) const OVERFLOW_THRESHOLD: usize = 2048;
+
+/// the section of a v1 table file holding the values too large for their records
+///
+/// a value is a chain of pages, each starting with the index of the next page
+/// (`NO_NEXT_PAGE` on the last one) and the number of data bytes it holds;
+/// paged table files keep such chains among their other pages (see `HeapFile`),
+/// so v1 files are only written by tests
+#[derive(Debug, Default)]
+pub(crate) struct OverflowPages {
+    bytes: Vec<u8>,
+}
+
+impl OverflowPages {
+    pub const PAGE_SIZE: usize = 4096;
+    // next page (u32), data length (u16)
+    const HEADER_SIZE: usize = 6;
+    const DATA_SIZE: usize = Self::PAGE_SIZE - Self::HEADER_SIZE;
+    const NO_NEXT_PAGE: u32 = u32::MAX;
+
+    /// `bytes` must be a whole number of pages
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        if !bytes.len().is_multiple_of(Self::PAGE_SIZE) {
+            return Err(Error::Corruption(String::from(
+                "overflow pages are not page aligned",
+            )));
+        }
+        Ok(Self { bytes })
+    }
+
+    #[cfg(test)]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// number of pages
+    pub fn len(&self) -> usize {
+        self.bytes.len() / Self::PAGE_SIZE
+    }
+
+    #[cfg(test)]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    #[cfg(test)]
+    /// append `data` as a new chain, returning the index of its first page
+    pub fn write(&mut self, data: &[u8]) -> Result<u32> {
+        let pages_len = data.len().div_ceil(Self::DATA_SIZE).max(1);
+        let first_page = u32::try_from(self.len())
+            .ok()
+            .filter(|first| (*first as u64 + pages_len as u64) < Self::NO_NEXT_PAGE as u64)
+            .ok_or_else(|| Error::Invalid(String::from("too many overflow pages")))?;
+        for idx in 0..pages_len {
+            let chunk = &data[idx * Self::DATA_SIZE..data.len().min((idx + 1) * Self::DATA_SIZE)];
+            let next_page = if idx + 1 == pages_len {
+                Self::NO_NEXT_PAGE
+            } else {
+                first_page + idx as u32 + 1
+            };
+            let mut page = Vec::with_capacity(Self::PAGE_SIZE);
+            page.extend_from_slice(&next_page.to_be_bytes());
+            page.extend_from_slice(&(chunk.len() as u16).to_be_bytes());
+            page.extend_from_slice(chunk);
+            page.resize(Self::PAGE_SIZE, 0);
+            self.bytes.extend_from_slice(&page);
+        }
+        Ok(first_page)
+    }
+
+    /// the `len` bytes of the chain starting at `first_page`
+    pub fn read(&self, first_page: u32, len: u64) -> Result<Vec<u8>> {
+        let corruption = |message: &str| Error::Corruption(format!("{} ({})", message, first_page));
+        let mut data = Vec::with_capacity(len.min(self.bytes.len() as u64) as usize);
+        let mut page = first_page;
+        // a chain visits each page at most once, so a longer one is a cycle
+        for _ in 0..self.len() {
+            let start = (page as usize)
+                .checked_mul(Self::PAGE_SIZE)
+                .filter(|start| *start < self.bytes.len())
+                .ok_or_else(|| corruption("overflow page out of range"))?;
+            let b = &self.bytes[start..start + Self::PAGE_SIZE];
+            let next_page = u32::from_be_bytes([b[0], b[1], b[2], b[3]]);
+            let used = u16::from_be_bytes([b[4], b[5]]) as usize;
+            if used > Self::DATA_SIZE {
+                return Err(corruption("invalid overflow page"));
+            }
+            data.extend_from_slice(&b[Self::HEADER_SIZE..Self::HEADER_SIZE + used]);
+            if next_page == Self::NO_NEXT_PAGE {
+                if data.len() as u64 != len {
+                    return Err(corruption("overflow value has the wrong length"));
+                }
+                return Ok(data);
+            }
+            page = next_page;
+        }
+        Err(corruption("overflow chain does not end"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_write_and_read() {
+        let mut pages = OverflowPages::default();
+        assert!(pages.is_empty());
+
+        let small = vec![1u8; 10];
+        let large = (0..10_000).map(|i| i as u8).collect::<Vec<_>>();
+        assert_eq!(pages.write(&small).unwrap(), 0);
+        assert_eq!(pages.write(&large).unwrap(), 1);
+        assert_eq!(pages.write(&[]).unwrap(), 4);
+        assert_eq!(pages.len(), 5);
+        assert_eq!(pages.as_bytes().len(), 5 * OverflowPages::PAGE_SIZE);
+
+        let pages = OverflowPages::from_bytes(pages.as_bytes().to_vec()).unwrap();
+        assert_eq!(pages.read(0, 10).unwrap(), small);
+        assert_eq!(pages.read(1, 10_000).unwrap(), large);
+        assert_eq!(pages.read(4, 0).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_read_corrupted() {
+        let mut pages = OverflowPages::default();
+        pages.write(&[1u8; 5000]).unwrap();
+
+        assert!(matches!(pages.read(2, 5000), Err(Error::Corruption(_))));
+        assert!(matches!(pages.read(0, 4999), Err(Error::Corruption(_))));
+
+        // the second page points back at the first
+        let mut bytes = pages.as_bytes().to_vec();
+        bytes[OverflowPages::PAGE_SIZE..OverflowPages::PAGE_SIZE + 4]
+            .copy_from_slice(&0u32.to_be_bytes());
+        let pages = OverflowPages::from_bytes(bytes).unwrap();
+        assert!(matches!(pages.read(0, 5000), Err(Error::Corruption(_))));
+
+        assert!(matches!(
+            OverflowPages::from_bytes(vec![0; 10]),
+            Err(Error::Corruption(_))
+        ));
+    }
+}
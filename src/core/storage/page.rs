@@ -0,0 +1,303 @@
+use crate::error::{Error, Result};
+
+use super::PageKindByteMap;
+
+/// every page of a table file has this size
+pub(crate) const PAGE_SIZE: usize = 4096;
+
+/// index of a page in its table file, the meta page being 0
+pub(crate) type PageId = u32;
+
+/// the end of a chain of pages
+pub(crate) const NO_PAGE: PageId = PageId::MAX;
+
+/// a page of records, addressed by slot so that they can move within the page
+///
+/// the header (kind, number of slots, start of the record area) is followed by the slots,
+/// each the offset and length of its record, while the records fill the page from the end;
+/// a deleted record leaves a dead slot (offset 0) for the next one to reuse
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SlottedPage {
+    bytes: Vec<u8>,
+}
+
+impl SlottedPage {
+    // kind (u8), number of slots (u16), start of the record area (u16)
+    const HEADER_SIZE: usize = 5;
+    // offset (u16), length (u16)
+    const SLOT_SIZE: usize = 4;
+    /// the largest record a page can hold
+    pub const MAX_RECORD_SIZE: usize = PAGE_SIZE - Self::HEADER_SIZE - Self::SLOT_SIZE;
+
+    pub fn new() -> Self {
+        let mut bytes = vec![0; PAGE_SIZE];
+        bytes[0] = PageKindByteMap::DATA;
+        let mut page = Self { bytes };
+        page.set_records_start(PAGE_SIZE);
+        page
+    }
+
+    /// `bytes` must be a data page, with every slot inside the record area
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        if bytes.len() != PAGE_SIZE || bytes[0] != PageKindByteMap::DATA {
+            return Err(Error::Corruption(String::from("not a data page")));
+        }
+        let page = Self { bytes };
+        let records_start = page.records_start();
+        if Self::HEADER_SIZE + page.slots_len() as usize * Self::SLOT_SIZE > records_start
+            || records_start > PAGE_SIZE
+        {
+            return Err(Error::Corruption(String::from("invalid data page header")));
+        }
+        for slot in 0..page.slots_len() {
+            let (offset, len) = page.slot(slot);
+            if offset != 0 && (offset < records_start || offset + len > PAGE_SIZE) {
+                return Err(Error::Corruption(String::from("invalid slot")));
+            }
+        }
+        Ok(page)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn slots_len(&self) -> u16 {
+        u16::from_be_bytes([self.bytes[1], self.bytes[2]])
+    }
+
+    /// the record in the slot, None if it was deleted
+    pub fn get(&self, slot: u16) -> Option<&[u8]> {
+        if slot >= self.slots_len() {
+            return None;
+        }
+        match self.slot(slot) {
+            (0, _) => None,
+            (offset, len) => Some(&self.bytes[offset..offset + len]),
+        }
+    }
+
+    /// the live records with their slots
+    pub fn records(&self) -> impl Iterator<Item = (u16, &[u8])> {
+        (0..self.slots_len()).filter_map(|slot| self.get(slot).map(|record| (slot, record)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records().next().is_none()
+    }
+
+    /// store the record, returning its slot, or None if the page has no room for it
+    pub fn insert(&mut self, record: &[u8]) -> Option<u16> {
+        let dead_slot = (0..self.slots_len()).find(|slot| self.slot(*slot).0 == 0);
+        let slot_size = if dead_slot.is_some() {
+            0
+        } else {
+            Self::SLOT_SIZE
+        };
+        if record.len() + slot_size > self.free_space() {
+            return None;
+        }
+        if record.len() + slot_size > self.gap() {
+            self.compact();
+        }
+        let slot = match dead_slot {
+            Some(slot) => slot,
+            None => {
+                let slot = self.slots_len();
+                self.bytes[1..3].copy_from_slice(&(slot + 1).to_be_bytes());
+                slot
+            }
+        };
+        let offset = self.records_start() - record.len();
+        self.bytes[offset..offset + record.len()].copy_from_slice(record);
+        self.set_records_start(offset);
+        self.set_slot(slot, offset, record.len());
+        Some(slot)
+    }
+
+    /// replace the record in the slot, keeping the slot,
+    /// false if the page has no room for the new one
+    pub fn update(&mut self, slot: u16, record: &[u8]) -> bool {
+        let Some(old_len) = self.get(slot).map(<[u8]>::len) else {
+            return false;
+        };
+        if record.len() > self.free_space() + old_len {
+            return false;
+        }
+        // a dead slot until the record is written again, so that compaction leaves it out
+        self.set_slot(slot, 0, 0);
+        if record.len() > self.gap() {
+            self.compact();
+        }
+        let offset = self.records_start() - record.len();
+        self.bytes[offset..offset + record.len()].copy_from_slice(record);
+        self.set_records_start(offset);
+        self.set_slot(slot, offset, record.len());
+        true
+    }
+
+    /// the space of the record is reclaimed by the next compaction
+    pub fn delete(&mut self, slot: u16) {
+        if slot >= self.slots_len() {
+            return;
+        }
+        self.set_slot(slot, 0, 0);
+        // trailing dead slots are dropped, the others keep the slots after them in place
+        let mut slots_len = self.slots_len();
+        while slots_len > 0 && self.slot(slots_len - 1).0 == 0 {
+            slots_len -= 1;
+        }
+        self.bytes[1..3].copy_from_slice(&slots_len.to_be_bytes());
+        if slots_len == 0 {
+            self.set_records_start(PAGE_SIZE);
+        }
+    }
+
+    /// bytes left for records and slots, once the page is compacted
+    pub fn free_space(&self) -> usize {
+        let used = self
+            .records()
+            .map(|(_, record)| record.len())
+            .sum::<usize>();
+        PAGE_SIZE - Self::HEADER_SIZE - self.slots_len() as usize * Self::SLOT_SIZE - used
+    }
+
+    /// bytes between the slots and the record area
+    fn gap(&self) -> usize {
+        self.records_start() - Self::HEADER_SIZE - self.slots_len() as usize * Self::SLOT_SIZE
+    }
+
+    /// move the live records to the end of the page, leaving the free space in one piece
+    fn compact(&mut self) {
+        let records = self
+            .records()
+            .map(|(slot, record)| (slot, record.to_vec()))
+            .collect::<Vec<_>>();
+        let mut offset = PAGE_SIZE;
+        for (slot, record) in records {
+            offset -= record.len();
+            self.bytes[offset..offset + record.len()].copy_from_slice(&record);
+            self.set_slot(slot, offset, record.len());
+        }
+        self.set_records_start(offset);
+    }
+
+    fn records_start(&self) -> usize {
+        u16::from_be_bytes([self.bytes[3], self.bytes[4]]) as usize
+    }
+
+    fn set_records_start(&mut self, offset: usize) {
+        self.bytes[3..5].copy_from_slice(&(offset as u16).to_be_bytes());
+    }
+
+    fn slot(&self, slot: u16) -> (usize, usize) {
+        let at = Self::HEADER_SIZE + slot as usize * Self::SLOT_SIZE;
+        let b = &self.bytes[at..at + Self::SLOT_SIZE];
+        (
+            u16::from_be_bytes([b[0], b[1]]) as usize,
+            u16::from_be_bytes([b[2], b[3]]) as usize,
+        )
+    }
+
+    fn set_slot(&mut self, slot: u16, offset: usize, len: usize) {
+        let at = Self::HEADER_SIZE + slot as usize * Self::SLOT_SIZE;
+        self.bytes[at..at + 2].copy_from_slice(&(offset as u16).to_be_bytes());
+        self.bytes[at + 2..at + 4].copy_from_slice(&(len as u16).to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_delete() {
+        let mut page = SlottedPage::new();
+        assert!(page.is_empty());
+        assert_eq!(page.insert(b"alice"), Some(0));
+        assert_eq!(page.insert(b"bob"), Some(1));
+        assert_eq!(page.insert(b"carol"), Some(2));
+        assert_eq!(page.get(1), Some(&b"bob"[..]));
+
+        page.delete(1);
+        assert_eq!(page.get(1), None);
+        assert_eq!(
+            page.records().collect::<Vec<_>>(),
+            vec![(0, &b"alice"[..]), (2, &b"carol"[..])]
+        );
+        // the dead slot is reused
+        assert_eq!(page.insert(b"dave"), Some(1));
+
+        page.delete(2);
+        assert_eq!(page.slots_len(), 2);
+        page.delete(0);
+        page.delete(1);
+        assert!(page.is_empty());
+        assert_eq!(page.free_space(), PAGE_SIZE - SlottedPage::HEADER_SIZE);
+    }
+
+    #[test]
+    fn test_full_page() {
+        let mut page = SlottedPage::new();
+        let record = vec![0xab; 1000];
+        for slot in 0..4 {
+            assert_eq!(page.insert(&record), Some(slot));
+        }
+        assert_eq!(page.insert(&record), None);
+
+        // the space of the deleted records is reclaimed by compacting the page
+        page.delete(1);
+        page.delete(2);
+        let large = vec![0xcd; 2000];
+        assert_eq!(page.insert(&large), Some(1));
+        assert_eq!(page.get(0), Some(&record[..]));
+        assert_eq!(page.get(1), Some(&large[..]));
+        assert_eq!(page.get(3), Some(&record[..]));
+
+        // as it is for an updated record, which keeps its slot
+        page.delete(3);
+        assert!(page.update(1, &vec![0xef; 2500]));
+        assert_eq!(page.get(1), Some(&vec![0xef; 2500][..]));
+        assert!(!page.update(0, &vec![0xef; 1600]));
+        assert!(page.update(0, b"small"));
+        assert_eq!(page.get(0), Some(&b"small"[..]));
+        assert!(!page.update(2, b"deleted"));
+
+        let mut page = SlottedPage::new();
+        assert_eq!(
+            page.insert(&vec![0; SlottedPage::MAX_RECORD_SIZE + 1]),
+            None
+        );
+        assert_eq!(page.insert(&vec![0; SlottedPage::MAX_RECORD_SIZE]), Some(0));
+        assert_eq!(page.free_space(), 0);
+
+        let page = SlottedPage::from_bytes(page.as_bytes().to_vec()).unwrap();
+        assert_eq!(page.get(0).unwrap().len(), SlottedPage::MAX_RECORD_SIZE);
+    }
+
+    #[test]
+    fn test_from_bytes_corrupted() {
+        let mut page = SlottedPage::new();
+        page.insert(b"alice").unwrap();
+
+        let mut bytes = page.as_bytes().to_vec();
+        bytes[0] = PageKindByteMap::OVERFLOW;
+        assert!(matches!(
+            SlottedPage::from_bytes(bytes),
+            Err(Error::Corruption(_))
+        ));
+
+        // the slot points past the end of the page
+        let mut bytes = page.as_bytes().to_vec();
+        bytes[7..9].copy_from_slice(&100u16.to_be_bytes());
+        assert!(matches!(
+            SlottedPage::from_bytes(bytes),
+            Err(Error::Corruption(_))
+        ));
+
+        assert!(matches!(
+            SlottedPage::from_bytes(vec![PageKindByteMap::DATA; 10]),
+            Err(Error::Corruption(_))
+        ));
+    }
+}
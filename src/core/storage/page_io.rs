@@ -0,0 +1,91 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+use crate::error::Result;
+
+use super::{page::PageId, wal::WalRecord, Storage, PAGE_SIZE};
+
+impl Storage {
+    /// read a page of the table file, bypassing the buffer pool
+    pub(crate) fn read_page(&self, table_name: &str, page_id: PageId) -> Result<Vec<u8>> {
+        let mut file = File::open(self.get_table_storage_path(table_name))?;
+        file.seek(SeekFrom::Start(page_id as u64 * PAGE_SIZE as u64))?;
+        let mut page = vec![0; PAGE_SIZE];
+        file.read_exact(&mut page)?;
+        Ok(page)
+    }
+
+    /// write pages of the table file, bypassing the buffer pool,
+    /// once they are logged with what they replace
+    pub(crate) fn write_pages(&self, table_name: &str, pages: &[(PageId, &[u8])]) -> Result<()> {
+        self.in_transaction(|| {
+            let mut file = File::options()
+                .read(true)
+                .write(true)
+                .open(self.get_table_storage_path(table_name))?;
+            let mut file_len = file.metadata()?.len();
+            let mut records = vec![];
+            for (page_id, page) in pages {
+                let offset = *page_id as u64 * PAGE_SIZE as u64;
+                let before = if offset < file_len {
+                    let mut before = vec![0; PAGE_SIZE];
+                    file.seek(SeekFrom::Start(offset))?;
+                    file.read_exact(&mut before)?;
+                    Some(before)
+                } else {
+                    None
+                };
+                records.push(WalRecord::Page {
+                    table_name: table_name.to_string(),
+                    page_id: *page_id,
+                    file_len,
+                    before,
+                    after: page.to_vec(),
+                });
+                file_len = file_len.max(offset + PAGE_SIZE as u64);
+            }
+            self.log(&records)?;
+
+            for (page_id, page) in pages {
+                file.seek(SeekFrom::Start(*page_id as u64 * PAGE_SIZE as u64))?;
+                file.write_all(page)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// replace the table file with one of `pages` at once, never leaving it half-written:
+    /// they are written to a temporary file and synced, which is then renamed over the table file
+    pub(super) fn replace_file(&self, table_name: &str, pages: &[Vec<u8>]) -> Result<()> {
+        self.in_transaction(|| {
+            std::fs::create_dir_all(&self.storage_dir)?;
+            let temp_path = self.get_temp_storage_path(table_name);
+            let mut file = File::create(&temp_path)?;
+            for page in pages {
+                file.write_all(page)?;
+            }
+            file.sync_all()?;
+            drop(file);
+
+            let path = self.get_table_storage_path(table_name);
+            self.buffer.borrow_mut().discard_table(table_name);
+            if std::path::Path::new(&path).exists() {
+                // the old file stays linked to the backup until the transaction commits
+                let backup = self.backup_name(table_name);
+                self.log(&[WalRecord::Drop {
+                    table_name: table_name.to_string(),
+                    backup: backup.clone(),
+                }])?;
+                std::fs::hard_link(&path, format!("{}/{}", self.storage_dir, backup))?;
+            } else {
+                self.log(&[WalRecord::Create {
+                    table_name: table_name.to_string(),
+                }])?;
+            }
+            std::fs::rename(&temp_path, &path)?;
+            self.sync_dir()
+        })
+    }
+}
@@ -0,0 +1,48 @@
+use crate::error::Result;
+
+use super::{wal::WalRecord, Storage};
+
+impl Storage {
+    /// remove the storage file of the table, moved aside until the transaction commits
+    /// returns false if the table did not exist
+    pub fn remove(&self, table_name: &str) -> Result<bool> {
+        self.in_transaction(|| {
+            let path = self.get_table_storage_path(table_name);
+            self.buffer.borrow_mut().discard_table(table_name);
+            if !std::path::Path::new(&path).exists() {
+                return Ok(false);
+            }
+            let backup = self.backup_name(table_name);
+            self.log(&[WalRecord::Drop {
+                table_name: table_name.to_string(),
+                backup: backup.clone(),
+            }])?;
+            std::fs::rename(path, format!("{}/{}", self.storage_dir, backup))?;
+            Ok(true)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::core::table::{DataType, Table};
+
+    use super::*;
+
+    #[test]
+    fn test_remove() {
+        let dir = std::env::temp_dir().join(format!("ubdb-test-remove-{}", std::process::id()));
+        let storage = Storage::new(dir.to_str().unwrap().to_string());
+        let table = Table::new(
+            String::from("user"),
+            vec![(String::from("id"), DataType::Int)],
+            vec![],
+        );
+        storage.flush(&table).unwrap();
+
+        assert!(storage.load("user").unwrap().is_some());
+        assert!(storage.remove("user").unwrap());
+        assert!(storage.load("user").unwrap().is_none());
+        assert!(!storage.remove("user").unwrap());
+    }
+}
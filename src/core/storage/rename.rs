@@ -0,0 +1,61 @@
+use crate::error::Result;
+
+use super::{wal::WalRecord, Storage};
+
+impl Storage {
+    /// move the storage file of the table to the new name
+    /// the caller is responsible for flushing the table with its new name afterwards
+    pub fn rename(&self, from: &str, to: &str) -> Result<()> {
+        self.in_transaction(|| {
+            // the pages are cached by table name, and flushed already
+            let mut buffer = self.buffer.borrow_mut();
+            buffer.discard_table(from);
+            buffer.discard_table(to);
+            drop(buffer);
+            let from_path = self.get_table_storage_path(from);
+            let to_path = self.get_table_storage_path(to);
+            // undoing the rename must find the files as they were
+            std::fs::metadata(&from_path)?;
+            if std::path::Path::new(&to_path).exists() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("table file {} already exists", to_path),
+                )
+                .into());
+            }
+            self.log(&[WalRecord::Rename {
+                from: from.to_string(),
+                to: to.to_string(),
+            }])?;
+            std::fs::rename(from_path, to_path)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::core::table::{DataType, Table};
+
+    use super::*;
+
+    #[test]
+    fn test_rename() {
+        let dir = std::env::temp_dir().join(format!("ubdb-test-rename-{}", std::process::id()));
+        let storage = Storage::new(dir.to_str().unwrap().to_string());
+        let mut table = Table::new(
+            String::from("user"),
+            vec![(String::from("id"), DataType::Int)],
+            vec![],
+        );
+        storage.flush(&table).unwrap();
+
+        storage.rename("user", "member").unwrap();
+        table.name = String::from("member");
+        storage.flush(&table).unwrap();
+
+        assert!(storage.load("user").unwrap().is_none());
+        assert_eq!(storage.load("member").unwrap().unwrap(), table);
+        assert!(storage.rename("user", "member").is_err());
+    }
+}
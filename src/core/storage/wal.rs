@@ -0,0 +1,781 @@
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+use crate::error::{Error, Result};
+
+use super::{page::PageId, Storage, WalRecordByteMap, PAGE_SIZE};
+
+/// a change to the table files, logged before it is made
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum WalRecord {
+    // a page written, with what it held before, None if it was past the end of the file
+    // (`file_len` long then)
+    Page {
+        table_name: String,
+        page_id: PageId,
+        file_len: u64,
+        before: Option<Vec<u8>>,
+        after: Vec<u8>,
+    },
+    // a table file created where there was none
+    Create {
+        table_name: String,
+    },
+    // a table file kept as `backup` in the storage directory before it is removed or replaced,
+    // until the transaction commits
+    Drop {
+        table_name: String,
+        backup: String,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+    // the end of a transaction, `durable` if every table file was synced before it
+    Commit {
+        durable: bool,
+    },
+}
+
+/// the write-ahead log of the storage directory
+///
+/// a transaction is the records since the last commit; those of committed transactions are
+/// replayed on recovery, except those before a durable commit, and those of the transaction
+/// left open by a crash are undone
+#[derive(Default)]
+pub(super) struct Wal {
+    // opened on the first record
+    file: Option<File>,
+    // nesting of `Storage::begin`
+    depth: usize,
+    // where the records of the open transaction start, None if it has none yet
+    txn_start: Option<u64>,
+    // tables whose files the open transaction changed
+    txn_tables: BTreeSet<String>,
+    // whether the open transaction created, removed or renamed a file
+    txn_file_ops: bool,
+    // files removed by the open transaction, kept until it commits
+    txn_backups: Vec<String>,
+    // tables whose files changed since they were last synced
+    unsynced: BTreeSet<String>,
+}
+
+impl Storage {
+    const WAL_FILE: &'static str = "ubdb.wal";
+    const BACKUP_FILE_EXT: &'static str = "dropped";
+    /// the first commit after the log grows past this syncs the table files and empties it
+    const CHECKPOINT_SIZE: u64 = 4 << 20;
+
+    /// start a transaction, or join the one already open
+    pub fn begin(&self) {
+        self.wal.borrow_mut().depth += 1;
+    }
+
+    /// end the transaction, whose changes survive a crash once this returns,
+    /// or are undone if it fails; a transaction that joined another ends with it
+    pub fn commit(&self) -> Result<()> {
+        if !self.leave_transaction() {
+            return Ok(());
+        }
+        let result = self.write_commit();
+        if result.is_err() {
+            self.undo_transaction()?;
+        }
+        result
+    }
+
+    /// undo the changes of the transaction,
+    /// or leave that to the one it joined
+    pub fn rollback(&self) -> Result<()> {
+        if !self.leave_transaction() {
+            return Ok(());
+        }
+        self.undo_transaction()
+    }
+
+    /// bring the table files to the state the last committed transaction left them in,
+    /// before anything else is read or written after a crash
+    pub fn recover(&self) -> Result<()> {
+        self.replay_wal()?;
+        self.sweep()
+    }
+
+    fn replay_wal(&self) -> Result<()> {
+        let bytes = match std::fs::read(self.wal_path()) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        let (records, _) = decode_records(&bytes)?;
+        let committed = records
+            .iter()
+            .rposition(|record| matches!(record, WalRecord::Commit { .. }))
+            .map_or(0, |idx| idx + 1);
+        let replayed = records[..committed]
+            .iter()
+            .rposition(|record| matches!(record, WalRecord::Commit { durable: true }))
+            .map_or(0, |idx| idx + 1);
+        // undone first, so that the files replayed into are those the committed ones wrote
+        self.undo(&records[committed..])?;
+        self.redo(&records[replayed..committed])?;
+
+        let tables = records.iter().flat_map(table_names).collect();
+        self.sync_table_files(&tables)?;
+        self.buffer.borrow_mut().discard_all();
+        let mut wal = self.wal.borrow_mut();
+        *wal = Wal::default();
+        let file = File::options().write(true).open(self.wal_path())?;
+        file.set_len(0)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    /// run `f` in a transaction of its own, or in the one already open
+    pub(super) fn in_transaction<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        self.begin();
+        match f() {
+            Ok(value) => {
+                self.commit()?;
+                Ok(value)
+            }
+            Err(err) => {
+                self.rollback()?;
+                Err(err)
+            }
+        }
+    }
+
+    /// append the records to the log and sync it, before the changes they describe are made
+    pub(super) fn log(&self, records: &[WalRecord]) -> Result<()> {
+        let mut wal = self.wal.borrow_mut();
+        let mut bytes = vec![];
+        for record in records {
+            encode_record(record, &mut bytes)?;
+            for table_name in table_names(record) {
+                wal.txn_tables.insert(table_name.clone());
+                wal.unsynced.insert(table_name);
+            }
+            match record {
+                WalRecord::Page { .. } | WalRecord::Commit { .. } => {}
+                WalRecord::Drop { backup, .. } => {
+                    wal.txn_file_ops = true;
+                    wal.txn_backups.push(backup.clone());
+                }
+                WalRecord::Create { .. } | WalRecord::Rename { .. } => wal.txn_file_ops = true,
+            }
+        }
+        let file = self.wal_file(&mut wal)?;
+        let end = file.seek(SeekFrom::End(0))?;
+        file.write_all(&bytes)?;
+        file.sync_data()?;
+        wal.txn_start.get_or_insert(end);
+        Ok(())
+    }
+
+    /// a name in the storage directory, not a table file, to keep the file of the table as
+    pub(super) fn backup_name(&self, table_name: &str) -> String {
+        let wal = self.wal.borrow();
+        format!(
+            "{}.{}.{}",
+            table_name,
+            wal.txn_backups.len(),
+            Self::BACKUP_FILE_EXT
+        )
+    }
+
+    /// remove the temporary files and backups a crash left behind,
+    /// none of which the table files depend on once the log is replayed
+    fn sweep(&self) -> Result<()> {
+        let entries = match std::fs::read_dir(&self.storage_dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut swept = false;
+        for entry in entries {
+            let path = entry?.path();
+            let ext = path.extension().and_then(|ext| ext.to_str());
+            if ext == Some(Self::TEMP_FILE_EXT) || ext == Some(Self::BACKUP_FILE_EXT) {
+                std::fs::remove_file(path)?;
+                swept = true;
+            }
+        }
+        if swept {
+            self.sync_dir()?;
+        }
+        Ok(())
+    }
+
+    fn wal_path(&self) -> String {
+        format!("{}/{}", self.storage_dir, Self::WAL_FILE)
+    }
+
+    /// the log, cut after its last whole record when first opened
+    fn wal_file<'w>(&self, wal: &'w mut Wal) -> Result<&'w mut File> {
+        if wal.file.is_none() {
+            std::fs::create_dir_all(&self.storage_dir)?;
+            let mut file = File::options()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(self.wal_path())?;
+            let mut bytes = vec![];
+            file.read_to_end(&mut bytes)?;
+            let (_, len) = decode_records(&bytes)?;
+            file.set_len(len as u64)?;
+            wal.file = Some(file);
+        }
+        Ok(wal.file.as_mut().unwrap())
+    }
+
+    /// whether the outermost transaction is the one ending
+    fn leave_transaction(&self) -> bool {
+        let mut wal = self.wal.borrow_mut();
+        debug_assert!(wal.depth > 0, "ended a transaction that was not begun");
+        wal.depth = wal.depth.saturating_sub(1);
+        wal.depth == 0
+    }
+
+    fn write_commit(&self) -> Result<()> {
+        let (durable, backups) = {
+            let mut wal = self.wal.borrow_mut();
+            if wal.txn_start.is_none() {
+                return Ok(());
+            }
+            let len = self.wal_file(&mut wal)?.metadata()?.len();
+            (
+                wal.txn_file_ops || len > Self::CHECKPOINT_SIZE,
+                wal.txn_backups.clone(),
+            )
+        };
+        // files created, removed or renamed cannot be replayed, so they are synced instead
+        if durable {
+            let unsynced = self.wal.borrow().unsynced.clone();
+            self.sync_table_files(&unsynced)?;
+        }
+        self.log(&[WalRecord::Commit { durable }])?;
+
+        let mut wal = self.wal.borrow_mut();
+        wal.txn_start = None;
+        wal.txn_tables.clear();
+        wal.txn_file_ops = false;
+        wal.txn_backups.clear();
+        if durable {
+            wal.unsynced.clear();
+            let file = self.wal_file(&mut wal)?;
+            file.set_len(0)?;
+            file.sync_data()?;
+        }
+        drop(wal);
+        for backup in backups {
+            self.remove_if_exists(&format!("{}/{}", self.storage_dir, backup))?;
+        }
+        Ok(())
+    }
+
+    /// undo the records of the open transaction and cut them from the log
+    fn undo_transaction(&self) -> Result<()> {
+        let mut wal = self.wal.borrow_mut();
+        let tables = std::mem::take(&mut wal.txn_tables);
+        wal.txn_file_ops = false;
+        wal.txn_backups.clear();
+        let Some(start) = wal.txn_start.take() else {
+            return Ok(());
+        };
+        let file = self.wal_file(&mut wal)?;
+        file.seek(SeekFrom::Start(start))?;
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)?;
+        let (records, _) = decode_records(&bytes)?;
+        drop(wal);
+
+        // the pages cached were those being undone
+        let mut buffer = self.buffer.borrow_mut();
+        for table_name in tables.iter() {
+            buffer.discard_table(table_name);
+        }
+        drop(buffer);
+        self.undo(&records)?;
+        let mut wal = self.wal.borrow_mut();
+        let file = self.wal_file(&mut wal)?;
+        file.set_len(start)?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// revert the records, last first, whether the change each describes was made or not
+    fn undo(&self, records: &[WalRecord]) -> Result<()> {
+        for record in records.iter().rev() {
+            match record {
+                WalRecord::Page {
+                    table_name,
+                    page_id,
+                    file_len,
+                    before,
+                    ..
+                } => {
+                    let Some(mut file) = self.open_table_file_for_write(table_name)? else {
+                        continue;
+                    };
+                    match before {
+                        Some(before) => {
+                            file.seek(SeekFrom::Start(*page_id as u64 * PAGE_SIZE as u64))?;
+                            file.write_all(before)?;
+                        }
+                        None => file.set_len(*file_len)?,
+                    }
+                }
+                WalRecord::Create { table_name } => {
+                    self.remove_if_exists(&self.get_table_storage_path(table_name))?;
+                }
+                WalRecord::Drop { table_name, backup } => {
+                    let backup = format!("{}/{}", self.storage_dir, backup);
+                    if std::path::Path::new(&backup).exists() {
+                        std::fs::rename(backup, self.get_table_storage_path(table_name))?;
+                    }
+                }
+                WalRecord::Rename { from, to } => {
+                    let from = self.get_table_storage_path(from);
+                    let to = self.get_table_storage_path(to);
+                    if !std::path::Path::new(&from).exists() && std::path::Path::new(&to).exists() {
+                        std::fs::rename(to, from)?;
+                    }
+                }
+                WalRecord::Commit { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// write the pages again, which the transactions since the last durable commit
+    /// changed no file but by
+    fn redo(&self, records: &[WalRecord]) -> Result<()> {
+        for record in records {
+            if let WalRecord::Page {
+                table_name,
+                page_id,
+                after,
+                ..
+            } = record
+            {
+                let Some(mut file) = self.open_table_file_for_write(table_name)? else {
+                    continue;
+                };
+                file.seek(SeekFrom::Start(*page_id as u64 * PAGE_SIZE as u64))?;
+                file.write_all(after)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn open_table_file_for_write(&self, table_name: &str) -> Result<Option<File>> {
+        match File::options()
+            .write(true)
+            .open(self.get_table_storage_path(table_name))
+        {
+            Ok(file) => Ok(Some(file)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// sync the files of the tables that still exist, and the directory listing them
+    fn sync_table_files(&self, tables: &BTreeSet<String>) -> Result<()> {
+        for table_name in tables {
+            if let Some(file) = self.open_table_file_for_write(table_name)? {
+                file.sync_all()?;
+            }
+        }
+        self.sync_dir()
+    }
+
+    /// sync the storage directory, so that the files created, removed or renamed in it stay so
+    pub(super) fn sync_dir(&self) -> Result<()> {
+        match File::open(&self.storage_dir) {
+            Ok(dir) => dir.sync_all()?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        Ok(())
+    }
+
+    fn remove_if_exists(&self, path: &str) -> Result<()> {
+        match std::fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// the tables whose files the record changes
+fn table_names(record: &WalRecord) -> Vec<String> {
+    match record {
+        WalRecord::Page { table_name, .. }
+        | WalRecord::Create { table_name }
+        | WalRecord::Drop { table_name, .. } => vec![table_name.clone()],
+        WalRecord::Rename { from, to } => vec![from.clone(), to.clone()],
+        WalRecord::Commit { .. } => vec![],
+    }
+}
+
+/// the record framed by its length (u32) and checksum (u32)
+fn encode_record(record: &WalRecord, b: &mut Vec<u8>) -> Result<()> {
+    let mut payload = vec![];
+    match record {
+        WalRecord::Page {
+            table_name,
+            page_id,
+            file_len,
+            before,
+            after,
+        } => {
+            payload.push(WalRecordByteMap::PAGE);
+            encode_name(table_name, &mut payload)?;
+            payload.extend_from_slice(&page_id.to_be_bytes());
+            payload.extend_from_slice(&file_len.to_be_bytes());
+            match before {
+                Some(before) => {
+                    payload.push(1);
+                    payload.extend_from_slice(before);
+                }
+                None => payload.push(0),
+            }
+            payload.extend_from_slice(after);
+        }
+        WalRecord::Create { table_name } => {
+            payload.push(WalRecordByteMap::CREATE);
+            encode_name(table_name, &mut payload)?;
+        }
+        WalRecord::Drop { table_name, backup } => {
+            payload.push(WalRecordByteMap::DROP);
+            encode_name(table_name, &mut payload)?;
+            encode_name(backup, &mut payload)?;
+        }
+        WalRecord::Rename { from, to } => {
+            payload.push(WalRecordByteMap::RENAME);
+            encode_name(from, &mut payload)?;
+            encode_name(to, &mut payload)?;
+        }
+        WalRecord::Commit { durable } => {
+            payload.push(WalRecordByteMap::COMMIT);
+            payload.push(*durable as u8);
+        }
+    }
+    b.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    b.extend_from_slice(&checksum(&payload).to_be_bytes());
+    b.extend_from_slice(&payload);
+    Ok(())
+}
+
+fn encode_name(name: &str, b: &mut Vec<u8>) -> Result<()> {
+    b.extend_from_slice(&Storage::len_u16(name.len(), "table name")?.to_be_bytes());
+    b.extend_from_slice(name.as_bytes());
+    Ok(())
+}
+
+/// the whole records at the start of `bytes`, and their length;
+/// what follows is a record cut short by a crash
+fn decode_records(bytes: &[u8]) -> Result<(Vec<WalRecord>, usize)> {
+    let mut records = vec![];
+    let mut offset = 0;
+    while let Some(frame) = bytes.get(offset..offset + 8) {
+        let len = u32::from_be_bytes([frame[0], frame[1], frame[2], frame[3]]) as usize;
+        let sum = u32::from_be_bytes([frame[4], frame[5], frame[6], frame[7]]);
+        let Some(payload) = bytes.get(offset + 8..offset + 8 + len) else {
+            break;
+        };
+        if checksum(payload) != sum {
+            break;
+        }
+        records.push(decode_record(payload)?);
+        offset += 8 + len;
+    }
+    Ok((records, offset))
+}
+
+fn decode_record(payload: &[u8]) -> Result<WalRecord> {
+    let mut reader = Reader { bytes: payload };
+    let record = match reader.take(1)?[0] {
+        WalRecordByteMap::PAGE => {
+            let table_name = reader.name()?;
+            let page_id = u32::from_be_bytes(reader.take(4)?.try_into().unwrap());
+            let file_len = u64::from_be_bytes(reader.take(8)?.try_into().unwrap());
+            let before = match reader.take(1)?[0] {
+                0 => None,
+                _ => Some(reader.take(PAGE_SIZE)?.to_vec()),
+            };
+            let after = reader.take(PAGE_SIZE)?.to_vec();
+            WalRecord::Page {
+                table_name,
+                page_id,
+                file_len,
+                before,
+                after,
+            }
+        }
+        WalRecordByteMap::CREATE => WalRecord::Create {
+            table_name: reader.name()?,
+        },
+        WalRecordByteMap::DROP => WalRecord::Drop {
+            table_name: reader.name()?,
+            backup: reader.name()?,
+        },
+        WalRecordByteMap::RENAME => WalRecord::Rename {
+            from: reader.name()?,
+            to: reader.name()?,
+        },
+        WalRecordByteMap::COMMIT => WalRecord::Commit {
+            durable: reader.take(1)?[0] != 0,
+        },
+        kind => {
+            return Err(Error::Corruption(format!(
+                "unknown log record kind: {}",
+                kind
+            )))
+        }
+    };
+    if !reader.bytes.is_empty() {
+        return Err(Error::Corruption(String::from("invalid log record")));
+    }
+    Ok(record)
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+}
+
+impl<'b> Reader<'b> {
+    fn take(&mut self, len: usize) -> Result<&'b [u8]> {
+        if self.bytes.len() < len {
+            return Err(Error::Corruption(String::from("invalid log record")));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let len = u16::from_be_bytes(self.take(2)?.try_into().unwrap()) as usize;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| Error::Corruption(String::from("invalid table name in log")))
+    }
+}
+
+/// FNV-1a, telling a record cut short from a whole one
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::core::{
+        storage::RecordId,
+        table::{DataType, Record, Table, Value},
+    };
+
+    use super::*;
+
+    fn setup(name: &str) -> (Storage, Table) {
+        let dir =
+            std::env::temp_dir().join(format!("ubdb-test-wal-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let storage = Storage::new(dir.to_str().unwrap().to_string());
+        let table = Table::new(
+            String::from("user"),
+            vec![(String::from("id"), DataType::Int)],
+            (0..1000)
+                .map(|id| Record::new(vec![Value::Int(id)]))
+                .collect(),
+        );
+        storage.flush(&table).unwrap();
+        (storage, table)
+    }
+
+    /// where the rows of the table are kept, in order
+    fn ids(storage: &Storage, table: &Table) -> Vec<RecordId> {
+        storage
+            .scan(table)
+            .unwrap()
+            .unwrap()
+            .map(|row| row.unwrap().0)
+            .collect()
+    }
+
+    /// what is left of the storage after a crash, recovered
+    fn restart(storage: Storage) -> Storage {
+        let restarted = Storage::new(storage.storage_dir.clone());
+        restarted.recover().unwrap();
+        restarted
+    }
+
+    #[test]
+    fn test_records() {
+        let records = vec![
+            WalRecord::Page {
+                table_name: String::from("user"),
+                page_id: 3,
+                file_len: 2 * PAGE_SIZE as u64,
+                before: None,
+                after: vec![0xab; PAGE_SIZE],
+            },
+            WalRecord::Page {
+                table_name: String::from("user"),
+                page_id: 1,
+                file_len: 4 * PAGE_SIZE as u64,
+                before: Some(vec![0xcd; PAGE_SIZE]),
+                after: vec![0xef; PAGE_SIZE],
+            },
+            WalRecord::Create {
+                table_name: String::from("todo"),
+            },
+            WalRecord::Drop {
+                table_name: String::from("todo"),
+                backup: String::from("todo.0.dropped"),
+            },
+            WalRecord::Rename {
+                from: String::from("user"),
+                to: String::from("member"),
+            },
+            WalRecord::Commit { durable: true },
+        ];
+        let mut bytes = vec![];
+        for record in records.iter() {
+            encode_record(record, &mut bytes).unwrap();
+        }
+        assert_eq!(
+            decode_records(&bytes).unwrap(),
+            (records.clone(), bytes.len())
+        );
+
+        // a record cut short, or garbled, ends the log
+        let whole = bytes.len();
+        let cut = bytes[..20].to_vec();
+        bytes.extend_from_slice(&cut);
+        assert_eq!(decode_records(&bytes).unwrap(), (records.clone(), whole));
+        bytes.truncate(whole);
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert_eq!(decode_records(&bytes).unwrap().0, records[..5].to_vec());
+    }
+
+    #[test]
+    fn test_rollback() {
+        let (storage, table) = setup("rollback");
+        let path = storage.get_table_storage_path("user");
+        let bytes = std::fs::read(&path).unwrap();
+
+        storage.begin();
+        let mut changed = table.clone();
+        changed.rows.remove(500);
+        changed.rows.push(Record::new(vec![Value::Int(1000)]));
+        storage
+            .delete(&table, &[ids(&storage, &table)[500]])
+            .unwrap();
+        storage.insert(&table, &changed.rows[999..]).unwrap();
+        assert_eq!(storage.load("user").unwrap().as_ref(), Some(&changed));
+        // a new definition replaces the file
+        let mut replaced = changed.clone();
+        replaced.columns[0].0 = String::from("user_id");
+        storage.flush(&replaced).unwrap();
+        storage.rename("user", "member").unwrap();
+        let todo = Table::new(
+            String::from("todo"),
+            vec![(String::from("id"), DataType::Int)],
+            vec![],
+        );
+        storage.flush(&todo).unwrap();
+        assert_eq!(storage.load("member").unwrap(), Some(replaced));
+        storage.rollback().unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), bytes);
+        assert_eq!(storage.load("user").unwrap(), Some(table));
+        assert_eq!(storage.list().unwrap(), vec!["user"]);
+        // nothing is left of the files moved aside
+        assert_eq!(std::fs::read_dir(&storage.storage_dir).unwrap().count(), 2);
+        std::fs::remove_dir_all(&storage.storage_dir).unwrap();
+    }
+
+    #[test]
+    fn test_recover_undo() {
+        let (storage, table) = setup("undo");
+
+        // a crash in the middle of a transaction
+        storage.begin();
+        let mut changed = table.clone();
+        changed.rows.clear();
+        storage.flush(&changed).unwrap();
+        storage.remove("user").unwrap();
+        let todo = Table::new(
+            String::from("todo"),
+            vec![(String::from("id"), DataType::Int)],
+            vec![Record::new(vec![Value::Int(1)])],
+        );
+        storage.flush(&todo).unwrap();
+        // a table file written halfway
+        let todo_path = storage.get_table_storage_path("todo");
+        let todo_bytes = std::fs::read(&todo_path).unwrap();
+        std::fs::write(&todo_path, &todo_bytes[..todo_bytes.len() / 2 + 1]).unwrap();
+
+        let storage = restart(storage);
+        assert_eq!(storage.load("user").unwrap(), Some(table));
+        assert_eq!(storage.list().unwrap(), vec!["user"]);
+        std::fs::remove_dir_all(&storage.storage_dir).unwrap();
+    }
+
+    #[test]
+    fn test_sweep() {
+        let (storage, table) = setup("sweep");
+        // a new file written halfway, and an old one kept for a transaction that committed
+        let temp_path = storage.get_temp_storage_path("user");
+        std::fs::write(&temp_path, [0; 100]).unwrap();
+        let backup_path = format!("{}/{}", storage.storage_dir, storage.backup_name("user"));
+        std::fs::copy(storage.get_table_storage_path("user"), &backup_path).unwrap();
+
+        let storage = restart(storage);
+        assert!(!std::path::Path::new(&temp_path).exists());
+        assert!(!std::path::Path::new(&backup_path).exists());
+        assert_eq!(storage.list().unwrap(), vec!["user"]);
+        assert_eq!(storage.load("user").unwrap(), Some(table));
+        std::fs::remove_dir_all(&storage.storage_dir).unwrap();
+    }
+
+    #[test]
+    fn test_recover_redo() {
+        let (storage, table) = setup("redo");
+        let path = storage.get_table_storage_path("user");
+        let bytes = std::fs::read(&path).unwrap();
+
+        let mut changed = table.clone();
+        for row in changed.rows.iter_mut().skip(100) {
+            row.values[0] = Value::Int(-1);
+        }
+        let updates = ids(&storage, &table)
+            .into_iter()
+            .zip(changed.rows.iter().cloned())
+            .skip(100)
+            .collect::<Vec<_>>();
+        storage.update(&table, &updates).unwrap();
+        // committed, but the pages did not reach the file before the crash
+        std::fs::write(&path, &bytes).unwrap();
+        // and the log ends with a record cut short
+        let log_path = storage.wal_path();
+        let mut log = std::fs::read(&log_path).unwrap();
+        log.extend_from_slice(&[0, 0, 0x10, 0]);
+        std::fs::write(&log_path, log).unwrap();
+
+        let storage = restart(storage);
+        assert_eq!(storage.load("user").unwrap(), Some(changed.clone()));
+        assert_eq!(std::fs::metadata(storage.wal_path()).unwrap().len(), 0);
+        // recovering again changes nothing
+        let storage = restart(storage);
+        assert_eq!(storage.load("user").unwrap(), Some(changed));
+        std::fs::remove_dir_all(&storage.storage_dir).unwrap();
+    }
+}
@@ -1,38 +1,265 @@
-#[derive(Debug, PartialEq)]
+use std::cmp::Ordering;
+
+use crate::{
+    error::CatalogError,
+    query::ast::{Expr, ForeignKey},
+};
+
+use super::{
+    datetime::{Date, Interval, Time, Timestamp},
+    decimal::Decimal,
+    json::Json,
+};
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Table {
     pub name: String,
     pub columns: Vec<(String, DataType)>,
+    pub constraints: Vec<Constraint>,
     pub rows: Vec<Record>,
 }
 impl Table {
     pub fn new(name: String, columns: Vec<(String, DataType)>, rows: Vec<Record>) -> Self {
-        // TODO: validation
         Self {
             name,
             columns,
+            constraints: vec![],
             rows,
         }
     }
 
-    #[allow(dead_code)]
+    /// constraints are validated against the columns by the executor when the table is created
+    pub fn with_constraints(mut self, constraints: Vec<Constraint>) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    /// the value of a column omitted from an INSERT
+    pub fn default_value(&self, idx: usize) -> Value {
+        let column_name = &self.columns[idx].0;
+        self.constraints
+            .iter()
+            .find_map(|constraint| match constraint {
+                Constraint::Default(name, value) if name == column_name => Some(value.clone()),
+                _ => None,
+            })
+            .unwrap_or(Value::Null)
+    }
+
     pub fn insert(&mut self, record: Record) {
         self.rows.push(record);
     }
+
+    /// resolve `column_name` or `table_name.column_name`
+    /// columns of a joined table are qualified, so an unqualified name matches them only if unambiguous
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        let position = |name: &str| {
+            self.columns
+                .iter()
+                .position(|(column_name, _)| column_name == name)
+        };
+        if let Some(idx) = position(name) {
+            return Some(idx);
+        }
+
+        match name.split_once('.') {
+            Some((table_name, column_name)) if table_name == self.name => position(column_name),
+            Some(_) => None,
+            None => {
+                let suffix = format!(".{}", name);
+                let mut matches = self
+                    .columns
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (column_name, _))| column_name.ends_with(&suffix));
+                match (matches.next(), matches.next()) {
+                    (Some((idx, _)), None) => Some(idx),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// like `column_index`, but tells an unknown column from an ambiguous one
+    pub fn resolve_column(&self, name: &str) -> Result<usize, CatalogError> {
+        if let Some(idx) = self.column_index(name) {
+            return Ok(idx);
+        }
+        let suffix = format!(".{}", name);
+        let candidates = self
+            .columns
+            .iter()
+            .filter(|(column_name, _)| column_name.ends_with(&suffix))
+            .count();
+        if !name.contains('.') && candidates > 1 {
+            return Err(CatalogError::AmbiguousColumn(name.to_string()));
+        }
+        Err(CatalogError::UnknownColumn(name.to_string()))
+    }
+
+    /// append a column, filling every existing record with its default, NULL unless one is set
+    pub fn add_column(&mut self, name: String, data_type: DataType) {
+        self.columns.push((name, data_type));
+        let value = self.default_value(self.columns.len() - 1);
+        for record in self.rows.iter_mut() {
+            record.values.push(value.clone());
+        }
+    }
+
+    /// rename a column along with every constraint referring to it
+    pub fn rename_column(&mut self, idx: usize, to: String) {
+        let from = std::mem::replace(&mut self.columns[idx].0, to.clone());
+        for constraint in self.constraints.iter_mut() {
+            match constraint {
+                Constraint::PrimaryKey(names) | Constraint::Unique(names) => {
+                    for name in names.iter_mut().filter(|name| **name == from) {
+                        *name = to.clone();
+                    }
+                }
+                Constraint::NotNull(name) | Constraint::Default(name, _) => {
+                    if *name == from {
+                        *name = to.clone();
+                    }
+                }
+                Constraint::Check(expr) => expr.rename_column(&from, &to),
+                Constraint::ForeignKey(foreign_key) => {
+                    for name in foreign_key.columns.iter_mut().filter(|name| **name == from) {
+                        *name = to.clone();
+                    }
+                }
+            }
+        }
+        let name = self.name.clone();
+        self.rename_referenced_column(&name, &from, &to);
+    }
+
+    pub fn foreign_keys(&self) -> impl Iterator<Item = &ForeignKey> {
+        self.constraints
+            .iter()
+            .filter_map(|constraint| match constraint {
+                Constraint::ForeignKey(foreign_key) => Some(foreign_key),
+                _ => None,
+            })
+    }
+
+    /// whether a foreign key of this table refers to `column_name` of `table_name`
+    pub fn references(&self, table_name: &str, column_name: Option<&str>) -> bool {
+        self.foreign_keys().any(|foreign_key| {
+            foreign_key.ref_table == table_name
+                && column_name.is_none_or(|column_name| {
+                    foreign_key
+                        .ref_columns
+                        .iter()
+                        .any(|name| name == column_name)
+                })
+        })
+    }
+
+    /// follow the rename of a column in a table referenced by this one
+    pub fn rename_referenced_column(&mut self, table_name: &str, from: &str, to: &str) {
+        for constraint in self.constraints.iter_mut() {
+            if let Constraint::ForeignKey(foreign_key) = constraint {
+                if foreign_key.ref_table != table_name {
+                    continue;
+                }
+                for name in foreign_key
+                    .ref_columns
+                    .iter_mut()
+                    .filter(|name| *name == from)
+                {
+                    *name = to.to_string();
+                }
+            }
+        }
+    }
+
+    /// follow the rename of a table referenced by this one
+    pub fn rename_referenced_table(&mut self, from: &str, to: &str) {
+        for constraint in self.constraints.iter_mut() {
+            if let Constraint::ForeignKey(foreign_key) = constraint {
+                if foreign_key.ref_table == from {
+                    foreign_key.ref_table = to.to_string();
+                }
+            }
+        }
+    }
+
+    pub fn drop_column(&mut self, idx: usize) {
+        for record in self.rows.iter_mut() {
+            record.values.remove(idx);
+        }
+        self.columns.remove(idx);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Constraint {
+    // (column_names)
+    PrimaryKey(Vec<String>),
+    // (column_names)
+    Unique(Vec<String>),
+    // (column_name)
+    NotNull(String),
+    // (column_name, value)
+    Default(String, Value),
+    Check(Expr),
+    // referenced columns are always resolved
+    ForeignKey(ForeignKey),
+}
+impl Constraint {
+    pub fn references_column(&self, column_name: &str) -> bool {
+        match self {
+            Constraint::PrimaryKey(names) | Constraint::Unique(names) => {
+                names.iter().any(|name| name == column_name)
+            }
+            Constraint::NotNull(name) | Constraint::Default(name, _) => name == column_name,
+            Constraint::Check(expr) => expr.references_column(column_name),
+            Constraint::ForeignKey(foreign_key) => {
+                foreign_key.columns.iter().any(|name| name == column_name)
+            }
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum DataType {
+    Boolean,
     Int,
+    BigInt,
+    Double,
+    // (precision, scale)
+    Decimal(u8, u8),
     VarChar(u16),
+    Date,
+    Time,
+    Timestamp,
+    Interval,
+    // unbounded, its values are `Value::VarChar`
+    Text,
+    Blob,
+    Json,
 }
 impl DataType {
     #[allow(dead_code)]
     pub fn size(&self) -> usize {
         match self {
+            DataType::Boolean => 1,
             DataType::Int => 4,
+            DataType::BigInt | DataType::Double => 8,
+            DataType::Decimal(..) | DataType::Interval => 16,
             DataType::VarChar(size) => *size as usize,
+            DataType::Date => 4,
+            DataType::Time | DataType::Timestamp => 8,
+            DataType::Text | DataType::Blob | DataType::Json => usize::MAX,
         }
     }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            DataType::Int | DataType::BigInt | DataType::Double | DataType::Decimal(..)
+        )
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -45,10 +272,94 @@ impl Record {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// values are totally ordered: first by type (in declaration order), then by value
+/// (numbers numerically, strings lexicographically, false before true, dates and times
+/// chronologically, intervals by length, blobs bytewise, JSON as `Json` orders itself)
+///
+/// this is the order for sorting, grouping and keys, where the values compared are of one column;
+/// comparisons in expressions across numeric types are done by `expr::compare`
+#[derive(Debug, Clone)]
 pub enum Value {
+    // recorded in the null bitmap on disk
+    Null,
+    Boolean(bool),
     Int(i32),
+    BigInt(i64),
+    Double(f64),
+    Decimal(Decimal),
     VarChar(String),
+    Date(Date),
+    Time(Time),
+    Timestamp(Timestamp),
+    Interval(Interval),
+    Blob(Vec<u8>),
+    Json(Json),
+}
+impl Value {
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Null => 0,
+            Value::Boolean(_) => 1,
+            Value::Int(_) => 2,
+            Value::BigInt(_) => 3,
+            Value::Double(_) => 4,
+            Value::Decimal(_) => 5,
+            Value::VarChar(_) => 6,
+            Value::Date(_) => 7,
+            Value::Time(_) => 8,
+            Value::Timestamp(_) => 9,
+            Value::Interval(_) => 10,
+            Value::Blob(_) => 11,
+            Value::Json(_) => 12,
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value {}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Boolean(l), Value::Boolean(r)) => l.cmp(r),
+            (Value::Int(l), Value::Int(r)) => l.cmp(r),
+            (Value::BigInt(l), Value::BigInt(r)) => l.cmp(r),
+            (Value::Double(l), Value::Double(r)) => compare_f64(*l, *r),
+            (Value::Decimal(l), Value::Decimal(r)) => l.cmp(r),
+            (Value::VarChar(l), Value::VarChar(r)) => l.cmp(r),
+            (Value::Date(l), Value::Date(r)) => l.cmp(r),
+            (Value::Time(l), Value::Time(r)) => l.cmp(r),
+            (Value::Timestamp(l), Value::Timestamp(r)) => l.cmp(r),
+            (Value::Interval(l), Value::Interval(r)) => l.cmp(r),
+            (Value::Blob(l), Value::Blob(r)) => l.cmp(r),
+            (Value::Json(l), Value::Json(r)) => l.cmp(r),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+/// a total order on doubles where 0.0 = -0.0 and NaN is greater than any other number
+pub fn compare_f64(l: f64, r: f64) -> Ordering {
+    if l == r {
+        return Ordering::Equal;
+    }
+    match (l.is_nan(), r.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => l.total_cmp(&r),
+    }
 }
 
 #[cfg(test)]
@@ -61,4 +372,100 @@ mod test {
         assert_eq!(DataType::VarChar(10).size(), 10);
         assert_eq!(DataType::VarChar(65535).size(), 65535);
     }
+
+    #[test]
+    fn test_add_and_drop_column() {
+        let mut table = Table::new(
+            String::from("user"),
+            vec![(String::from("id"), DataType::Int)],
+            vec![
+                Record::new(vec![Value::Int(1)]),
+                Record::new(vec![Value::Int(2)]),
+            ],
+        );
+
+        table.add_column(String::from("name"), DataType::VarChar(10));
+        assert_eq!(table.column_index("name"), Some(1));
+        assert_eq!(
+            table.rows,
+            vec![
+                Record::new(vec![Value::Int(1), Value::Null]),
+                Record::new(vec![Value::Int(2), Value::Null]),
+            ]
+        );
+
+        table.drop_column(0);
+        assert_eq!(
+            table.columns,
+            vec![(String::from("name"), DataType::VarChar(10))]
+        );
+        assert_eq!(
+            table.rows,
+            vec![
+                Record::new(vec![Value::Null]),
+                Record::new(vec![Value::Null]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_column_index() {
+        let table = Table::new(
+            String::from("user"),
+            vec![
+                (String::from("id"), DataType::Int),
+                (String::from("todo.id"), DataType::Int),
+                (String::from("todo.title"), DataType::VarChar(10)),
+            ],
+            vec![],
+        );
+        assert_eq!(table.column_index("id"), Some(0));
+        assert_eq!(table.column_index("user.id"), Some(0));
+        assert_eq!(table.column_index("todo.id"), Some(1));
+        assert_eq!(table.column_index("title"), Some(2));
+        assert_eq!(table.column_index("todo.name"), None);
+        assert_eq!(table.column_index("other.id"), None);
+
+        let joined = Table::new(
+            String::from("a JOIN b"),
+            vec![
+                (String::from("a.id"), DataType::Int),
+                (String::from("b.id"), DataType::Int),
+            ],
+            vec![],
+        );
+        // ambiguous
+        assert_eq!(joined.column_index("id"), None);
+        assert_eq!(joined.column_index("b.id"), Some(1));
+        assert_eq!(
+            joined.resolve_column("id"),
+            Err(CatalogError::AmbiguousColumn(String::from("id")))
+        );
+        assert_eq!(
+            joined.resolve_column("c.id"),
+            Err(CatalogError::UnknownColumn(String::from("c.id")))
+        );
+    }
+
+    #[test]
+    fn test_value_ordering() {
+        let mut values = vec![
+            Value::VarChar(String::from("b")),
+            Value::Null,
+            Value::Int(10),
+            Value::VarChar(String::from("a")),
+            Value::Int(-1),
+        ];
+        values.sort();
+        assert_eq!(
+            values,
+            vec![
+                Value::Null,
+                Value::Int(-1),
+                Value::Int(10),
+                Value::VarChar(String::from("a")),
+                Value::VarChar(String::from("b")),
+            ]
+        );
+    }
 }
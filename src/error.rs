@@ -0,0 +1,92 @@
+use std::fmt::Display;
+
+use crate::query::parser::ParseError;
+
+/// errors surfaced while parsing or executing a statement
+#[derive(Debug)]
+pub enum Error {
+    // the query is not valid syntax
+    Parse(ParseError),
+    // a table or column is missing or conflicts with an existing one
+    Catalog(CatalogError),
+    // a value does not fit the type it is used as
+    Type(String),
+    // the statement is well-formed but cannot be executed as written
+    Invalid(String),
+    // a write would violate a constraint of the table
+    Constraint(String),
+    // the storage could not be read or written
+    Io(std::io::Error),
+    // a storage file could not be decoded
+    Corruption(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CatalogError {
+    UnknownTable(String),
+    TableExists(String),
+    UnknownColumn(String),
+    AmbiguousColumn(String),
+    ColumnExists(String),
+    DuplicateColumn(String),
+    DuplicateTable(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse(err) => write!(f, "{}", err),
+            Error::Catalog(err) => write!(f, "{}", err),
+            Error::Type(message) | Error::Invalid(message) | Error::Constraint(message) => {
+                write!(f, "{}", message)
+            }
+            Error::Io(err) => write!(f, "I/O error: {}", err),
+            Error::Corruption(message) => write!(f, "corrupted storage: {}", message),
+        }
+    }
+}
+
+impl Display for CatalogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CatalogError::UnknownTable(name) => write!(f, "table {} does not exist", name),
+            CatalogError::TableExists(name) => write!(f, "table {} already exists", name),
+            CatalogError::UnknownColumn(name) => write!(f, "unknown column: {}", name),
+            CatalogError::AmbiguousColumn(name) => write!(f, "ambiguous column: {}", name),
+            CatalogError::ColumnExists(name) => write!(f, "column {} already exists", name),
+            CatalogError::DuplicateColumn(name) => write!(f, "duplicate column: {}", name),
+            CatalogError::DuplicateTable(name) => {
+                write!(f, "table name {} specified more than once", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<CatalogError> for Error {
+    fn from(err: CatalogError) -> Self {
+        Error::Catalog(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
@@ -1,5 +1,3 @@
-mod core;
-mod query;
 mod repl;
 
 fn main() {
@@ -1,30 +1,574 @@
+use std::fmt::Display;
+
 #[derive(Debug)]
-pub struct Query(Vec<QueryStatement>);
+pub struct Query(pub Vec<QueryStatement>);
 
 #[derive(Debug, PartialEq)]
 pub enum QueryStatement {
-    // TODO: AND, OR, others
-    // (table_name, is_all, columns, where(key_name, value))
-    Select(String, bool, Vec<String>, Option<(String, Value)>),
+    Select(SelectStatement),
+
+    // (table_name, set(key_name, value)[], where)
+    Update(String, Vec<(String, Value)>, Option<Expr>),
+
+    // (table_name, columns, values[][])
+    // columns is empty when omitted (all columns in table order)
+    Insert(String, Vec<String>, Vec<Vec<Value>>),
+
+    // (table_name, where)
+    Delete(String, Option<Expr>),
+
+    // (table_name, (column_name, data_type)[], constraints)
+    // column constraints are normalized into table constraints
+    CreateTable(String, Vec<(String, DataType)>, Vec<Constraint>),
 
-    // TODO: AND, OR, others
-    // (table_name, set(key_name, value)[], where(key_name, value))
-    Update(String, Vec<(String, Value)>, (String, Value)),
+    // (table_name, action)
+    AlterTable(String, AlterTableAction),
 
-    // (table_name, (column_name, data_type)[])
-    CreateTable(String, Vec<(String, DataType)>),
+    // (table_name, if_exists)
+    DropTable(String, bool),
+
+    // (table_name)
+    Truncate(String),
 
     Exit,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct SelectStatement {
+    pub from: TableRef,
+    pub projections: Vec<SelectItem>,
+    pub cond: Option<Expr>,
+    pub group_by: Vec<Expr>,
+    pub having: Option<Expr>,
+    pub order_by: Vec<(Expr, Order)>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum TableRef {
+    // (table_name, alias)
+    Table(String, Option<String>),
+    // (left, join_type, right, on)
+    Join(Box<TableRef>, JoinType, Box<TableRef>, Option<Expr>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum JoinType {
+    Inner,
+    Left,
+    Right,
+    Full,
+    // also used for comma joins
+    Cross,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SelectItem {
+    // *
+    Wildcard,
+    // (expr, alias)
+    Expr(Expr, Option<String>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr {
+    // column_name, or table_name.column_name when qualified
+    Column(String),
+    Value(Value),
+    // (left, operator, right)
+    Binary(Box<Expr>, BinaryOperator, Box<Expr>),
+    Not(Box<Expr>),
+    // -expr
+    Neg(Box<Expr>),
+    // (expr, negated) for expr IS [NOT] NULL
+    IsNull(Box<Expr>, bool),
+    // (function, arg), arg is None for COUNT(*)
+    Aggregate(AggregateFunction, Option<Box<Expr>>),
+    // (function, args)
+    Function(Function, Vec<Expr>),
+}
+
+impl Expr {
+    pub fn has_aggregate(&self) -> bool {
+        match self {
+            Expr::Aggregate(..) => true,
+            Expr::Binary(left, _, right) => left.has_aggregate() || right.has_aggregate(),
+            Expr::Not(expr) | Expr::Neg(expr) | Expr::IsNull(expr, _) => expr.has_aggregate(),
+            Expr::Function(_, args) => args.iter().any(|arg| arg.has_aggregate()),
+            Expr::Column(_) | Expr::Value(_) => false,
+        }
+    }
+}
+
+/// formats back to sql, also used as the default result column name
+impl Expr {
+    /// whether `name` is referred to anywhere in the expression
+    pub fn references_column(&self, name: &str) -> bool {
+        match self {
+            Expr::Column(column_name) => column_name == name,
+            Expr::Value(_) | Expr::Aggregate(_, None) => false,
+            Expr::Binary(left, _, right) => {
+                left.references_column(name) || right.references_column(name)
+            }
+            Expr::Not(expr) | Expr::Neg(expr) | Expr::IsNull(expr, _) => {
+                expr.references_column(name)
+            }
+            Expr::Aggregate(_, Some(arg)) => arg.references_column(name),
+            Expr::Function(_, args) => args.iter().any(|arg| arg.references_column(name)),
+        }
+    }
+
+    pub fn rename_column(&mut self, from: &str, to: &str) {
+        match self {
+            Expr::Column(column_name) if column_name == from => *column_name = to.to_string(),
+            Expr::Column(_) | Expr::Value(_) | Expr::Aggregate(_, None) => {}
+            Expr::Binary(left, _, right) => {
+                left.rename_column(from, to);
+                right.rename_column(from, to);
+            }
+            Expr::Not(expr) | Expr::Neg(expr) | Expr::IsNull(expr, _) => {
+                expr.rename_column(from, to)
+            }
+            Expr::Aggregate(_, Some(arg)) => arg.rename_column(from, to),
+            Expr::Function(_, args) => {
+                for arg in args.iter_mut() {
+                    arg.rename_column(from, to);
+                }
+            }
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // nested operators are parenthesized so that the output parses back to the same tree
+        let operand = |f: &mut std::fmt::Formatter<'_>, expr: &Expr| match expr {
+            Expr::Binary(..) | Expr::Not(_) | Expr::IsNull(..) => write!(f, "({})", expr),
+            _ => write!(f, "{}", expr),
+        };
+        match self {
+            Expr::Column(name) => write!(f, "{}", name),
+            Expr::Value(value) => write!(f, "{}", value),
+            Expr::Binary(left, operator, right) => {
+                operand(f, left)?;
+                write!(f, " {} ", operator)?;
+                operand(f, right)
+            }
+            Expr::Not(expr) => {
+                write!(f, "NOT ")?;
+                operand(f, expr)
+            }
+            // `-(1)` rather than `-1`, which is a literal
+            Expr::Neg(expr) => match **expr {
+                Expr::Column(_) | Expr::Aggregate(..) | Expr::Function(..) => {
+                    write!(f, "-{}", expr)
+                }
+                _ => write!(f, "-({})", expr),
+            },
+            Expr::IsNull(expr, negated) => {
+                operand(f, expr)?;
+                if *negated {
+                    write!(f, " IS NOT NULL")
+                } else {
+                    write!(f, " IS NULL")
+                }
+            }
+            Expr::Aggregate(function, None) => write!(f, "{}(*)", function),
+            Expr::Aggregate(function, Some(arg)) => write!(f, "{}({})", function, arg),
+            Expr::Function(Function::Extract(field), args) => {
+                write!(f, "EXTRACT({} FROM {})", field, args[0])
+            }
+            Expr::Function(Function::DateTrunc(field), args) => write!(
+                f,
+                "DATE_TRUNC('{}', {})",
+                field.to_string().to_lowercase(),
+                args[0]
+            ),
+            Expr::Function(function, args) => {
+                let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+                write!(f, "{}({})", function, args.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "COUNT" => Some(AggregateFunction::Count),
+            "SUM" => Some(AggregateFunction::Sum),
+            "AVG" => Some(AggregateFunction::Avg),
+            "MIN" => Some(AggregateFunction::Min),
+            "MAX" => Some(AggregateFunction::Max),
+            _ => None,
+        }
+    }
+}
+
+impl Display for AggregateFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AggregateFunction::Count => "COUNT",
+            AggregateFunction::Sum => "SUM",
+            AggregateFunction::Avg => "AVG",
+            AggregateFunction::Min => "MIN",
+            AggregateFunction::Max => "MAX",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// scalar functions, evaluated once per row
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Function {
+    // NOW()
+    Now,
+    // EXTRACT(field FROM arg)
+    Extract(DateTimeField),
+    // DATE_TRUNC('field', arg)
+    DateTrunc(DateTimeField),
+    // JSON_EXTRACT(json, path)
+    JsonExtract,
+    // JSON_ARRAY_LENGTH(json[, path])
+    JsonArrayLength,
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Function::Now => "NOW",
+            Function::Extract(_) => "EXTRACT",
+            Function::DateTrunc(_) => "DATE_TRUNC",
+            Function::JsonExtract => "JSON_EXTRACT",
+            Function::JsonArrayLength => "JSON_ARRAY_LENGTH",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DateTimeField {
+    Year,
+    Quarter,
+    Month,
+    // ISO 8601 week of the year
+    Week,
+    Day,
+    Hour,
+    Minute,
+    // including the fractional part
+    Second,
+    // DOW, 0 for Sunday through 6 for Saturday
+    DayOfWeek,
+    // DOY, starting at 1
+    DayOfYear,
+    // seconds since 1970-01-01 00:00:00 (or the length of an interval)
+    Epoch,
+}
+
+impl DateTimeField {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "YEAR" => Some(DateTimeField::Year),
+            "QUARTER" => Some(DateTimeField::Quarter),
+            "MONTH" => Some(DateTimeField::Month),
+            "WEEK" => Some(DateTimeField::Week),
+            "DAY" => Some(DateTimeField::Day),
+            "HOUR" => Some(DateTimeField::Hour),
+            "MINUTE" => Some(DateTimeField::Minute),
+            "SECOND" => Some(DateTimeField::Second),
+            "DOW" => Some(DateTimeField::DayOfWeek),
+            "DOY" => Some(DateTimeField::DayOfYear),
+            "EPOCH" => Some(DateTimeField::Epoch),
+            _ => None,
+        }
+    }
+}
+
+impl Display for DateTimeField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DateTimeField::Year => "YEAR",
+            DateTimeField::Quarter => "QUARTER",
+            DateTimeField::Month => "MONTH",
+            DateTimeField::Week => "WEEK",
+            DateTimeField::Day => "DAY",
+            DateTimeField::Hour => "HOUR",
+            DateTimeField::Minute => "MINUTE",
+            DateTimeField::Second => "SECOND",
+            DateTimeField::DayOfWeek => "DOW",
+            DateTimeField::DayOfYear => "DOY",
+            DateTimeField::Epoch => "EPOCH",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BinaryOperator {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    And,
+    Or,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    // ->, a JSON object field or array element
+    JsonGet,
+    // ->>, the same as text
+    JsonGetText,
+}
+
+impl BinaryOperator {
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinaryOperator::Eq
+                | BinaryOperator::NotEq
+                | BinaryOperator::Lt
+                | BinaryOperator::LtEq
+                | BinaryOperator::Gt
+                | BinaryOperator::GtEq
+        )
+    }
+
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(
+            self,
+            BinaryOperator::Add
+                | BinaryOperator::Sub
+                | BinaryOperator::Mul
+                | BinaryOperator::Div
+                | BinaryOperator::Mod
+        )
+    }
+}
+
+impl Display for BinaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operator = match self {
+            BinaryOperator::Eq => "=",
+            BinaryOperator::NotEq => "<>",
+            BinaryOperator::Lt => "<",
+            BinaryOperator::LtEq => "<=",
+            BinaryOperator::Gt => ">",
+            BinaryOperator::GtEq => ">=",
+            BinaryOperator::And => "AND",
+            BinaryOperator::Or => "OR",
+            BinaryOperator::Add => "+",
+            BinaryOperator::Sub => "-",
+            BinaryOperator::Mul => "*",
+            BinaryOperator::Div => "/",
+            BinaryOperator::Mod => "%",
+            BinaryOperator::JsonGet => "->",
+            BinaryOperator::JsonGetText => "->>",
+        };
+        write!(f, "{}", operator)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Constraint {
+    // (column_names)
+    PrimaryKey(Vec<String>),
+    // (column_names)
+    Unique(Vec<String>),
+    // (column_name)
+    NotNull(String),
+    // (column_name, value)
+    Default(String, Value),
+    Check(Expr),
+    ForeignKey(ForeignKey),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ForeignKey {
+    pub columns: Vec<String>,
+    pub ref_table: String,
+    // empty when omitted, meaning the primary key of ref_table
+    pub ref_columns: Vec<String>,
+    pub on_delete: ReferentialAction,
+    pub on_update: ReferentialAction,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ReferentialAction {
+    // also NO ACTION, the default
+    Restrict,
+    Cascade,
+    SetNull,
+    SetDefault,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum AlterTableAction {
+    // (column_name, data_type)
+    AddColumn(String, DataType),
+    // (column_name)
+    DropColumn(String),
+    // (from, to)
+    RenameColumn(String, String),
+    // (new_table_name)
+    RenameTo(String),
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum DataType {
+    Boolean,
     Int,
+    BigInt,
+    Double,
+    // (precision, scale)
+    Decimal(u8, u8),
     VarChar(u16),
+    Date,
+    Time,
+    Timestamp,
+    Interval,
+    Text,
+    Blob,
+    Json,
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Value {
-    Int(i32),
+    Int(i64),
+    // a number with a fractional part, kept as written (e.g. "-1.50")
+    Decimal(String),
+    Boolean(bool),
     VarChar(String),
+    // typed literals like DATE '2026-10-18', the quoted text kept as written
+    Date(String),
+    Time(String),
+    Timestamp(String),
+    Interval(String),
+    Json(String),
+    // X'...'
+    Blob(Vec<u8>),
+    Null,
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Int(v) => write!(f, "{}", v),
+            Value::Decimal(v) => write!(f, "{}", v),
+            Value::Boolean(true) => write!(f, "TRUE"),
+            Value::Boolean(false) => write!(f, "FALSE"),
+            Value::VarChar(v) => write!(f, "'{}'", v),
+            Value::Date(v) => write!(f, "DATE '{}'", v),
+            Value::Time(v) => write!(f, "TIME '{}'", v),
+            Value::Timestamp(v) => write!(f, "TIMESTAMP '{}'", v),
+            Value::Interval(v) => write!(f, "INTERVAL '{}'", v),
+            Value::Json(v) => write!(f, "JSON '{}'", v),
+            Value::Blob(v) => {
+                write!(f, "X'")?;
+                for byte in v {
+                    write!(f, "{:02X}", byte)?;
+                }
+                write!(f, "'")
+            }
+            Value::Null => write!(f, "NULL"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_display_expr() {
+        let expr = Expr::Binary(
+            Box::new(Expr::Binary(
+                Box::new(Expr::Column(String::from("id"))),
+                BinaryOperator::GtEq,
+                Box::new(Expr::Value(Value::Int(1))),
+            )),
+            BinaryOperator::And,
+            Box::new(Expr::Not(Box::new(Expr::Binary(
+                Box::new(Expr::Column(String::from("name"))),
+                BinaryOperator::Eq,
+                Box::new(Expr::Value(Value::VarChar(String::from("mike")))),
+            )))),
+        );
+        assert_eq!(expr.to_string(), "(id >= 1) AND (NOT (name = 'mike'))");
+
+        let expr = Expr::Not(Box::new(Expr::IsNull(
+            Box::new(Expr::Column(String::from("name"))),
+            true,
+        )));
+        assert_eq!(expr.to_string(), "NOT (name IS NOT NULL)");
+
+        assert_eq!(
+            Expr::Aggregate(AggregateFunction::Count, None).to_string(),
+            "COUNT(*)"
+        );
+        assert_eq!(
+            Expr::Aggregate(
+                AggregateFunction::Sum,
+                Some(Box::new(Expr::Column(String::from("id"))))
+            )
+            .to_string(),
+            "SUM(id)"
+        );
+
+        let ts = || Expr::Column(String::from("ts"));
+        assert_eq!(
+            Expr::Function(Function::DateTrunc(DateTimeField::Day), vec![ts()]).to_string(),
+            "DATE_TRUNC('day', ts)"
+        );
+        assert_eq!(
+            Expr::Binary(
+                Box::new(Expr::Function(
+                    Function::Extract(DateTimeField::DayOfWeek),
+                    vec![ts()]
+                )),
+                BinaryOperator::Eq,
+                Box::new(Expr::Function(Function::Now, vec![])),
+            )
+            .to_string(),
+            "EXTRACT(DOW FROM ts) = NOW()"
+        );
+        assert_eq!(
+            Value::Interval(String::from("1 day")).to_string(),
+            "INTERVAL '1 day'"
+        );
+        assert_eq!(Value::Blob(vec![0xde, 0xad, 0x0f]).to_string(), "X'DEAD0F'");
+        assert_eq!(
+            Expr::Binary(
+                Box::new(Expr::Binary(
+                    Box::new(Expr::Column(String::from("doc"))),
+                    BinaryOperator::JsonGet,
+                    Box::new(Expr::Value(Value::VarChar(String::from("tags")))),
+                )),
+                BinaryOperator::JsonGetText,
+                Box::new(Expr::Value(Value::Int(0))),
+            )
+            .to_string(),
+            "(doc -> 'tags') ->> 0"
+        );
+    }
 }
@@ -4,33 +4,114 @@ pub enum Token {
     Select,
     From,
     Where,
+    And,
+    Or,
+    Not,
+    Order,
+    By,
+    Asc,
+    Desc,
+    Limit,
+    Offset,
+    Group,
+    Having,
+    As,
+    Join,
+    Inner,
+    Left,
+    Right,
+    Full,
+    Outer,
+    Cross,
+    On,
     Update,
     Set,
+    Insert,
+    Into,
+    Values,
+    Delete,
     Exit,
     Create,
     Table,
+    Drop,
+    Truncate,
+    Alter,
+    Add,
+    Column,
+    Rename,
+    To,
+    If,
+    Exists,
+    Is,
+    Null,
+    Primary,
+    Key,
+    Unique,
+    Default,
+    Check,
+    Foreign,
+    References,
+    Cascade,
+    Restrict,
+    No,
+    Action,
+    Boolean,
     Int,
+    BigInt,
+    Double,
+    Decimal,
     VarChar,
+    Date,
+    Time,
+    Timestamp,
+    Interval,
+    Text,
+    Blob,
+    Json,
+    True,
+    False,
 
     // values
-    Integer(i32),
+    // digits without a sign, which the parser folds in
+    Integer(u64),
+    // digits with a fractional part, as written
+    Number(String),
     Ident(String),
     String(String),
+    // X'...', decoded
+    Bytes(Vec<u8>),
 
     // symbols
     Equal,
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
     Asterisk,
+    Plus,
+    Minus,
+    // ->
+    Arrow,
+    // ->>
+    DoubleArrow,
+    Slash,
+    Percent,
     Comma,
+    Dot,
     SemiColon,
     LParen,
     RParen,
 
     Illegal,
+    // a literal that is well formed but cannot be read, and why
+    Invalid(String),
     Eof,
 }
 
 pub struct Lexer {
-    input: String,
+    // indexed by position, a literal can be large
+    input: Vec<char>,
     position: usize,
     read_position: usize,
     ch: char,
@@ -39,7 +120,7 @@ pub struct Lexer {
 impl Lexer {
     pub fn new(input: String) -> Self {
         let mut lexer = Self {
-            input,
+            input: input.chars().collect(),
             position: 0,
             read_position: 0,
             ch: '\0',
@@ -48,19 +129,63 @@ impl Lexer {
         lexer
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Token {
         self.skip_whitespace();
         let token = match self.ch {
             '\u{0}' => Token::Eof,
             '=' => Token::Equal,
+            '<' => match self.peek_char() {
+                '=' => {
+                    self.read_char();
+                    Token::LessThanOrEqual
+                }
+                '>' => {
+                    self.read_char();
+                    Token::NotEqual
+                }
+                _ => Token::LessThan,
+            },
+            '>' => match self.peek_char() {
+                '=' => {
+                    self.read_char();
+                    Token::GreaterThanOrEqual
+                }
+                _ => Token::GreaterThan,
+            },
+            '!' => match self.peek_char() {
+                '=' => {
+                    self.read_char();
+                    Token::NotEqual
+                }
+                _ => Token::Illegal,
+            },
             '*' => Token::Asterisk,
+            '+' => Token::Plus,
+            '-' => match self.peek_char() {
+                '>' => {
+                    self.read_char();
+                    if self.peek_char() == '>' {
+                        self.read_char();
+                        Token::DoubleArrow
+                    } else {
+                        Token::Arrow
+                    }
+                }
+                _ => Token::Minus,
+            },
+            '/' => Token::Slash,
+            '%' => Token::Percent,
             ',' => Token::Comma,
+            '.' => Token::Dot,
             ';' => Token::SemiColon,
             '(' => Token::LParen,
             ')' => Token::RParen,
             '0'..='9' => self.read_number(),
             '\'' => self.read_string(),
-            _ => Self::word_to_token(&self.read_word()),
+            'X' | 'x' if self.peek_char() == '\'' => self.read_hex_string(),
+            c if c.is_alphabetic() || c == '_' => Self::word_to_token(&self.read_word()),
+            _ => Token::Illegal,
         };
         self.read_char();
         token
@@ -71,12 +196,71 @@ impl Lexer {
             "SELECT" | "select" => Token::Select,
             "FROM" | "from" => Token::From,
             "WHERE" | "where" => Token::Where,
+            "AND" | "and" => Token::And,
+            "OR" | "or" => Token::Or,
+            "NOT" | "not" => Token::Not,
+            "ORDER" | "order" => Token::Order,
+            "BY" | "by" => Token::By,
+            "ASC" | "asc" => Token::Asc,
+            "DESC" | "desc" => Token::Desc,
+            "LIMIT" | "limit" => Token::Limit,
+            "OFFSET" | "offset" => Token::Offset,
+            "GROUP" | "group" => Token::Group,
+            "HAVING" | "having" => Token::Having,
+            "AS" | "as" => Token::As,
+            "JOIN" | "join" => Token::Join,
+            "INNER" | "inner" => Token::Inner,
+            "LEFT" | "left" => Token::Left,
+            "RIGHT" | "right" => Token::Right,
+            "FULL" | "full" => Token::Full,
+            "OUTER" | "outer" => Token::Outer,
+            "CROSS" | "cross" => Token::Cross,
+            "ON" | "on" => Token::On,
             "UPDATE" | "update" => Token::Update,
             "SET" | "set" => Token::Set,
+            "INSERT" | "insert" => Token::Insert,
+            "INTO" | "into" => Token::Into,
+            "VALUES" | "values" => Token::Values,
+            "DELETE" | "delete" => Token::Delete,
             "CREATE" | "create" => Token::Create,
             "TABLE" | "table" => Token::Table,
+            "DROP" | "drop" => Token::Drop,
+            "TRUNCATE" | "truncate" => Token::Truncate,
+            "ALTER" | "alter" => Token::Alter,
+            "ADD" | "add" => Token::Add,
+            "COLUMN" | "column" => Token::Column,
+            "RENAME" | "rename" => Token::Rename,
+            "TO" | "to" => Token::To,
+            "IF" | "if" => Token::If,
+            "EXISTS" | "exists" => Token::Exists,
+            "IS" | "is" => Token::Is,
+            "NULL" | "null" => Token::Null,
+            "PRIMARY" | "primary" => Token::Primary,
+            "KEY" | "key" => Token::Key,
+            "UNIQUE" | "unique" => Token::Unique,
+            "DEFAULT" | "default" => Token::Default,
+            "CHECK" | "check" => Token::Check,
+            "FOREIGN" | "foreign" => Token::Foreign,
+            "REFERENCES" | "references" => Token::References,
+            "CASCADE" | "cascade" => Token::Cascade,
+            "RESTRICT" | "restrict" => Token::Restrict,
+            "NO" | "no" => Token::No,
+            "ACTION" | "action" => Token::Action,
+            "BOOLEAN" | "boolean" => Token::Boolean,
             "INT" | "int" => Token::Int,
+            "BIGINT" | "bigint" => Token::BigInt,
+            "DOUBLE" | "double" => Token::Double,
+            "DECIMAL" | "decimal" | "NUMERIC" | "numeric" => Token::Decimal,
             "VARCHAR" | "varchar" => Token::VarChar,
+            "DATE" | "date" => Token::Date,
+            "TIME" | "time" => Token::Time,
+            "TIMESTAMP" | "timestamp" => Token::Timestamp,
+            "INTERVAL" | "interval" => Token::Interval,
+            "TEXT" | "text" => Token::Text,
+            "BLOB" | "blob" | "BYTEA" | "bytea" => Token::Blob,
+            "JSON" | "json" => Token::Json,
+            "TRUE" | "true" => Token::True,
+            "FALSE" | "false" => Token::False,
             "exit" => Token::Exit,
             _ => Token::Ident(word.to_string()),
         }
@@ -88,7 +272,7 @@ impl Lexer {
             self.read_char();
         }
         self.read_position -= 1;
-        self.input[position..self.position].to_string()
+        self.input[position..self.position].iter().collect()
     }
 
     fn read_number(&mut self) -> Token {
@@ -96,8 +280,23 @@ impl Lexer {
         while self.ch.is_ascii_digit() {
             self.read_char();
         }
+        if self.ch == '.' && self.peek_char().is_ascii_digit() {
+            self.read_char(); // skip .
+            while self.ch.is_ascii_digit() {
+                self.read_char();
+            }
+            self.read_position -= 1;
+            return Token::Number(self.input[position..self.position].iter().collect());
+        }
         self.read_position -= 1;
-        Token::Integer(self.input[position..self.position].parse().unwrap())
+        // the range of the target type is checked on write
+        let digits = self.input[position..self.position]
+            .iter()
+            .collect::<String>();
+        match digits.parse() {
+            Ok(value) => Token::Integer(value),
+            Err(_) => Token::Invalid(format!("integer out of range: {}", digits)),
+        }
     }
 
     fn read_string(&mut self) -> Token {
@@ -107,20 +306,50 @@ impl Lexer {
             if self.ch == '\'' {
                 break;
             }
+            // unterminated string
+            if self.read_position > self.input.len() {
+                return Token::Illegal;
+            }
+        }
+        Token::String(self.input[position..self.position].iter().collect())
+    }
+
+    /// an even number of hex digits in quotes
+    fn read_hex_string(&mut self) -> Token {
+        self.read_char(); // skip X
+        let digits = match self.read_string() {
+            Token::String(digits) => digits,
+            token => return token,
+        };
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Token::Illegal;
+        }
+        if !digits.len().is_multiple_of(2) {
+            return Token::Invalid(String::from(
+                "hex literal must have an even number of digits",
+            ));
         }
-        Token::String(self.input[position..self.position].to_string())
+        let bytes = (0..digits.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap())
+            .collect();
+        Token::Bytes(bytes)
     }
 
     fn read_char(&mut self) {
         if self.read_position >= self.input.len() {
             self.ch = '\0';
         } else {
-            self.ch = self.input.chars().nth(self.read_position).unwrap();
+            self.ch = self.input[self.read_position];
         }
         self.position = self.read_position;
         self.read_position += 1;
     }
 
+    fn peek_char(&self) -> char {
+        self.input.get(self.read_position).copied().unwrap_or('\0')
+    }
+
     fn skip_whitespace(&mut self) {
         while self.ch.is_whitespace() {
             self.read_char();
@@ -141,7 +370,25 @@ mod test {
                 SELECT name FROM user;
                 SELECT * FROM user;
                 exit;
-                CREATE TABLE user (id INT, name VARCHAR);"#,
+                CREATE TABLE user (id INT, name VARCHAR);
+                INSERT INTO user (id, name) VALUES (1, 'mike');
+                DELETE FROM user;
+                DROP TABLE IF EXISTS user;
+                TRUNCATE TABLE user;
+                ALTER TABLE user RENAME COLUMN name TO nickname;
+                SELECT * FROM user WHERE NOT (id = 1 OR id = 2) AND name = 'mike';
+                < <= > >= <> != ! ?
+                SELECT id FROM user ORDER BY id DESC, name ASC LIMIT 10 OFFSET 5;
+                SELECT COUNT(*) AS n FROM todo GROUP BY user_id HAVING n > 1;
+                SELECT user.name FROM user LEFT OUTER JOIN todo ON user.id = todo.user_id;
+                SELECT * FROM user WHERE name IS NOT NULL;
+                PRIMARY KEY UNIQUE DEFAULT CHECK
+                FOREIGN REFERENCES CASCADE RESTRICT NO ACTION
+                BOOLEAN BIGINT DOUBLE DECIMAL NUMERIC TRUE false
+                1.5 -2 + - * / % 3. user.id
+                DATE '2026-10-18' time TIMESTAMP INTERVAL DATE_TRUNC
+                TEXT blob BYTEA X'DEAD' x'beef' X'' x
+                doc JSON -> 'a' ->> 0 - >"#,
         );
         let mut lexer = Lexer::new(input);
 
@@ -184,6 +431,227 @@ mod test {
         assert_eq!(lexer.next(), Token::RParen);
         assert_eq!(lexer.next(), Token::SemiColon);
 
+        assert_eq!(lexer.next(), Token::Insert);
+        assert_eq!(lexer.next(), Token::Into);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user")));
+        assert_eq!(lexer.next(), Token::LParen);
+        assert_eq!(lexer.next(), Token::Ident(String::from("id")));
+        assert_eq!(lexer.next(), Token::Comma);
+        assert_eq!(lexer.next(), Token::Ident(String::from("name")));
+        assert_eq!(lexer.next(), Token::RParen);
+        assert_eq!(lexer.next(), Token::Values);
+        assert_eq!(lexer.next(), Token::LParen);
+        assert_eq!(lexer.next(), Token::Integer(1));
+        assert_eq!(lexer.next(), Token::Comma);
+        assert_eq!(lexer.next(), Token::String(String::from("mike")));
+        assert_eq!(lexer.next(), Token::RParen);
+        assert_eq!(lexer.next(), Token::SemiColon);
+
+        assert_eq!(lexer.next(), Token::Delete);
+        assert_eq!(lexer.next(), Token::From);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user")));
+        assert_eq!(lexer.next(), Token::SemiColon);
+
+        assert_eq!(lexer.next(), Token::Drop);
+        assert_eq!(lexer.next(), Token::Table);
+        assert_eq!(lexer.next(), Token::If);
+        assert_eq!(lexer.next(), Token::Exists);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user")));
+        assert_eq!(lexer.next(), Token::SemiColon);
+
+        assert_eq!(lexer.next(), Token::Truncate);
+        assert_eq!(lexer.next(), Token::Table);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user")));
+        assert_eq!(lexer.next(), Token::SemiColon);
+
+        assert_eq!(lexer.next(), Token::Alter);
+        assert_eq!(lexer.next(), Token::Table);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user")));
+        assert_eq!(lexer.next(), Token::Rename);
+        assert_eq!(lexer.next(), Token::Column);
+        assert_eq!(lexer.next(), Token::Ident(String::from("name")));
+        assert_eq!(lexer.next(), Token::To);
+        assert_eq!(lexer.next(), Token::Ident(String::from("nickname")));
+        assert_eq!(lexer.next(), Token::SemiColon);
+
+        assert_eq!(lexer.next(), Token::Select);
+        assert_eq!(lexer.next(), Token::Asterisk);
+        assert_eq!(lexer.next(), Token::From);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user")));
+        assert_eq!(lexer.next(), Token::Where);
+        assert_eq!(lexer.next(), Token::Not);
+        assert_eq!(lexer.next(), Token::LParen);
+        assert_eq!(lexer.next(), Token::Ident(String::from("id")));
+        assert_eq!(lexer.next(), Token::Equal);
+        assert_eq!(lexer.next(), Token::Integer(1));
+        assert_eq!(lexer.next(), Token::Or);
+        assert_eq!(lexer.next(), Token::Ident(String::from("id")));
+        assert_eq!(lexer.next(), Token::Equal);
+        assert_eq!(lexer.next(), Token::Integer(2));
+        assert_eq!(lexer.next(), Token::RParen);
+        assert_eq!(lexer.next(), Token::And);
+        assert_eq!(lexer.next(), Token::Ident(String::from("name")));
+        assert_eq!(lexer.next(), Token::Equal);
+        assert_eq!(lexer.next(), Token::String(String::from("mike")));
+        assert_eq!(lexer.next(), Token::SemiColon);
+
+        assert_eq!(lexer.next(), Token::LessThan);
+        assert_eq!(lexer.next(), Token::LessThanOrEqual);
+        assert_eq!(lexer.next(), Token::GreaterThan);
+        assert_eq!(lexer.next(), Token::GreaterThanOrEqual);
+        assert_eq!(lexer.next(), Token::NotEqual);
+        assert_eq!(lexer.next(), Token::NotEqual);
+        assert_eq!(lexer.next(), Token::Illegal);
+        assert_eq!(lexer.next(), Token::Illegal);
+
+        assert_eq!(lexer.next(), Token::Select);
+        assert_eq!(lexer.next(), Token::Ident(String::from("id")));
+        assert_eq!(lexer.next(), Token::From);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user")));
+        assert_eq!(lexer.next(), Token::Order);
+        assert_eq!(lexer.next(), Token::By);
+        assert_eq!(lexer.next(), Token::Ident(String::from("id")));
+        assert_eq!(lexer.next(), Token::Desc);
+        assert_eq!(lexer.next(), Token::Comma);
+        assert_eq!(lexer.next(), Token::Ident(String::from("name")));
+        assert_eq!(lexer.next(), Token::Asc);
+        assert_eq!(lexer.next(), Token::Limit);
+        assert_eq!(lexer.next(), Token::Integer(10));
+        assert_eq!(lexer.next(), Token::Offset);
+        assert_eq!(lexer.next(), Token::Integer(5));
+        assert_eq!(lexer.next(), Token::SemiColon);
+
+        assert_eq!(lexer.next(), Token::Select);
+        assert_eq!(lexer.next(), Token::Ident(String::from("COUNT")));
+        assert_eq!(lexer.next(), Token::LParen);
+        assert_eq!(lexer.next(), Token::Asterisk);
+        assert_eq!(lexer.next(), Token::RParen);
+        assert_eq!(lexer.next(), Token::As);
+        assert_eq!(lexer.next(), Token::Ident(String::from("n")));
+        assert_eq!(lexer.next(), Token::From);
+        assert_eq!(lexer.next(), Token::Ident(String::from("todo")));
+        assert_eq!(lexer.next(), Token::Group);
+        assert_eq!(lexer.next(), Token::By);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user_id")));
+        assert_eq!(lexer.next(), Token::Having);
+        assert_eq!(lexer.next(), Token::Ident(String::from("n")));
+        assert_eq!(lexer.next(), Token::GreaterThan);
+        assert_eq!(lexer.next(), Token::Integer(1));
+        assert_eq!(lexer.next(), Token::SemiColon);
+
+        assert_eq!(lexer.next(), Token::Select);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user")));
+        assert_eq!(lexer.next(), Token::Dot);
+        assert_eq!(lexer.next(), Token::Ident(String::from("name")));
+        assert_eq!(lexer.next(), Token::From);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user")));
+        assert_eq!(lexer.next(), Token::Left);
+        assert_eq!(lexer.next(), Token::Outer);
+        assert_eq!(lexer.next(), Token::Join);
+        assert_eq!(lexer.next(), Token::Ident(String::from("todo")));
+        assert_eq!(lexer.next(), Token::On);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user")));
+        assert_eq!(lexer.next(), Token::Dot);
+        assert_eq!(lexer.next(), Token::Ident(String::from("id")));
+        assert_eq!(lexer.next(), Token::Equal);
+        assert_eq!(lexer.next(), Token::Ident(String::from("todo")));
+        assert_eq!(lexer.next(), Token::Dot);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user_id")));
+        assert_eq!(lexer.next(), Token::SemiColon);
+
+        assert_eq!(lexer.next(), Token::Select);
+        assert_eq!(lexer.next(), Token::Asterisk);
+        assert_eq!(lexer.next(), Token::From);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user")));
+        assert_eq!(lexer.next(), Token::Where);
+        assert_eq!(lexer.next(), Token::Ident(String::from("name")));
+        assert_eq!(lexer.next(), Token::Is);
+        assert_eq!(lexer.next(), Token::Not);
+        assert_eq!(lexer.next(), Token::Null);
+        assert_eq!(lexer.next(), Token::SemiColon);
+
+        assert_eq!(lexer.next(), Token::Primary);
+        assert_eq!(lexer.next(), Token::Key);
+        assert_eq!(lexer.next(), Token::Unique);
+        assert_eq!(lexer.next(), Token::Default);
+        assert_eq!(lexer.next(), Token::Check);
+
+        assert_eq!(lexer.next(), Token::Foreign);
+        assert_eq!(lexer.next(), Token::References);
+        assert_eq!(lexer.next(), Token::Cascade);
+        assert_eq!(lexer.next(), Token::Restrict);
+        assert_eq!(lexer.next(), Token::No);
+        assert_eq!(lexer.next(), Token::Action);
+
+        assert_eq!(lexer.next(), Token::Boolean);
+        assert_eq!(lexer.next(), Token::BigInt);
+        assert_eq!(lexer.next(), Token::Double);
+        assert_eq!(lexer.next(), Token::Decimal);
+        assert_eq!(lexer.next(), Token::Decimal);
+        assert_eq!(lexer.next(), Token::True);
+        assert_eq!(lexer.next(), Token::False);
+
+        assert_eq!(lexer.next(), Token::Number(String::from("1.5")));
+        assert_eq!(lexer.next(), Token::Minus);
+        assert_eq!(lexer.next(), Token::Integer(2));
+        assert_eq!(lexer.next(), Token::Plus);
+        assert_eq!(lexer.next(), Token::Minus);
+        assert_eq!(lexer.next(), Token::Asterisk);
+        assert_eq!(lexer.next(), Token::Slash);
+        assert_eq!(lexer.next(), Token::Percent);
+        assert_eq!(lexer.next(), Token::Integer(3));
+        assert_eq!(lexer.next(), Token::Dot);
+        assert_eq!(lexer.next(), Token::Ident(String::from("user")));
+        assert_eq!(lexer.next(), Token::Dot);
+        assert_eq!(lexer.next(), Token::Ident(String::from("id")));
+
+        assert_eq!(lexer.next(), Token::Date);
+        assert_eq!(lexer.next(), Token::String(String::from("2026-10-18")));
+        assert_eq!(lexer.next(), Token::Time);
+        assert_eq!(lexer.next(), Token::Timestamp);
+        assert_eq!(lexer.next(), Token::Interval);
+        assert_eq!(lexer.next(), Token::Ident(String::from("DATE_TRUNC")));
+
+        assert_eq!(lexer.next(), Token::Text);
+        assert_eq!(lexer.next(), Token::Blob);
+        assert_eq!(lexer.next(), Token::Blob);
+        assert_eq!(lexer.next(), Token::Bytes(vec![0xde, 0xad]));
+        assert_eq!(lexer.next(), Token::Bytes(vec![0xbe, 0xef]));
+        assert_eq!(lexer.next(), Token::Bytes(vec![]));
+        assert_eq!(lexer.next(), Token::Ident(String::from("x")));
+
+        assert_eq!(lexer.next(), Token::Ident(String::from("doc")));
+        assert_eq!(lexer.next(), Token::Json);
+        assert_eq!(lexer.next(), Token::Arrow);
+        assert_eq!(lexer.next(), Token::String(String::from("a")));
+        assert_eq!(lexer.next(), Token::DoubleArrow);
+        assert_eq!(lexer.next(), Token::Integer(0));
+        assert_eq!(lexer.next(), Token::Minus);
+        assert_eq!(lexer.next(), Token::GreaterThan);
+
+        assert_eq!(lexer.next(), Token::Eof);
+    }
+
+    #[test]
+    fn test_lexer_illegal() {
+        use super::{Lexer, Token};
+
+        let mut lexer = Lexer::new(String::from(
+            "18446744073709551615 18446744073709551616 X'ABC' x'GG' 'unterminated",
+        ));
+        assert_eq!(lexer.next(), Token::Integer(u64::MAX));
+        assert_eq!(
+            lexer.next(),
+            Token::Invalid(String::from("integer out of range: 18446744073709551616"))
+        );
+        assert_eq!(
+            lexer.next(),
+            Token::Invalid(String::from(
+                "hex literal must have an even number of digits"
+            ))
+        );
+        assert_eq!(lexer.next(), Token::Illegal);
+        assert_eq!(lexer.next(), Token::Illegal);
         assert_eq!(lexer.next(), Token::Eof);
     }
 }
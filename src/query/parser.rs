@@ -1,19 +1,28 @@
 use std::fmt::Display;
 
 use super::{
-    ast::{QueryStatement, Value},
+    ast::{
+        AggregateFunction, AlterTableAction, BinaryOperator, Constraint, DateTimeField, Expr,
+        ForeignKey, Function, JoinType, Order, QueryStatement, ReferentialAction, SelectItem,
+        SelectStatement, TableRef, Value,
+    },
     lex::{Lexer, Token},
 };
 
 #[derive(Debug, PartialEq)]
 pub enum ParseError {
     UnexpectedToken(Token),
+    UnknownFunction(String),
+    // a literal that cannot be read, and why
+    InvalidLiteral(String),
 }
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ParseError::UnexpectedToken(token) => write!(f, "unexpected token: {:?}", token),
+            ParseError::UnknownFunction(name) => write!(f, "unknown function: {}", name),
+            ParseError::InvalidLiteral(message) => write!(f, "{}", message),
         }
     }
 }
@@ -41,16 +50,35 @@ impl Parser {
         while self.current_token != Token::Eof {
             let stmt = self.parse_statement()?;
             statements.push(stmt);
-            self.next_token();
+            // a statement ends with ; or the input, never with what it could not parse
+            match self.current_token {
+                Token::SemiColon => self.next_token(),
+                Token::Eof => {}
+                _ => return Err(ParseError::UnexpectedToken(self.current_token.clone())),
+            }
         }
         Ok(statements)
     }
 
+    /// parse input consisting of a single expression, e.g. a CHECK constraint kept as text
+    pub fn parse_standalone_expr(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.parse_expr()?;
+        if self.current_token != Token::Eof {
+            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+        }
+        Ok(expr)
+    }
+
     fn parse_statement(&mut self) -> Result<QueryStatement, ParseError> {
         match self.current_token {
             Token::Select => Ok(self.parse_select_statement()?),
             Token::Update => Ok(self.parse_update_statement()?),
+            Token::Insert => Ok(self.parse_insert_statement()?),
+            Token::Delete => Ok(self.parse_delete_statement()?),
             Token::Create => Ok(self.parse_create_table_statement()?),
+            Token::Alter => Ok(self.parse_alter_table_statement()?),
+            Token::Drop => Ok(self.parse_drop_table_statement()?),
+            Token::Truncate => Ok(self.parse_truncate_statement()?),
             Token::Exit => Ok(self.parse_exit_statement()?),
             _ => Err(ParseError::UnexpectedToken(self.current_token.clone())),
         }
@@ -58,67 +86,180 @@ impl Parser {
 
     fn parse_select_statement(&mut self) -> Result<QueryStatement, ParseError> {
         self.next_token(); // skip select
-        let (table_name, is_all, columns, cond) = self.parse_select_arg()?;
-        Ok(QueryStatement::Select(table_name, is_all, columns, cond))
+        let select = self.parse_select_arg()?;
+        Ok(QueryStatement::Select(select))
     }
 
-    fn parse_select_arg(
-        &mut self,
-    ) -> Result<(String, bool, Vec<String>, Option<(String, Value)>), ParseError> {
-        let mut is_all = false;
-        let mut columns = Vec::new();
+    fn parse_select_arg(&mut self) -> Result<SelectStatement, ParseError> {
+        let mut projections = vec![self.parse_select_item()?];
+        while self.current_token == Token::Comma {
+            self.next_token(); // skip ,
+            projections.push(self.parse_select_item()?);
+        }
 
-        if self.current_token == Token::Asterisk {
-            is_all = true;
-            self.next_token(); // skip *
-        } else {
-            match &self.current_token {
-                Token::Ident(name) => {
-                    columns.push(name.clone());
-                    self.next_token() // skip name
+        if self.current_token != Token::From {
+            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+        }
+        self.next_token(); // skip from
+
+        let from = self.parse_table_ref()?;
+
+        // where
+        let mut cond = None;
+        if self.current_token == Token::Where {
+            self.next_token(); // skip where
+            cond = Some(self.parse_expr()?);
+        }
+
+        // group by
+        let mut group_by = Vec::new();
+        if self.current_token == Token::Group {
+            self.next_token(); // skip group
+            if self.current_token != Token::By {
+                return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+            }
+            self.next_token(); // skip by
+            group_by.push(self.parse_expr()?);
+            while self.current_token == Token::Comma {
+                self.next_token(); // skip ,
+                group_by.push(self.parse_expr()?);
+            }
+        }
+
+        // having
+        let mut having = None;
+        if self.current_token == Token::Having {
+            self.next_token(); // skip having
+            having = Some(self.parse_expr()?);
+        }
+
+        // order by
+        let mut order_by = Vec::new();
+        if self.current_token == Token::Order {
+            self.next_token(); // skip order
+            if self.current_token != Token::By {
+                return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+            }
+            self.next_token(); // skip by
+            loop {
+                let key = self.parse_expr()?;
+                let order = match self.current_token {
+                    Token::Asc => {
+                        self.next_token(); // skip asc
+                        Order::Asc
+                    }
+                    Token::Desc => {
+                        self.next_token(); // skip desc
+                        Order::Desc
+                    }
+                    _ => Order::Asc,
+                };
+                order_by.push((key, order));
+                if self.current_token == Token::Comma {
+                    self.next_token(); // skip ,
+                } else {
+                    break;
                 }
-                _ => return Err(ParseError::UnexpectedToken(self.current_token.clone())),
             }
         }
 
-        while self.current_token == Token::Comma {
-            self.next_token(); // skip ,
-            if self.current_token == Token::Asterisk {
-                is_all = true;
-                self.next_token(); // skip *
+        // limit, offset
+        let mut limit = None;
+        if self.current_token == Token::Limit {
+            self.next_token(); // skip limit
+            limit = Some(self.parse_int()?);
+        }
+        let mut offset = None;
+        if self.current_token == Token::Offset {
+            self.next_token(); // skip offset
+            offset = Some(self.parse_int()?);
+        }
+
+        Ok(SelectStatement {
+            from,
+            projections,
+            cond,
+            group_by,
+            having,
+            order_by,
+            limit,
+            offset,
+        })
+    }
+
+    /// table [[AS] alias] { [INNER | LEFT [OUTER] | RIGHT [OUTER] | FULL [OUTER]] JOIN table ON expr | CROSS JOIN table | , table }
+    fn parse_table_ref(&mut self) -> Result<TableRef, ParseError> {
+        let mut left = self.parse_table_primary()?;
+        loop {
+            let join_type = match self.current_token {
+                Token::Comma => JoinType::Cross,
+                Token::Join | Token::Inner => JoinType::Inner,
+                Token::Left => JoinType::Left,
+                Token::Right => JoinType::Right,
+                Token::Full => JoinType::Full,
+                Token::Cross => JoinType::Cross,
+                _ => return Ok(left),
+            };
+            if self.current_token == Token::Comma {
+                self.next_token(); // skip ,
             } else {
-                match &self.current_token {
-                    Token::Ident(name) => {
-                        columns.push(name.clone());
-                        self.next_token() // skip name
+                if self.current_token != Token::Join {
+                    self.next_token(); // skip inner, left, right, full, cross
+                    if self.current_token == Token::Outer
+                        && matches!(join_type, JoinType::Left | JoinType::Right | JoinType::Full)
+                    {
+                        self.next_token(); // skip outer
                     }
-                    _ => return Err(ParseError::UnexpectedToken(self.current_token.clone())),
                 }
+                if self.current_token != Token::Join {
+                    return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                }
+                self.next_token(); // skip join
             }
-        }
 
-        if self.current_token != Token::From {
-            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+            let right = self.parse_table_primary()?;
+
+            let mut on = None;
+            if join_type != JoinType::Cross {
+                if self.current_token != Token::On {
+                    return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                }
+                self.next_token(); // skip on
+                on = Some(self.parse_expr()?);
+            }
+
+            left = TableRef::Join(Box::new(left), join_type, Box::new(right), on);
         }
-        self.next_token(); // skip from
+    }
 
+    fn parse_table_primary(&mut self) -> Result<TableRef, ParseError> {
         let table_name = self.parse_ident()?;
-
-        // where
-        if self.current_token != Token::Where {
-            return Ok((table_name, is_all, columns, None));
+        let mut alias = None;
+        if self.current_token == Token::As {
+            self.next_token(); // skip as
+            alias = Some(self.parse_ident()?);
+        } else if Self::is_ident(&self.current_token)
+            && !matches!(self.current_token, Token::Left | Token::Right | Token::Full)
+        {
+            // LEFT, RIGHT and FULL start a join here
+            alias = Some(self.parse_ident()?);
         }
-        self.next_token(); // skip where
-                           // cond
-        let key = self.parse_ident()?;
-        if self.current_token != Token::Equal {
-            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+        Ok(TableRef::Table(table_name, alias))
+    }
+
+    fn parse_select_item(&mut self) -> Result<SelectItem, ParseError> {
+        if self.current_token == Token::Asterisk {
+            self.next_token(); // skip *
+            return Ok(SelectItem::Wildcard);
         }
-        self.next_token(); // skip =
-        let value = self.parse_value()?;
-        let cond = (key, value);
 
-        Ok((table_name, is_all, columns, Some(cond)))
+        let expr = self.parse_expr()?;
+        let mut alias = None;
+        if self.current_token == Token::As {
+            self.next_token(); // skip as
+            alias = Some(self.parse_ident()?);
+        }
+        Ok(SelectItem::Expr(expr, alias))
     }
 
     fn parse_update_statement(&mut self) -> Result<QueryStatement, ParseError> {
@@ -153,34 +294,401 @@ impl Parser {
 
         // where
         if self.current_token != Token::Where {
+            return Ok(QueryStatement::Update(table_name, assignments, None));
+        }
+        self.next_token(); // skip where
+        let cond = self.parse_expr()?;
+
+        Ok(QueryStatement::Update(table_name, assignments, Some(cond)))
+    }
+
+    fn parse_delete_statement(&mut self) -> Result<QueryStatement, ParseError> {
+        self.next_token(); // skip delete
+        if self.current_token != Token::From {
             return Err(ParseError::UnexpectedToken(self.current_token.clone()));
         }
+        self.next_token(); // skip from
+
+        let table_name = self.parse_ident()?;
+
+        // where
+        if self.current_token != Token::Where {
+            return Ok(QueryStatement::Delete(table_name, None));
+        }
         self.next_token(); // skip where
-                           // cond
-        let key = self.parse_ident()?;
-        if self.current_token != Token::Equal {
+        let cond = self.parse_expr()?;
+
+        Ok(QueryStatement::Delete(table_name, Some(cond)))
+    }
+
+    /// precedence (lowest to highest): OR, AND, NOT, comparison, + -, * / %, -> ->>
+    fn parse_expr(&mut self) -> Result<Expr, ParseError> {
+        self.parse_or_expr()
+    }
+
+    fn parse_or_expr(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_and_expr()?;
+        while self.current_token == Token::Or {
+            self.next_token(); // skip or
+            let right = self.parse_and_expr()?;
+            left = Expr::Binary(Box::new(left), BinaryOperator::Or, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and_expr(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_not_expr()?;
+        while self.current_token == Token::And {
+            self.next_token(); // skip and
+            let right = self.parse_not_expr()?;
+            left = Expr::Binary(Box::new(left), BinaryOperator::And, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not_expr(&mut self) -> Result<Expr, ParseError> {
+        if self.current_token == Token::Not {
+            self.next_token(); // skip not
+            let expr = self.parse_not_expr()?;
+            return Ok(Expr::Not(Box::new(expr)));
+        }
+        self.parse_comparison_expr()
+    }
+
+    fn parse_comparison_expr(&mut self) -> Result<Expr, ParseError> {
+        let left = self.parse_additive_expr()?;
+        if self.current_token == Token::Is {
+            return self.parse_is_null(left);
+        }
+        let operator = match self.current_token {
+            Token::Equal => BinaryOperator::Eq,
+            Token::NotEqual => BinaryOperator::NotEq,
+            Token::LessThan => BinaryOperator::Lt,
+            Token::LessThanOrEqual => BinaryOperator::LtEq,
+            Token::GreaterThan => BinaryOperator::Gt,
+            Token::GreaterThanOrEqual => BinaryOperator::GtEq,
+            _ => return Ok(left),
+        };
+        self.next_token(); // skip operator
+        let right = self.parse_additive_expr()?;
+        Ok(Expr::Binary(Box::new(left), operator, Box::new(right)))
+    }
+
+    fn parse_additive_expr(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_multiplicative_expr()?;
+        loop {
+            let operator = match self.current_token {
+                Token::Plus => BinaryOperator::Add,
+                Token::Minus => BinaryOperator::Sub,
+                _ => return Ok(left),
+            };
+            self.next_token(); // skip operator
+            let right = self.parse_multiplicative_expr()?;
+            left = Expr::Binary(Box::new(left), operator, Box::new(right));
+        }
+    }
+
+    fn parse_multiplicative_expr(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_unary_expr()?;
+        loop {
+            let operator = match self.current_token {
+                Token::Asterisk => BinaryOperator::Mul,
+                Token::Slash => BinaryOperator::Div,
+                Token::Percent => BinaryOperator::Mod,
+                _ => return Ok(left),
+            };
+            self.next_token(); // skip operator
+            let right = self.parse_unary_expr()?;
+            left = Expr::Binary(Box::new(left), operator, Box::new(right));
+        }
+    }
+
+    /// prefix minus, except on a number, which is a negative literal
+    fn parse_unary_expr(&mut self) -> Result<Expr, ParseError> {
+        if self.current_token == Token::Minus
+            && !matches!(self.peek_token, Token::Integer(_) | Token::Number(_))
+        {
+            self.next_token(); // skip -
+            return Ok(Expr::Neg(Box::new(self.parse_unary_expr()?)));
+        }
+        self.parse_json_access_expr()
+    }
+
+    /// `->` and `->>` bind tightest, so `doc -> 'a' ->> 'b' = 'x'` needs no parentheses
+    fn parse_json_access_expr(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_primary_expr()?;
+        loop {
+            let operator = match self.current_token {
+                Token::Arrow => BinaryOperator::JsonGet,
+                Token::DoubleArrow => BinaryOperator::JsonGetText,
+                _ => return Ok(left),
+            };
+            self.next_token(); // skip operator
+            let right = self.parse_primary_expr()?;
+            left = Expr::Binary(Box::new(left), operator, Box::new(right));
+        }
+    }
+
+    fn parse_is_null(&mut self, expr: Expr) -> Result<Expr, ParseError> {
+        self.next_token(); // skip is
+        let negated = self.current_token == Token::Not;
+        if negated {
+            self.next_token(); // skip not
+        }
+        if self.current_token != Token::Null {
             return Err(ParseError::UnexpectedToken(self.current_token.clone()));
         }
-        self.next_token(); // skip =
-        let value = self.parse_value()?;
+        self.next_token(); // skip null
+        Ok(Expr::IsNull(Box::new(expr), negated))
+    }
 
-        Ok(QueryStatement::Update(
-            table_name,
-            assignments,
-            (key, value),
-        ))
+    fn parse_primary_expr(&mut self) -> Result<Expr, ParseError> {
+        match self.current_token {
+            Token::Ident(_) if self.peek_token == Token::LParen => self.parse_function_call(),
+            ref token if Self::is_ident(token) && self.peek_token == Token::Dot => {
+                let table_name = self.parse_ident()?;
+                self.next_token(); // skip .
+                let column_name = self.parse_ident()?;
+                Ok(Expr::Column(format!("{}.{}", table_name, column_name)))
+            }
+            Token::Ident(_) => Ok(Expr::Column(self.parse_ident()?)),
+            // `date` is a column, `date '2024-01-01'` a literal
+            ref token if Self::is_ident(token) && !matches!(self.peek_token, Token::String(_)) => {
+                Ok(Expr::Column(self.parse_ident()?))
+            }
+            Token::LParen => {
+                self.next_token(); // skip (
+                let expr = self.parse_expr()?;
+                if self.current_token != Token::RParen {
+                    return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                }
+                self.next_token(); // skip )
+                Ok(expr)
+            }
+            _ => Ok(Expr::Value(self.parse_value()?)),
+        }
+    }
+
+    fn parse_function_call(&mut self) -> Result<Expr, ParseError> {
+        let name = self.parse_ident()?;
+        let expr = if let Some(function) = AggregateFunction::from_name(&name) {
+            self.next_token(); // skip (
+            let arg =
+                if function == AggregateFunction::Count && self.current_token == Token::Asterisk {
+                    self.next_token(); // skip *
+                    None
+                } else {
+                    Some(Box::new(self.parse_expr()?))
+                };
+            Expr::Aggregate(function, arg)
+        } else {
+            match name.to_uppercase().as_str() {
+                "NOW" => {
+                    self.next_token(); // skip (
+                    Expr::Function(Function::Now, vec![])
+                }
+                "EXTRACT" => {
+                    self.next_token(); // skip (
+                    let field = self.parse_date_time_field()?;
+                    if self.current_token != Token::From {
+                        return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                    }
+                    self.next_token(); // skip from
+                    Expr::Function(Function::Extract(field), vec![self.parse_expr()?])
+                }
+                "DATE_TRUNC" => {
+                    self.next_token(); // skip (
+                    let field = match self.current_token.to_owned() {
+                        Token::String(name) => DateTimeField::from_name(&name),
+                        _ => None,
+                    };
+                    let Some(field) = field else {
+                        return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                    };
+                    self.next_token(); // skip field
+                    if self.current_token != Token::Comma {
+                        return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                    }
+                    self.next_token(); // skip ,
+                    Expr::Function(Function::DateTrunc(field), vec![self.parse_expr()?])
+                }
+                "JSON_EXTRACT" => {
+                    self.next_token(); // skip (
+                    Expr::Function(Function::JsonExtract, self.parse_function_args()?)
+                }
+                "JSON_ARRAY_LENGTH" => {
+                    self.next_token(); // skip (
+                    Expr::Function(Function::JsonArrayLength, self.parse_function_args()?)
+                }
+                _ => return Err(ParseError::UnknownFunction(name)),
+            }
+        };
+
+        if self.current_token != Token::RParen {
+            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+        }
+        self.next_token(); // skip )
+        Ok(expr)
+    }
+
+    /// comma separated expressions, the arity is checked when the function is typed
+    fn parse_function_args(&mut self) -> Result<Vec<Expr>, ParseError> {
+        let mut args = vec![self.parse_expr()?];
+        while self.current_token == Token::Comma {
+            self.next_token(); // skip ,
+            args.push(self.parse_expr()?);
+        }
+        Ok(args)
+    }
+
+    fn parse_date_time_field(&mut self) -> Result<DateTimeField, ParseError> {
+        match self.current_token.to_owned() {
+            Token::Ident(name) => match DateTimeField::from_name(&name) {
+                Some(field) => {
+                    self.next_token(); // skip field
+                    Ok(field)
+                }
+                None => Err(ParseError::UnexpectedToken(self.current_token.clone())),
+            },
+            _ => Err(ParseError::UnexpectedToken(self.current_token.clone())),
+        }
+    }
+
+    fn parse_insert_statement(&mut self) -> Result<QueryStatement, ParseError> {
+        self.next_token(); // skip insert
+        if self.current_token != Token::Into {
+            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+        }
+        self.next_token(); // skip into
+
+        let table_name = self.parse_ident()?;
+
+        // columns (optional)
+        let mut columns = Vec::new();
+        if self.current_token == Token::LParen {
+            self.next_token(); // skip (
+            columns.push(self.parse_ident()?);
+            while self.current_token == Token::Comma {
+                self.next_token(); // skip ,
+                columns.push(self.parse_ident()?);
+            }
+            if self.current_token != Token::RParen {
+                return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+            }
+            self.next_token(); // skip )
+        }
+
+        if self.current_token != Token::Values {
+            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+        }
+        self.next_token(); // skip values
+
+        let mut rows = Vec::new();
+        loop {
+            if self.current_token != Token::LParen {
+                return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+            }
+            self.next_token(); // skip (
+            let mut values = vec![self.parse_value()?];
+            while self.current_token == Token::Comma {
+                self.next_token(); // skip ,
+                values.push(self.parse_value()?);
+            }
+            if self.current_token != Token::RParen {
+                return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+            }
+            self.next_token(); // skip )
+            rows.push(values);
+
+            if self.current_token == Token::Comma {
+                self.next_token(); // skip ,
+            } else {
+                break;
+            }
+        }
+
+        Ok(QueryStatement::Insert(table_name, columns, rows))
     }
 
     fn parse_value(&mut self) -> Result<Value, ParseError> {
         match self.current_token.to_owned() {
-            Token::Integer(value) => {
+            Token::Minus => {
+                self.next_token(); // skip -
+                match self.current_token.to_owned() {
+                    // i64::MIN has no positive counterpart, so the sign is folded in first
+                    Token::Integer(value) => match 0i64.checked_sub_unsigned(value) {
+                        Some(value) => {
+                            self.next_token(); // skip value
+                            Ok(Value::Int(value))
+                        }
+                        None => Err(ParseError::InvalidLiteral(format!(
+                            "integer out of range: -{}",
+                            value
+                        ))),
+                    },
+                    Token::Invalid(message) => Err(ParseError::InvalidLiteral(message)),
+                    Token::Number(value) => {
+                        self.next_token(); // skip value
+                        Ok(Value::Decimal(format!("-{}", value)))
+                    }
+                    _ => Err(ParseError::UnexpectedToken(self.current_token.clone())),
+                }
+            }
+            Token::Integer(value) => match i64::try_from(value) {
+                Ok(value) => {
+                    self.next_token(); // skip value
+                    Ok(Value::Int(value))
+                }
+                Err(_) => Err(ParseError::InvalidLiteral(format!(
+                    "integer out of range: {}",
+                    value
+                ))),
+            },
+            Token::Number(value) => {
                 self.next_token(); // skip value
-                Ok(Value::Int(value))
+                Ok(Value::Decimal(value))
+            }
+            Token::Invalid(message) => Err(ParseError::InvalidLiteral(message)),
+            Token::True => {
+                self.next_token(); // skip true
+                Ok(Value::Boolean(true))
+            }
+            Token::False => {
+                self.next_token(); // skip false
+                Ok(Value::Boolean(false))
             }
             Token::String(value) => {
                 self.next_token(); // skip value
                 Ok(Value::VarChar(value))
             }
+            Token::Bytes(value) => {
+                self.next_token(); // skip value
+                Ok(Value::Blob(value))
+            }
+            Token::Null => {
+                self.next_token(); // skip null
+                Ok(Value::Null)
+            }
+            // typed literals: DATE '2026-10-18', TIME '12:00', TIMESTAMP '...', INTERVAL '1 day',
+            // JSON '{...}'
+            token @ (Token::Date
+            | Token::Time
+            | Token::Timestamp
+            | Token::Interval
+            | Token::Json) => {
+                self.next_token(); // skip type
+                let Token::String(value) = self.current_token.to_owned() else {
+                    return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                };
+                self.next_token(); // skip value
+                Ok(match token {
+                    Token::Date => Value::Date(value),
+                    Token::Time => Value::Time(value),
+                    Token::Timestamp => Value::Timestamp(value),
+                    Token::Interval => Value::Interval(value),
+                    _ => Value::Json(value),
+                })
+            }
             _ => Err(ParseError::UnexpectedToken(self.current_token.clone())),
         }
     }
@@ -200,10 +708,19 @@ impl Parser {
         self.next_token(); // skip (
 
         let mut columns = Vec::new();
+        let mut constraints = Vec::new();
         loop {
-            let column_name = self.parse_ident()?;
-            let data_type = self.parse_data_type()?;
-            columns.push((column_name, data_type));
+            match self.current_token {
+                Token::Primary | Token::Unique | Token::Check | Token::Foreign => {
+                    constraints.push(self.parse_table_constraint()?)
+                }
+                _ => {
+                    let column_name = self.parse_ident()?;
+                    let data_type = self.parse_data_type()?;
+                    self.parse_column_constraints(&column_name, &mut constraints)?;
+                    columns.push((column_name, data_type));
+                }
+            }
             if self.current_token == Token::Comma {
                 self.next_token(); // skip ,
             } else {
@@ -215,53 +732,439 @@ impl Parser {
         }
         self.next_token(); // skip )
 
-        Ok(QueryStatement::CreateTable(table_name, columns))
+        Ok(QueryStatement::CreateTable(
+            table_name,
+            columns,
+            constraints,
+        ))
     }
 
-    fn parse_data_type(&mut self) -> Result<super::ast::DataType, ParseError> {
-        match self.current_token.to_owned() {
-            Token::Int => {
-                self.next_token(); // skip int
-                Ok(super::ast::DataType::Int)
-            }
-            Token::VarChar => {
-                self.next_token(); // skip varchar
-                if self.current_token != Token::LParen {
-                    return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+    /// constraints following a column definition, e.g. `id INT PRIMARY KEY CHECK (id > 0)`
+    fn parse_column_constraints(
+        &mut self,
+        column_name: &str,
+        constraints: &mut Vec<Constraint>,
+    ) -> Result<(), ParseError> {
+        loop {
+            let constraint = match self.current_token {
+                Token::Primary => {
+                    self.next_token(); // skip primary
+                    if self.current_token != Token::Key {
+                        return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                    }
+                    self.next_token(); // skip key
+                    Constraint::PrimaryKey(vec![column_name.to_string()])
                 }
-                self.next_token(); // skip (
-                let length = self.parse_int()?;
-                if self.current_token != Token::RParen {
-                    return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                Token::Unique => {
+                    self.next_token(); // skip unique
+                    Constraint::Unique(vec![column_name.to_string()])
                 }
-                self.next_token(); // skip )
-                Ok(super::ast::DataType::VarChar(length as u16))
-            }
-            _ => Err(ParseError::UnexpectedToken(self.current_token.clone())),
+                Token::Not => {
+                    self.next_token(); // skip not
+                    if self.current_token != Token::Null {
+                        return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                    }
+                    self.next_token(); // skip null
+                    Constraint::NotNull(column_name.to_string())
+                }
+                Token::Null => {
+                    // nullable, which is the default anyway
+                    self.next_token(); // skip null
+                    continue;
+                }
+                Token::Default => {
+                    self.next_token(); // skip default
+                    Constraint::Default(column_name.to_string(), self.parse_value()?)
+                }
+                Token::Check => Constraint::Check(self.parse_check()?),
+                Token::References => {
+                    Constraint::ForeignKey(self.parse_references(vec![column_name.to_string()])?)
+                }
+                _ => return Ok(()),
+            };
+            constraints.push(constraint);
         }
     }
 
-    fn parse_ident(&mut self) -> Result<String, ParseError> {
-        match self.current_token.to_owned() {
-            Token::Ident(name) => {
-                self.next_token(); // skip name
-                Ok(name)
+    /// `PRIMARY KEY (a, b)`, `UNIQUE (a, b)`, `CHECK (expr)` or `FOREIGN KEY (a, b) REFERENCES ...`
+    /// in the column list
+    fn parse_table_constraint(&mut self) -> Result<Constraint, ParseError> {
+        match self.current_token {
+            Token::Primary => {
+                self.next_token(); // skip primary
+                if self.current_token != Token::Key {
+                    return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                }
+                self.next_token(); // skip key
+                Ok(Constraint::PrimaryKey(self.parse_column_list()?))
             }
-            _ => Err(ParseError::UnexpectedToken(self.current_token.clone())),
-        }
-    }
-
-    fn parse_int(&mut self) -> Result<i32, ParseError> {
-        match self.current_token.to_owned() {
-            Token::Integer(value) => {
-                self.next_token(); // skip value
-                Ok(value)
+            Token::Unique => {
+                self.next_token(); // skip unique
+                Ok(Constraint::Unique(self.parse_column_list()?))
+            }
+            Token::Check => Ok(Constraint::Check(self.parse_check()?)),
+            Token::Foreign => {
+                self.next_token(); // skip foreign
+                if self.current_token != Token::Key {
+                    return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                }
+                self.next_token(); // skip key
+                let columns = self.parse_column_list()?;
+                Ok(Constraint::ForeignKey(self.parse_references(columns)?))
             }
             _ => Err(ParseError::UnexpectedToken(self.current_token.clone())),
         }
     }
 
-    fn parse_exit_statement(&mut self) -> Result<QueryStatement, ParseError> {
+    /// `REFERENCES table [(a, b)] [ON DELETE action] [ON UPDATE action]`
+    fn parse_references(&mut self, columns: Vec<String>) -> Result<ForeignKey, ParseError> {
+        if self.current_token != Token::References {
+            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+        }
+        self.next_token(); // skip references
+        let ref_table = self.parse_ident()?;
+        let ref_columns = if self.current_token == Token::LParen {
+            self.parse_column_list()?
+        } else {
+            vec![]
+        };
+
+        let mut foreign_key = ForeignKey {
+            columns,
+            ref_table,
+            ref_columns,
+            on_delete: ReferentialAction::Restrict,
+            on_update: ReferentialAction::Restrict,
+        };
+        while self.current_token == Token::On {
+            self.next_token(); // skip on
+            match self.current_token {
+                Token::Delete => {
+                    self.next_token(); // skip delete
+                    foreign_key.on_delete = self.parse_referential_action()?;
+                }
+                Token::Update => {
+                    self.next_token(); // skip update
+                    foreign_key.on_update = self.parse_referential_action()?;
+                }
+                _ => return Err(ParseError::UnexpectedToken(self.current_token.clone())),
+            }
+        }
+        Ok(foreign_key)
+    }
+
+    fn parse_referential_action(&mut self) -> Result<ReferentialAction, ParseError> {
+        let action = match self.current_token {
+            Token::Restrict => ReferentialAction::Restrict,
+            Token::Cascade => ReferentialAction::Cascade,
+            Token::No => {
+                self.next_token(); // skip no
+                if self.current_token != Token::Action {
+                    return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                }
+                ReferentialAction::Restrict
+            }
+            Token::Set => {
+                self.next_token(); // skip set
+                match self.current_token {
+                    Token::Null => ReferentialAction::SetNull,
+                    Token::Default => ReferentialAction::SetDefault,
+                    _ => return Err(ParseError::UnexpectedToken(self.current_token.clone())),
+                }
+            }
+            _ => return Err(ParseError::UnexpectedToken(self.current_token.clone())),
+        };
+        self.next_token(); // skip last word of the action
+        Ok(action)
+    }
+
+    fn parse_check(&mut self) -> Result<Expr, ParseError> {
+        self.next_token(); // skip check
+        if self.current_token != Token::LParen {
+            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+        }
+        self.next_token(); // skip (
+        let expr = self.parse_expr()?;
+        if self.current_token != Token::RParen {
+            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+        }
+        self.next_token(); // skip )
+        Ok(expr)
+    }
+
+    /// `(a, b, ...)`
+    fn parse_column_list(&mut self) -> Result<Vec<String>, ParseError> {
+        if self.current_token != Token::LParen {
+            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+        }
+        self.next_token(); // skip (
+        let mut column_names = vec![self.parse_ident()?];
+        while self.current_token == Token::Comma {
+            self.next_token(); // skip ,
+            column_names.push(self.parse_ident()?);
+        }
+        if self.current_token != Token::RParen {
+            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+        }
+        self.next_token(); // skip )
+        Ok(column_names)
+    }
+
+    fn parse_alter_table_statement(&mut self) -> Result<QueryStatement, ParseError> {
+        self.next_token(); // skip alter
+        if self.current_token != Token::Table {
+            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+        }
+        self.next_token(); // skip table
+
+        let table_name = self.parse_ident()?;
+
+        let action = match self.current_token {
+            Token::Add => {
+                self.next_token(); // skip add
+                                   // `column` may also be the name of the column
+                if self.current_token == Token::Column && Self::is_ident(&self.peek_token) {
+                    self.next_token(); // skip column
+                }
+                let column_name = self.parse_ident()?;
+                let data_type = self.parse_data_type()?;
+                AlterTableAction::AddColumn(column_name, data_type)
+            }
+            Token::Drop => {
+                self.next_token(); // skip drop
+                if self.current_token == Token::Column && Self::is_ident(&self.peek_token) {
+                    self.next_token(); // skip column
+                }
+                let column_name = self.parse_ident()?;
+                AlterTableAction::DropColumn(column_name)
+            }
+            Token::Rename => {
+                self.next_token(); // skip rename
+                                   // `to` and `column` may also be names, told apart by the token after them
+                if self.current_token == Token::To {
+                    self.next_token(); // skip to
+                    if self.current_token == Token::To && Self::is_ident(&self.peek_token) {
+                        // RENAME to TO name
+                        self.next_token(); // skip to
+                        AlterTableAction::RenameColumn(String::from("to"), self.parse_ident()?)
+                    } else {
+                        AlterTableAction::RenameTo(self.parse_ident()?)
+                    }
+                } else {
+                    let from = if self.current_token == Token::Column {
+                        self.next_token(); // skip column
+                        if self.current_token == Token::To && self.peek_token != Token::To {
+                            // RENAME column TO name
+                            String::from("column")
+                        } else {
+                            self.parse_ident()?
+                        }
+                    } else {
+                        self.parse_ident()?
+                    };
+                    if self.current_token != Token::To {
+                        return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                    }
+                    self.next_token(); // skip to
+                    let to = self.parse_ident()?;
+                    AlterTableAction::RenameColumn(from, to)
+                }
+            }
+            _ => return Err(ParseError::UnexpectedToken(self.current_token.clone())),
+        };
+
+        Ok(QueryStatement::AlterTable(table_name, action))
+    }
+
+    fn parse_drop_table_statement(&mut self) -> Result<QueryStatement, ParseError> {
+        self.next_token(); // skip drop
+        if self.current_token != Token::Table {
+            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+        }
+        self.next_token(); // skip table
+
+        let mut if_exists = false;
+        // a table may be called `if`
+        if self.current_token == Token::If && self.peek_token == Token::Exists {
+            self.next_token(); // skip if
+            if self.current_token != Token::Exists {
+                return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+            }
+            self.next_token(); // skip exists
+            if_exists = true;
+        }
+
+        let table_name = self.parse_ident()?;
+        Ok(QueryStatement::DropTable(table_name, if_exists))
+    }
+
+    fn parse_truncate_statement(&mut self) -> Result<QueryStatement, ParseError> {
+        self.next_token(); // skip truncate
+        if self.current_token == Token::Table {
+            self.next_token(); // skip table
+        }
+
+        let table_name = self.parse_ident()?;
+        Ok(QueryStatement::Truncate(table_name))
+    }
+
+    fn parse_data_type(&mut self) -> Result<super::ast::DataType, ParseError> {
+        match self.current_token.to_owned() {
+            Token::Boolean => {
+                self.next_token(); // skip boolean
+                Ok(super::ast::DataType::Boolean)
+            }
+            Token::Int => {
+                self.next_token(); // skip int
+                Ok(super::ast::DataType::Int)
+            }
+            Token::BigInt => {
+                self.next_token(); // skip bigint
+                Ok(super::ast::DataType::BigInt)
+            }
+            Token::Double => {
+                self.next_token(); // skip double
+                Ok(super::ast::DataType::Double)
+            }
+            Token::Decimal => {
+                self.next_token(); // skip decimal
+
+                // DECIMAL is DECIMAL(10, 0), DECIMAL(p) is DECIMAL(p, 0)
+                let (mut precision, mut scale) = (10, 0);
+                if self.current_token == Token::LParen {
+                    self.next_token(); // skip (
+                    if !matches!(self.current_token, Token::Integer(1..=38)) {
+                        return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                    }
+                    precision = self.parse_int()?;
+                    scale = 0;
+                    if self.current_token == Token::Comma {
+                        self.next_token(); // skip ,
+                        if !matches!(self.current_token, Token::Integer(s) if s <= precision as u64)
+                        {
+                            return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                        }
+                        scale = self.parse_int()?;
+                    }
+                    if self.current_token != Token::RParen {
+                        return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                    }
+                    self.next_token(); // skip )
+                }
+                Ok(super::ast::DataType::Decimal(precision, scale))
+            }
+            Token::VarChar => {
+                self.next_token(); // skip varchar
+                if self.current_token != Token::LParen {
+                    return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                }
+                self.next_token(); // skip (
+                if self.current_token == Token::Integer(0) {
+                    return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                }
+                let length = self.parse_int()?;
+                if self.current_token != Token::RParen {
+                    return Err(ParseError::UnexpectedToken(self.current_token.clone()));
+                }
+                self.next_token(); // skip )
+                Ok(super::ast::DataType::VarChar(length))
+            }
+            Token::Date => {
+                self.next_token(); // skip date
+                Ok(super::ast::DataType::Date)
+            }
+            Token::Time => {
+                self.next_token(); // skip time
+                Ok(super::ast::DataType::Time)
+            }
+            Token::Timestamp => {
+                self.next_token(); // skip timestamp
+                Ok(super::ast::DataType::Timestamp)
+            }
+            Token::Interval => {
+                self.next_token(); // skip interval
+                Ok(super::ast::DataType::Interval)
+            }
+            Token::Text => {
+                self.next_token(); // skip text
+                Ok(super::ast::DataType::Text)
+            }
+            Token::Blob => {
+                self.next_token(); // skip blob
+                Ok(super::ast::DataType::Blob)
+            }
+            Token::Json => {
+                self.next_token(); // skip json
+                Ok(super::ast::DataType::Json)
+            }
+            _ => Err(ParseError::UnexpectedToken(self.current_token.clone())),
+        }
+    }
+
+    /// a name, which may also be one of the non-reserved keywords
+    fn parse_ident(&mut self) -> Result<String, ParseError> {
+        let name = match self.current_token.to_owned() {
+            Token::Ident(name) => name,
+            token => match Self::non_reserved_keyword(&token) {
+                Some(keyword) => keyword.to_string(),
+                None => return Err(ParseError::UnexpectedToken(token)),
+            },
+        };
+        self.next_token(); // skip name
+        Ok(name)
+    }
+
+    fn is_ident(token: &Token) -> bool {
+        matches!(token, Token::Ident(_)) || Self::non_reserved_keyword(token).is_some()
+    }
+
+    /// keywords that only have a meaning in some positions, and are names everywhere else
+    fn non_reserved_keyword(token: &Token) -> Option<&'static str> {
+        match token {
+            Token::Date => Some("date"),
+            Token::Time => Some("time"),
+            Token::Timestamp => Some("timestamp"),
+            Token::Interval => Some("interval"),
+            Token::Text => Some("text"),
+            Token::Json => Some("json"),
+            Token::Key => Some("key"),
+            Token::Cascade => Some("cascade"),
+            Token::Restrict => Some("restrict"),
+            Token::No => Some("no"),
+            Token::Action => Some("action"),
+            Token::Boolean => Some("boolean"),
+            Token::BigInt => Some("bigint"),
+            Token::Double => Some("double"),
+            Token::Decimal => Some("decimal"),
+            Token::Blob => Some("blob"),
+            Token::Add => Some("add"),
+            Token::Column => Some("column"),
+            Token::Rename => Some("rename"),
+            Token::To => Some("to"),
+            Token::If => Some("if"),
+            Token::Exists => Some("exists"),
+            Token::Left => Some("left"),
+            Token::Right => Some("right"),
+            Token::Full => Some("full"),
+            _ => None,
+        }
+    }
+
+    /// an integer literal that fits in `T`
+    fn parse_int<T: TryFrom<u64>>(&mut self) -> Result<T, ParseError> {
+        match self.current_token.to_owned() {
+            Token::Integer(value) => match T::try_from(value) {
+                Ok(value) => {
+                    self.next_token(); // skip value
+                    Ok(value)
+                }
+                Err(_) => Err(ParseError::UnexpectedToken(self.current_token.clone())),
+            },
+            _ => Err(ParseError::UnexpectedToken(self.current_token.clone())),
+        }
+    }
+
+    fn parse_exit_statement(&mut self) -> Result<QueryStatement, ParseError> {
         self.next_token(); // skip exit
         Ok(QueryStatement::Exit)
     }
@@ -283,13 +1186,50 @@ mod test {
         Parser::new(lexer).parse()
     }
 
+    fn select(
+        table_name: String,
+        projections: Vec<SelectItem>,
+        cond: Option<Expr>,
+    ) -> QueryStatement {
+        QueryStatement::Select(SelectStatement {
+            projections,
+            cond,
+            ..select_from(&table_name)
+        })
+    }
+
+    fn select_from(table_name: &str) -> SelectStatement {
+        SelectStatement {
+            from: TableRef::Table(table_name.to_string(), None),
+            projections: vec![SelectItem::Wildcard],
+            cond: None,
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        }
+    }
+
+    fn column(name: &str) -> SelectItem {
+        SelectItem::Expr(Expr::Column(name.to_string()), None)
+    }
+
+    fn eq(column: &str, value: Value) -> Expr {
+        Expr::Binary(
+            Box::new(Expr::Column(column.to_string())),
+            BinaryOperator::Eq,
+            Box::new(Expr::Value(value)),
+        )
+    }
+
     #[test]
     fn test_parse_select_single() {
         let statements = parse(String::from("SELECT foo FROM user;")).unwrap();
         assert_eq!(statements.len(), 1);
         assert_eq!(
             statements[0],
-            QueryStatement::Select(String::from("user"), false, vec!["foo".to_string(),], None)
+            select(String::from("user"), vec![column("foo")], None)
         );
     }
 
@@ -299,10 +1239,9 @@ mod test {
         assert_eq!(statements.len(), 1);
         assert_eq!(
             statements[0],
-            QueryStatement::Select(
+            select(
                 String::from("user"),
-                false,
-                vec!["foo".to_string(), "bar".to_string()],
+                vec![column("foo"), column("bar")],
                 None
             )
         );
@@ -314,11 +1253,10 @@ mod test {
         assert_eq!(statements.len(), 1);
         assert_eq!(
             statements[0],
-            QueryStatement::Select(
+            select(
                 String::from("users"),
-                false,
-                vec!["foo".to_string(), "bar".to_string()],
-                Some(("id".to_string(), Value::Int(1)))
+                vec![column("foo"), column("bar")],
+                Some(eq("id", Value::Int(1)))
             )
         );
     }
@@ -330,7 +1268,7 @@ mod test {
             assert_eq!(statements.len(), 1);
             assert_eq!(
                 statements[0],
-                QueryStatement::Select(String::from("user"), true, vec![], None)
+                select(String::from("user"), vec![SelectItem::Wildcard], None)
             );
         }
         {
@@ -338,7 +1276,11 @@ mod test {
             assert_eq!(statements.len(), 1);
             assert_eq!(
                 statements[0],
-                QueryStatement::Select(String::from("user"), true, vec!["foo".to_string()], None)
+                select(
+                    String::from("user"),
+                    vec![SelectItem::Wildcard, column("foo")],
+                    None
+                )
             );
         }
         {
@@ -346,74 +1288,1164 @@ mod test {
             assert_eq!(statements.len(), 1);
             assert_eq!(
                 statements[0],
-                QueryStatement::Select(String::from("user"), true, vec!["foo".to_string()], None)
+                select(
+                    String::from("user"),
+                    vec![column("foo"), SelectItem::Wildcard],
+                    None
+                )
             );
         }
     }
 
     #[test]
-    fn test_parse_set_single() {
-        let statements =
-            parse(String::from("UPDATE user SET name = 'mike' WHERE id = 1;")).unwrap();
-        assert_eq!(statements.len(), 1);
+    fn test_parse_where_boolean() {
+        let statements = parse(String::from(
+            "SELECT * FROM user WHERE id = 1 OR id = 2 AND NOT name = 'mike';",
+        ))
+        .unwrap();
         assert_eq!(
             statements[0],
-            QueryStatement::Update(
+            select(
                 String::from("user"),
-                vec![("name".to_string(), Value::VarChar("mike".to_string()))],
-                ("id".to_string(), Value::Int(1))
+                vec![SelectItem::Wildcard],
+                Some(Expr::Binary(
+                    Box::new(eq("id", Value::Int(1))),
+                    BinaryOperator::Or,
+                    Box::new(Expr::Binary(
+                        Box::new(eq("id", Value::Int(2))),
+                        BinaryOperator::And,
+                        Box::new(Expr::Not(Box::new(eq(
+                            "name",
+                            Value::VarChar("mike".to_string())
+                        )))),
+                    )),
+                ))
             )
         );
     }
 
     #[test]
-    fn test_parse_set_multi() {
+    fn test_parse_where_paren() {
         let statements = parse(String::from(
-            "UPDATE user SET foo = 1, bar = 999 WHERE name = 'mike';",
+            "DELETE FROM user WHERE (id = 1 OR id = 2) AND name = 'mike';",
         ))
         .unwrap();
-        assert_eq!(statements.len(), 1);
         assert_eq!(
             statements[0],
-            QueryStatement::Update(
+            QueryStatement::Delete(
+                String::from("user"),
+                Some(Expr::Binary(
+                    Box::new(Expr::Binary(
+                        Box::new(eq("id", Value::Int(1))),
+                        BinaryOperator::Or,
+                        Box::new(eq("id", Value::Int(2))),
+                    )),
+                    BinaryOperator::And,
+                    Box::new(eq("name", Value::VarChar("mike".to_string()))),
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_where_comparison() {
+        let statements = parse(String::from(
+            "SELECT * FROM user WHERE id >= 1 AND id < 10 AND name <> 'mike';",
+        ))
+        .unwrap();
+        let cmp = |column: &str, operator, value| {
+            Box::new(Expr::Binary(
+                Box::new(Expr::Column(column.to_string())),
+                operator,
+                Box::new(Expr::Value(value)),
+            ))
+        };
+        assert_eq!(
+            statements[0],
+            select(
                 String::from("user"),
+                vec![SelectItem::Wildcard],
+                Some(Expr::Binary(
+                    Box::new(Expr::Binary(
+                        cmp("id", BinaryOperator::GtEq, Value::Int(1)),
+                        BinaryOperator::And,
+                        cmp("id", BinaryOperator::Lt, Value::Int(10)),
+                    )),
+                    BinaryOperator::And,
+                    cmp(
+                        "name",
+                        BinaryOperator::NotEq,
+                        Value::VarChar("mike".to_string())
+                    ),
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_arithmetic() {
+        let statements = parse(String::from(
+            "SELECT * FROM item WHERE price * -2 + 1 > (id - 1.5) % 3 AND active = TRUE;",
+        ))
+        .unwrap();
+        let binary = |left, operator, right| Box::new(Expr::Binary(left, operator, right));
+        let column = |name: &str| Box::new(Expr::Column(name.to_string()));
+        let value = |value| Box::new(Expr::Value(value));
+        assert_eq!(
+            statements[0],
+            select(
+                String::from("item"),
+                vec![SelectItem::Wildcard],
+                Some(Expr::Binary(
+                    binary(
+                        binary(
+                            binary(column("price"), BinaryOperator::Mul, value(Value::Int(-2))),
+                            BinaryOperator::Add,
+                            value(Value::Int(1)),
+                        ),
+                        BinaryOperator::Gt,
+                        binary(
+                            binary(
+                                column("id"),
+                                BinaryOperator::Sub,
+                                value(Value::Decimal(String::from("1.5"))),
+                            ),
+                            BinaryOperator::Mod,
+                            value(Value::Int(3)),
+                        ),
+                    ),
+                    BinaryOperator::And,
+                    binary(
+                        column("active"),
+                        BinaryOperator::Eq,
+                        value(Value::Boolean(true))
+                    ),
+                ))
+            )
+        );
+
+        // subtraction is left associative
+        let statements = parse(String::from("SELECT * FROM item WHERE 1 - 2 - 3 = -4;")).unwrap();
+        assert_eq!(
+            statements[0],
+            select(
+                String::from("item"),
+                vec![SelectItem::Wildcard],
+                Some(Expr::Binary(
+                    binary(
+                        binary(
+                            value(Value::Int(1)),
+                            BinaryOperator::Sub,
+                            value(Value::Int(2))
+                        ),
+                        BinaryOperator::Sub,
+                        value(Value::Int(3)),
+                    ),
+                    BinaryOperator::Eq,
+                    value(Value::Int(-4)),
+                ))
+            )
+        );
+
+        // prefix minus binds tighter than * and applies to any operand
+        let statements = parse(String::from(
+            "SELECT -price * 2, - -id, -(id - 1) FROM item;",
+        ))
+        .unwrap();
+        let neg = |expr| Box::new(Expr::Neg(expr));
+        assert_eq!(
+            statements[0],
+            select(
+                String::from("item"),
                 vec![
-                    ("foo".to_string(), Value::Int(1)),
-                    ("bar".to_string(), Value::Int(999))
+                    SelectItem::Expr(
+                        Expr::Binary(
+                            neg(column("price")),
+                            BinaryOperator::Mul,
+                            value(Value::Int(2))
+                        ),
+                        None
+                    ),
+                    SelectItem::Expr(Expr::Neg(neg(column("id"))), None),
+                    SelectItem::Expr(
+                        Expr::Neg(binary(
+                            column("id"),
+                            BinaryOperator::Sub,
+                            value(Value::Int(1))
+                        )),
+                        None
+                    ),
                 ],
-                ("name".to_string(), Value::VarChar("mike".to_string()))
+                None
             )
         );
+        // and displays back to input parsing to the same tree
+        let QueryStatement::Select(select) = &statements[0] else {
+            unreachable!()
+        };
+        for item in select.projections.iter() {
+            let SelectItem::Expr(expr, _) = item else {
+                unreachable!()
+            };
+            assert_eq!(
+                Parser::new(Lexer::new(expr.to_string()))
+                    .parse_standalone_expr()
+                    .unwrap(),
+                *expr
+            );
+        }
+
+        // the sign is part of the literal, so the smallest BIGINT can be written
+        let literal =
+            |input: &str| Parser::new(Lexer::new(input.to_string())).parse_standalone_expr();
+        assert_eq!(
+            literal("-9223372036854775808"),
+            Ok(Expr::Value(Value::Int(i64::MIN)))
+        );
+        assert_eq!(
+            literal("9223372036854775807"),
+            Ok(Expr::Value(Value::Int(i64::MAX)))
+        );
+        for (input, message) in [
+            (
+                "9223372036854775808",
+                "integer out of range: 9223372036854775808",
+            ),
+            (
+                "-9223372036854775809",
+                "integer out of range: -9223372036854775809",
+            ),
+            (
+                "-99999999999999999999",
+                "integer out of range: 99999999999999999999",
+            ),
+        ] {
+            assert_eq!(
+                literal(input),
+                Err(ParseError::InvalidLiteral(String::from(message)))
+            );
+        }
     }
 
     #[test]
-    fn test_parse_error() {
-        {
-            let err = parse(String::from("SELECT;")).unwrap_err();
-            assert_eq!(err, ParseError::UnexpectedToken(Token::SemiColon));
-        }
-        {
-            let err = parse(String::from("SELECT 1;")).unwrap_err();
-            assert_eq!(err, ParseError::UnexpectedToken(Token::Integer(1)));
+    fn test_parse_date_time() {
+        let statements = parse(String::from(
+            "SELECT EXTRACT(year FROM created), DATE_TRUNC('day', NOW()) FROM event WHERE created > TIMESTAMP '2026-10-18 09:30' - INTERVAL '1 day' AND day = DATE '2026-10-18';",
+        ))
+        .unwrap();
+        let column = |name: &str| Box::new(Expr::Column(name.to_string()));
+        let value = |value| Box::new(Expr::Value(value));
+        assert_eq!(
+            statements[0],
+            select(
+                String::from("event"),
+                vec![
+                    SelectItem::Expr(
+                        Expr::Function(
+                            Function::Extract(DateTimeField::Year),
+                            vec![Expr::Column(String::from("created"))]
+                        ),
+                        None
+                    ),
+                    SelectItem::Expr(
+                        Expr::Function(
+                            Function::DateTrunc(DateTimeField::Day),
+                            vec![Expr::Function(Function::Now, vec![])]
+                        ),
+                        None
+                    ),
+                ],
+                Some(Expr::Binary(
+                    Box::new(Expr::Binary(
+                        column("created"),
+                        BinaryOperator::Gt,
+                        Box::new(Expr::Binary(
+                            value(Value::Timestamp(String::from("2026-10-18 09:30"))),
+                            BinaryOperator::Sub,
+                            value(Value::Interval(String::from("1 day"))),
+                        )),
+                    )),
+                    BinaryOperator::And,
+                    Box::new(Expr::Binary(
+                        column("day"),
+                        BinaryOperator::Eq,
+                        value(Value::Date(String::from("2026-10-18"))),
+                    )),
+                ))
+            )
+        );
+
+        // the display form parses back to the same tree
+        let QueryStatement::Select(select) = &statements[0] else {
+            unreachable!()
+        };
+        let cond = select.cond.as_ref().unwrap();
+        assert_eq!(
+            Parser::new(Lexer::new(cond.to_string()))
+                .parse_standalone_expr()
+                .unwrap(),
+            *cond
+        );
+
+        for (input, token) in [
+            ("SELECT NOW(1) FROM t;", Token::Integer(1)),
+            (
+                "SELECT EXTRACT(fortnight FROM d) FROM t;",
+                Token::Ident(String::from("fortnight")),
+            ),
+            ("SELECT EXTRACT(DAY, d) FROM t;", Token::Comma),
+            (
+                "SELECT DATE_TRUNC(day, d) FROM t;",
+                Token::Ident(String::from("day")),
+            ),
+            ("SELECT DATE_TRUNC('day') FROM t;", Token::RParen),
+            // without a string, DATE is the column date and the 1 is left over
+            ("SELECT * FROM t WHERE d = DATE 1;", Token::Integer(1)),
+        ] {
+            assert_eq!(
+                parse(String::from(input)),
+                Err(ParseError::UnexpectedToken(token)),
+                "{}",
+                input
+            );
         }
     }
 
     #[test]
-    fn test_parse_create_table() {
+    fn test_parse_non_reserved_keywords() {
         let statements = parse(String::from(
-            "CREATE TABLE users (id INT, name VARCHAR(255));",
+            "CREATE TABLE ev (id INT, date DATE, key TEXT); SELECT e.date, time AS text FROM ev json WHERE date > DATE '2026-10-18' ORDER BY key;",
         ))
         .unwrap();
-        assert_eq!(statements.len(), 1);
         assert_eq!(
             statements[0],
             QueryStatement::CreateTable(
-                "users".to_string(),
+                "ev".to_string(),
                 vec![
                     ("id".to_string(), DataType::Int),
-                    ("name".to_string(), DataType::VarChar(255)),
-                ]
+                    ("date".to_string(), DataType::Date),
+                    ("key".to_string(), DataType::Text),
+                ],
+                vec![]
             )
         );
+        assert_eq!(
+            statements[1],
+            QueryStatement::Select(SelectStatement {
+                from: TableRef::Table(String::from("ev"), Some(String::from("json"))),
+                projections: vec![
+                    column("e.date"),
+                    SelectItem::Expr(
+                        Expr::Column(String::from("time")),
+                        Some(String::from("text"))
+                    ),
+                ],
+                cond: Some(Expr::Binary(
+                    Box::new(Expr::Column(String::from("date"))),
+                    BinaryOperator::Gt,
+                    Box::new(Expr::Value(Value::Date(String::from("2026-10-18")))),
+                )),
+                order_by: vec![(Expr::Column(String::from("key")), Order::Asc)],
+                ..select_from("ev")
+            })
+        );
+
+        // the keywords keep their meaning where they have one
+        assert_eq!(
+            parse(String::from("CREATE TABLE t (date PRIMARY KEY);")),
+            Err(ParseError::UnexpectedToken(Token::Primary))
+        );
+
+        for name in [
+            "boolean", "double", "decimal", "bigint", "blob", "add", "column", "rename", "to",
+            "if", "exists", "left", "right", "full",
+        ] {
+            let statements = parse(format!(
+                "CREATE TABLE t ({name} INT); SELECT {name}, t.{name} FROM t WHERE {name} = 1 ORDER BY {name};"
+            ))
+            .unwrap();
+            assert_eq!(
+                statements[0],
+                QueryStatement::CreateTable(
+                    "t".to_string(),
+                    vec![(name.to_string(), DataType::Int)],
+                    vec![]
+                ),
+                "{}",
+                name
+            );
+            assert_eq!(
+                statements[1],
+                QueryStatement::Select(SelectStatement {
+                    projections: vec![column(name), column(&format!("t.{}", name))],
+                    cond: Some(Expr::Binary(
+                        Box::new(Expr::Column(name.to_string())),
+                        BinaryOperator::Eq,
+                        Box::new(Expr::Value(Value::Int(1))),
+                    )),
+                    order_by: vec![(Expr::Column(name.to_string()), Order::Asc)],
+                    ..select_from("t")
+                }),
+                "{}",
+                name
+            );
+        }
+
+        // where the words could be names or keywords, the tokens after them decide
+        assert_eq!(
+            parse(String::from(
+                "ALTER TABLE t ADD column INT; ALTER TABLE t DROP column; ALTER TABLE t RENAME column TO c; ALTER TABLE t RENAME to TO c; ALTER TABLE t RENAME COLUMN to TO c; ALTER TABLE t RENAME TO to; DROP TABLE if;"
+            ))
+            .unwrap(),
+            vec![
+                QueryStatement::AlterTable(
+                    "t".to_string(),
+                    AlterTableAction::AddColumn("column".to_string(), DataType::Int)
+                ),
+                QueryStatement::AlterTable(
+                    "t".to_string(),
+                    AlterTableAction::DropColumn("column".to_string())
+                ),
+                QueryStatement::AlterTable(
+                    "t".to_string(),
+                    AlterTableAction::RenameColumn("column".to_string(), "c".to_string())
+                ),
+                QueryStatement::AlterTable(
+                    "t".to_string(),
+                    AlterTableAction::RenameColumn("to".to_string(), "c".to_string())
+                ),
+                QueryStatement::AlterTable(
+                    "t".to_string(),
+                    AlterTableAction::RenameColumn("to".to_string(), "c".to_string())
+                ),
+                QueryStatement::AlterTable(
+                    "t".to_string(),
+                    AlterTableAction::RenameTo("to".to_string())
+                ),
+                QueryStatement::DropTable("if".to_string(), false),
+            ]
+        );
+        assert_eq!(
+            parse(String::from("SELECT * FROM t LEFT JOIN u ON t.id = u.id;")).unwrap()[0],
+            parse(String::from(
+                "SELECT * FROM t LEFT OUTER JOIN u ON t.id = u.id;"
+            ))
+            .unwrap()[0]
+        );
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let statements = parse(String::from(
+            "SELECT doc -> 'tags' ->> 0, JSON_EXTRACT(doc, '$.a'), json_array_length(doc, '$.tags') FROM t WHERE doc ->> 'name' = 'alice' AND doc -> 'n' = JSON '1';",
+        ))
+        .unwrap();
+        let column = |name: &str| Box::new(Expr::Column(name.to_string()));
+        let value = |value| Box::new(Expr::Value(value));
+        assert_eq!(
+            statements[0],
+            select(
+                String::from("t"),
+                vec![
+                    SelectItem::Expr(
+                        Expr::Binary(
+                            Box::new(Expr::Binary(
+                                column("doc"),
+                                BinaryOperator::JsonGet,
+                                value(Value::VarChar(String::from("tags"))),
+                            )),
+                            BinaryOperator::JsonGetText,
+                            value(Value::Int(0)),
+                        ),
+                        None
+                    ),
+                    SelectItem::Expr(
+                        Expr::Function(
+                            Function::JsonExtract,
+                            vec![
+                                Expr::Column(String::from("doc")),
+                                Expr::Value(Value::VarChar(String::from("$.a"))),
+                            ]
+                        ),
+                        None
+                    ),
+                    SelectItem::Expr(
+                        Expr::Function(
+                            Function::JsonArrayLength,
+                            vec![
+                                Expr::Column(String::from("doc")),
+                                Expr::Value(Value::VarChar(String::from("$.tags"))),
+                            ]
+                        ),
+                        None
+                    ),
+                ],
+                Some(Expr::Binary(
+                    Box::new(Expr::Binary(
+                        Box::new(Expr::Binary(
+                            column("doc"),
+                            BinaryOperator::JsonGetText,
+                            value(Value::VarChar(String::from("name"))),
+                        )),
+                        BinaryOperator::Eq,
+                        value(Value::VarChar(String::from("alice"))),
+                    )),
+                    BinaryOperator::And,
+                    Box::new(Expr::Binary(
+                        Box::new(Expr::Binary(
+                            column("doc"),
+                            BinaryOperator::JsonGet,
+                            value(Value::VarChar(String::from("n"))),
+                        )),
+                        BinaryOperator::Eq,
+                        value(Value::Json(String::from("1"))),
+                    )),
+                ))
+            )
+        );
+
+        // the display form parses back to the same tree
+        let QueryStatement::Select(select) = &statements[0] else {
+            unreachable!()
+        };
+        let SelectItem::Expr(expr, _) = &select.projections[0] else {
+            unreachable!()
+        };
+        for expr in [expr, select.cond.as_ref().unwrap()] {
+            assert_eq!(
+                Parser::new(Lexer::new(expr.to_string()))
+                    .parse_standalone_expr()
+                    .unwrap(),
+                *expr
+            );
+        }
+
+        let statements = parse(String::from("CREATE TABLE t (doc JSON);")).unwrap();
+        assert_eq!(
+            statements[0],
+            QueryStatement::CreateTable(
+                "t".to_string(),
+                vec![("doc".to_string(), DataType::Json)],
+                vec![]
+            )
+        );
+        assert_eq!(
+            parse(String::from("SELECT doc -> FROM t;")),
+            Err(ParseError::UnexpectedToken(Token::From))
+        );
+    }
+
+    #[test]
+    fn test_parse_where_is_null() {
+        let statements = parse(String::from(
+            "SELECT * FROM user WHERE name IS NULL OR id IS NOT NULL AND id <> NULL;",
+        ))
+        .unwrap();
+        let column = |name: &str| Box::new(Expr::Column(name.to_string()));
+        assert_eq!(
+            statements[0],
+            select(
+                String::from("user"),
+                vec![SelectItem::Wildcard],
+                Some(Expr::Binary(
+                    Box::new(Expr::IsNull(column("name"), false)),
+                    BinaryOperator::Or,
+                    Box::new(Expr::Binary(
+                        Box::new(Expr::IsNull(column("id"), true)),
+                        BinaryOperator::And,
+                        Box::new(Expr::Binary(
+                            column("id"),
+                            BinaryOperator::NotEq,
+                            Box::new(Expr::Value(Value::Null)),
+                        )),
+                    )),
+                ))
+            )
+        );
+
+        assert_eq!(
+            parse(String::from("SELECT * FROM user WHERE name IS 1;")),
+            Err(ParseError::UnexpectedToken(Token::Integer(1)))
+        );
+    }
+
+    #[test]
+    fn test_parse_select_order_by() {
+        let statements = parse(String::from(
+            "SELECT * FROM todo WHERE id > 1 ORDER BY user_id DESC, id LIMIT 10 OFFSET 20;",
+        ))
+        .unwrap();
+        assert_eq!(
+            statements[0],
+            QueryStatement::Select(SelectStatement {
+                from: TableRef::Table(String::from("todo"), None),
+                projections: vec![SelectItem::Wildcard],
+                cond: Some(Expr::Binary(
+                    Box::new(Expr::Column("id".to_string())),
+                    BinaryOperator::Gt,
+                    Box::new(Expr::Value(Value::Int(1))),
+                )),
+                group_by: vec![],
+                having: None,
+                order_by: vec![
+                    (Expr::Column("user_id".to_string()), Order::Desc),
+                    (Expr::Column("id".to_string()), Order::Asc),
+                ],
+                limit: Some(10),
+                offset: Some(20),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_select_limit() {
+        let statements = parse(String::from(
+            "SELECT id FROM todo LIMIT 5; SELECT id FROM todo ORDER BY id ASC OFFSET 5;",
+        ))
+        .unwrap();
+        assert_eq!(
+            statements,
+            vec![
+                QueryStatement::Select(SelectStatement {
+                    projections: vec![column("id")],
+                    limit: Some(5),
+                    ..select_from("todo")
+                }),
+                QueryStatement::Select(SelectStatement {
+                    projections: vec![column("id")],
+                    order_by: vec![(Expr::Column("id".to_string()), Order::Asc)],
+                    offset: Some(5),
+                    ..select_from("todo")
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_select_aggregate() {
+        let statements = parse(String::from(
+            "SELECT user_id, COUNT(*) AS todos, max(id) FROM todo GROUP BY user_id HAVING COUNT(id) > 1;",
+        ))
+        .unwrap();
+        assert_eq!(
+            statements[0],
+            QueryStatement::Select(SelectStatement {
+                projections: vec![
+                    column("user_id"),
+                    SelectItem::Expr(
+                        Expr::Aggregate(AggregateFunction::Count, None),
+                        Some("todos".to_string())
+                    ),
+                    SelectItem::Expr(
+                        Expr::Aggregate(
+                            AggregateFunction::Max,
+                            Some(Box::new(Expr::Column("id".to_string())))
+                        ),
+                        None
+                    ),
+                ],
+                group_by: vec![Expr::Column("user_id".to_string())],
+                having: Some(Expr::Binary(
+                    Box::new(Expr::Aggregate(
+                        AggregateFunction::Count,
+                        Some(Box::new(Expr::Column("id".to_string())))
+                    )),
+                    BinaryOperator::Gt,
+                    Box::new(Expr::Value(Value::Int(1))),
+                )),
+                ..select_from("todo")
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_select_join() {
+        let statements = parse(String::from(
+            "SELECT u.name, todo.title FROM user AS u JOIN todo ON u.id = todo.user_id LEFT OUTER JOIN tag t ON t.todo_id = todo.id CROSS JOIN a, b;",
+        ))
+        .unwrap();
+        let qualified_eq = |left: &str, right: &str| {
+            Some(Expr::Binary(
+                Box::new(Expr::Column(left.to_string())),
+                BinaryOperator::Eq,
+                Box::new(Expr::Column(right.to_string())),
+            ))
+        };
+        let table = |name: &str, alias: Option<&str>| {
+            Box::new(TableRef::Table(
+                name.to_string(),
+                alias.map(|alias| alias.to_string()),
+            ))
+        };
+        assert_eq!(
+            statements[0],
+            QueryStatement::Select(SelectStatement {
+                from: TableRef::Join(
+                    Box::new(TableRef::Join(
+                        Box::new(TableRef::Join(
+                            Box::new(TableRef::Join(
+                                table("user", Some("u")),
+                                JoinType::Inner,
+                                table("todo", None),
+                                qualified_eq("u.id", "todo.user_id"),
+                            )),
+                            JoinType::Left,
+                            table("tag", Some("t")),
+                            qualified_eq("t.todo_id", "todo.id"),
+                        )),
+                        JoinType::Cross,
+                        table("a", None),
+                        None,
+                    )),
+                    JoinType::Cross,
+                    table("b", None),
+                    None,
+                ),
+                projections: vec![column("u.name"), column("todo.title")],
+                ..select_from("")
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_join_error() {
+        let err = parse(String::from("SELECT * FROM a JOIN b;")).unwrap_err();
+        assert_eq!(err, ParseError::UnexpectedToken(Token::SemiColon));
+        let err = parse(String::from("SELECT * FROM a CROSS b;")).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::UnexpectedToken(Token::Ident(String::from("b")))
+        );
+    }
+
+    #[test]
+    fn test_parse_set_single() {
+        let statements =
+            parse(String::from("UPDATE user SET name = 'mike' WHERE id = 1;")).unwrap();
+        assert_eq!(statements.len(), 1);
+        assert_eq!(
+            statements[0],
+            QueryStatement::Update(
+                String::from("user"),
+                vec![("name".to_string(), Value::VarChar("mike".to_string()))],
+                Some(eq("id", Value::Int(1)))
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_set_multi() {
+        let statements = parse(String::from(
+            "UPDATE user SET foo = 1, bar = 999 WHERE name = 'mike';",
+        ))
+        .unwrap();
+        assert_eq!(statements.len(), 1);
+        assert_eq!(
+            statements[0],
+            QueryStatement::Update(
+                String::from("user"),
+                vec![
+                    ("foo".to_string(), Value::Int(1)),
+                    ("bar".to_string(), Value::Int(999))
+                ],
+                Some(eq("name", Value::VarChar("mike".to_string())))
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_insert() {
+        let statements = parse(String::from(
+            "INSERT INTO user (id, name) VALUES (1, 'mike'), (2, 'bob');",
+        ))
+        .unwrap();
+        assert_eq!(statements.len(), 1);
+        assert_eq!(
+            statements[0],
+            QueryStatement::Insert(
+                String::from("user"),
+                vec!["id".to_string(), "name".to_string()],
+                vec![
+                    vec![Value::Int(1), Value::VarChar("mike".to_string())],
+                    vec![Value::Int(2), Value::VarChar("bob".to_string())],
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_insert_without_columns() {
+        let statements = parse(String::from("INSERT INTO user VALUES (1, 'mike');")).unwrap();
+        assert_eq!(statements.len(), 1);
+        assert_eq!(
+            statements[0],
+            QueryStatement::Insert(
+                String::from("user"),
+                vec![],
+                vec![vec![Value::Int(1), Value::VarChar("mike".to_string())]]
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_delete() {
+        {
+            let statements = parse(String::from("DELETE FROM user;")).unwrap();
+            assert_eq!(statements.len(), 1);
+            assert_eq!(
+                statements[0],
+                QueryStatement::Delete(String::from("user"), None)
+            );
+        }
+        {
+            let statements = parse(String::from("DELETE FROM user WHERE name = 'mike';")).unwrap();
+            assert_eq!(statements.len(), 1);
+            assert_eq!(
+                statements[0],
+                QueryStatement::Delete(
+                    String::from("user"),
+                    Some(eq("name", Value::VarChar("mike".to_string())))
+                )
+            );
+        }
+        // a statement must end with ; or the input
+        assert_eq!(
+            parse(String::from("DELETE FROM t oops")),
+            Err(ParseError::UnexpectedToken(Token::Ident(String::from(
+                "oops"
+            ))))
+        );
+        assert_eq!(
+            parse(String::from("DELETE FROM t; DELETE FROM u"))
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        {
+            let err = parse(String::from("SELECT;")).unwrap_err();
+            assert_eq!(err, ParseError::UnexpectedToken(Token::SemiColon));
+        }
+        {
+            let err = parse(String::from("SELECT 1;")).unwrap_err();
+            assert_eq!(err, ParseError::UnexpectedToken(Token::SemiColon));
+        }
+        {
+            let err = parse(String::from("SELECT foo(id) FROM user;")).unwrap_err();
+            assert_eq!(err, ParseError::UnknownFunction(String::from("foo")));
+        }
+        {
+            let err = parse(String::from("CREATE TABLE t (name VARCHAR(65536));")).unwrap_err();
+            assert_eq!(err, ParseError::UnexpectedToken(Token::Integer(65536)));
+        }
+        {
+            let err = parse(String::from("CREATE TABLE t (name VARCHAR(0));")).unwrap_err();
+            assert_eq!(err, ParseError::UnexpectedToken(Token::Integer(0)));
+        }
+    }
+
+    #[test]
+    fn test_parse_create_table() {
+        let statements = parse(String::from(
+            "CREATE TABLE users (id INT, name VARCHAR(255));",
+        ))
+        .unwrap();
+        assert_eq!(statements.len(), 1);
+        assert_eq!(
+            statements[0],
+            QueryStatement::CreateTable(
+                "users".to_string(),
+                vec![
+                    ("id".to_string(), DataType::Int),
+                    ("name".to_string(), DataType::VarChar(255)),
+                ],
+                vec![]
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_create_table_numeric_types() {
+        let statements = parse(String::from(
+            "CREATE TABLE item (id BIGINT, active BOOLEAN, weight DOUBLE, price DECIMAL(6, 2), qty NUMERIC(4), total DECIMAL);",
+        ))
+        .unwrap();
+        assert_eq!(
+            statements[0],
+            QueryStatement::CreateTable(
+                "item".to_string(),
+                vec![
+                    ("id".to_string(), DataType::BigInt),
+                    ("active".to_string(), DataType::Boolean),
+                    ("weight".to_string(), DataType::Double),
+                    ("price".to_string(), DataType::Decimal(6, 2)),
+                    ("qty".to_string(), DataType::Decimal(4, 0)),
+                    ("total".to_string(), DataType::Decimal(10, 0)),
+                ],
+                vec![]
+            )
+        );
+
+        let statements = parse(String::from(
+            "CREATE TABLE event (day DATE, at TIME, created TIMESTAMP, duration INTERVAL);",
+        ))
+        .unwrap();
+        assert_eq!(
+            statements[0],
+            QueryStatement::CreateTable(
+                "event".to_string(),
+                vec![
+                    ("day".to_string(), DataType::Date),
+                    ("at".to_string(), DataType::Time),
+                    ("created".to_string(), DataType::Timestamp),
+                    ("duration".to_string(), DataType::Interval),
+                ],
+                vec![]
+            )
+        );
+
+        let statements = parse(String::from(
+            "CREATE TABLE file (name TEXT, data BLOB, thumbnail BYTEA); INSERT INTO file VALUES ('a', X'DEAD', x'');",
+        ))
+        .unwrap();
+        assert_eq!(
+            statements,
+            vec![
+                QueryStatement::CreateTable(
+                    "file".to_string(),
+                    vec![
+                        ("name".to_string(), DataType::Text),
+                        ("data".to_string(), DataType::Blob),
+                        ("thumbnail".to_string(), DataType::Blob),
+                    ],
+                    vec![]
+                ),
+                QueryStatement::Insert(
+                    "file".to_string(),
+                    vec![],
+                    vec![vec![
+                        Value::VarChar("a".to_string()),
+                        Value::Blob(vec![0xde, 0xad]),
+                        Value::Blob(vec![]),
+                    ]]
+                ),
+            ]
+        );
+        assert_eq!(
+            parse(String::from("INSERT INTO file VALUES ('a', X'ABC', x'');")),
+            Err(ParseError::InvalidLiteral(String::from(
+                "hex literal must have an even number of digits"
+            )))
+        );
+
+        for input in [
+            "CREATE TABLE t (a DECIMAL(0));",
+            "CREATE TABLE t (a DECIMAL(39));",
+            "CREATE TABLE t (a DECIMAL(2, 3));",
+        ] {
+            assert!(parse(String::from(input)).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_create_table_constraints() {
+        let statements = parse(String::from(
+            "CREATE TABLE todo (id INT PRIMARY KEY, user_id INT NOT NULL CHECK (user_id > 0), title VARCHAR(10) NULL DEFAULT 'untitled', UNIQUE (user_id, title), CHECK (id <> user_id));",
+        ))
+        .unwrap();
+        assert_eq!(
+            statements[0],
+            QueryStatement::CreateTable(
+                "todo".to_string(),
+                vec![
+                    ("id".to_string(), DataType::Int),
+                    ("user_id".to_string(), DataType::Int),
+                    ("title".to_string(), DataType::VarChar(10)),
+                ],
+                vec![
+                    Constraint::PrimaryKey(vec!["id".to_string()]),
+                    Constraint::NotNull("user_id".to_string()),
+                    Constraint::Check(Expr::Binary(
+                        Box::new(Expr::Column("user_id".to_string())),
+                        BinaryOperator::Gt,
+                        Box::new(Expr::Value(Value::Int(0))),
+                    )),
+                    Constraint::Default(
+                        "title".to_string(),
+                        Value::VarChar("untitled".to_string())
+                    ),
+                    Constraint::Unique(vec!["user_id".to_string(), "title".to_string()]),
+                    Constraint::Check(Expr::Binary(
+                        Box::new(Expr::Column("id".to_string())),
+                        BinaryOperator::NotEq,
+                        Box::new(Expr::Column("user_id".to_string())),
+                    )),
+                ]
+            )
+        );
+
+        assert_eq!(
+            parse(String::from("CREATE TABLE t (id INT PRIMARY);")),
+            Err(ParseError::UnexpectedToken(Token::RParen))
+        );
+        assert_eq!(
+            parse(String::from("CREATE TABLE t (id INT, UNIQUE id);")),
+            Err(ParseError::UnexpectedToken(Token::Ident("id".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_parse_create_table_foreign_key() {
+        let statements = parse(String::from(
+            "CREATE TABLE todo (id INT, user_id INT REFERENCES user ON DELETE CASCADE, owner VARCHAR(10), FOREIGN KEY (user_id, owner) REFERENCES member (id, name) ON UPDATE SET NULL ON DELETE NO ACTION);",
+        ))
+        .unwrap();
+        assert_eq!(
+            statements[0],
+            QueryStatement::CreateTable(
+                "todo".to_string(),
+                vec![
+                    ("id".to_string(), DataType::Int),
+                    ("user_id".to_string(), DataType::Int),
+                    ("owner".to_string(), DataType::VarChar(10)),
+                ],
+                vec![
+                    Constraint::ForeignKey(ForeignKey {
+                        columns: vec!["user_id".to_string()],
+                        ref_table: "user".to_string(),
+                        ref_columns: vec![],
+                        on_delete: ReferentialAction::Cascade,
+                        on_update: ReferentialAction::Restrict,
+                    }),
+                    Constraint::ForeignKey(ForeignKey {
+                        columns: vec!["user_id".to_string(), "owner".to_string()],
+                        ref_table: "member".to_string(),
+                        ref_columns: vec!["id".to_string(), "name".to_string()],
+                        on_delete: ReferentialAction::Restrict,
+                        on_update: ReferentialAction::SetNull,
+                    }),
+                ]
+            )
+        );
+
+        assert_eq!(
+            parse(String::from(
+                "CREATE TABLE t (id INT REFERENCES user ON DELETE SET DEFAULT ON UPDATE RESTRICT);"
+            ))
+            .unwrap()[0],
+            QueryStatement::CreateTable(
+                "t".to_string(),
+                vec![("id".to_string(), DataType::Int)],
+                vec![Constraint::ForeignKey(ForeignKey {
+                    columns: vec!["id".to_string()],
+                    ref_table: "user".to_string(),
+                    ref_columns: vec![],
+                    on_delete: ReferentialAction::SetDefault,
+                    on_update: ReferentialAction::Restrict,
+                })]
+            )
+        );
+        assert_eq!(
+            parse(String::from(
+                "CREATE TABLE t (id INT REFERENCES user ON DELETE SET 1);"
+            )),
+            Err(ParseError::UnexpectedToken(Token::Integer(1)))
+        );
+
+        // the referential actions are names outside of the clause
+        assert_eq!(
+            parse(String::from(
+                "CREATE TABLE c (action INT, no INT REFERENCES t ON DELETE NO ACTION, cascade INT, restrict INT);"
+            ))
+            .unwrap()[0],
+            QueryStatement::CreateTable(
+                "c".to_string(),
+                vec![
+                    ("action".to_string(), DataType::Int),
+                    ("no".to_string(), DataType::Int),
+                    ("cascade".to_string(), DataType::Int),
+                    ("restrict".to_string(), DataType::Int),
+                ],
+                vec![Constraint::ForeignKey(ForeignKey {
+                    columns: vec!["no".to_string()],
+                    ref_table: "t".to_string(),
+                    ref_columns: vec![],
+                    on_delete: ReferentialAction::Restrict,
+                    on_update: ReferentialAction::Restrict,
+                })]
+            )
+        );
+        assert_eq!(
+            parse(String::from("SELECT no, action FROM t cascade;")).unwrap()[0],
+            QueryStatement::Select(SelectStatement {
+                from: TableRef::Table(String::from("t"), Some(String::from("cascade"))),
+                projections: vec![column("no"), column("action")],
+                ..select_from("t")
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_alter_table() {
+        let statements = parse(String::from(
+            "ALTER TABLE user ADD COLUMN age INT; ALTER TABLE user ADD nick VARCHAR(8); ALTER TABLE user DROP COLUMN age; ALTER TABLE user RENAME COLUMN nick TO nickname; ALTER TABLE user RENAME TO member;",
+        ))
+        .unwrap();
+        assert_eq!(
+            statements,
+            vec![
+                QueryStatement::AlterTable(
+                    "user".to_string(),
+                    AlterTableAction::AddColumn("age".to_string(), DataType::Int)
+                ),
+                QueryStatement::AlterTable(
+                    "user".to_string(),
+                    AlterTableAction::AddColumn("nick".to_string(), DataType::VarChar(8))
+                ),
+                QueryStatement::AlterTable(
+                    "user".to_string(),
+                    AlterTableAction::DropColumn("age".to_string())
+                ),
+                QueryStatement::AlterTable(
+                    "user".to_string(),
+                    AlterTableAction::RenameColumn("nick".to_string(), "nickname".to_string())
+                ),
+                QueryStatement::AlterTable(
+                    "user".to_string(),
+                    AlterTableAction::RenameTo("member".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_drop_table() {
+        {
+            let statements = parse(String::from("DROP TABLE user;")).unwrap();
+            assert_eq!(statements.len(), 1);
+            assert_eq!(
+                statements[0],
+                QueryStatement::DropTable("user".to_string(), false)
+            );
+        }
+        {
+            let statements = parse(String::from("DROP TABLE IF EXISTS user;")).unwrap();
+            assert_eq!(statements.len(), 1);
+            assert_eq!(
+                statements[0],
+                QueryStatement::DropTable("user".to_string(), true)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_truncate() {
+        let statements = parse(String::from("TRUNCATE TABLE user; TRUNCATE todo;")).unwrap();
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0], QueryStatement::Truncate("user".to_string()));
+        assert_eq!(statements[1], QueryStatement::Truncate("todo".to_string()));
     }
 }
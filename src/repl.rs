@@ -1,14 +1,25 @@
 use std::io::Write;
 
-use crate::{
-    core::Executer,
+use ubdb::{
+    core::{
+        result::{QueryResult, ResultSet},
+        table::Value,
+        Executer,
+    },
+    error::Error,
     query::{lex::Lexer, parser::Parser},
 };
 
 const STORAGE_PATH: &str = "db";
 
 pub fn start() {
-    let mut executer = Executer::new(STORAGE_PATH.to_string());
+    let mut executer = match Executer::new(STORAGE_PATH.to_string()) {
+        Ok(executer) => executer,
+        Err(err) => {
+            println!("ERROR: {}", err);
+            return;
+        }
+    };
 
     loop {
         // prompt
@@ -17,7 +28,15 @@ pub fn start() {
 
         // receive query from stdin
         let mut query_raw = String::new();
-        std::io::stdin().read_line(&mut query_raw).unwrap();
+        match std::io::stdin().read_line(&mut query_raw) {
+            // end of input
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(err) => {
+                println!("ERROR: {}", Error::from(err));
+                break;
+            }
+        }
 
         // parsing
         let lexer = Lexer::new(query_raw);
@@ -27,15 +46,72 @@ pub fn start() {
         // execution
         match query {
             Ok(query_stmts) => {
-                let is_continue = executer.execute(query_stmts);
-                if !is_continue {
-                    break;
+                for stmt in query_stmts {
+                    match executer.execute(stmt) {
+                        Ok(QueryResult::Exit) => {
+                            println!("bye!");
+                            return;
+                        }
+                        Ok(result) => print_result(&result),
+                        Err(err) => {
+                            // the rest of the line is not executed
+                            println!("ERROR: {}", err);
+                            break;
+                        }
+                    }
                 }
             }
             Err(err) => {
-                println!("{}", err);
+                println!("ERROR: {}", Error::from(err));
                 continue;
             }
         };
     }
 }
+
+fn print_result(result: &QueryResult) {
+    match result {
+        QueryResult::Rows(result_set) => print_rows(result_set),
+        QueryResult::Affected(affected) => println!("Query OK, {} row(s) affected", affected),
+        QueryResult::Message(message) => println!("{}", message),
+        QueryResult::Exit => {}
+    }
+}
+
+fn print_rows(result_set: &ResultSet) {
+    if result_set.rows.is_empty() {
+        println!("Empty set");
+        return;
+    }
+    println!("{}", result_set.column_names().join(", "));
+    for row in result_set.rows.iter() {
+        let values = row
+            .values
+            .iter()
+            .map(format_value)
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("{}", values);
+    }
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Boolean(v) => v.to_string(),
+        Value::Int(v) => v.to_string(),
+        Value::BigInt(v) => v.to_string(),
+        Value::Double(v) => v.to_string(),
+        Value::Decimal(v) => v.to_string(),
+        Value::VarChar(v) => v.clone(),
+        Value::Date(v) => v.to_string(),
+        Value::Time(v) => v.to_string(),
+        Value::Timestamp(v) => v.to_string(),
+        Value::Interval(v) => v.to_string(),
+        Value::Json(v) => v.to_string(),
+        Value::Blob(v) => format!(
+            "\\x{}",
+            v.iter().map(|b| format!("{:02x}", b)).collect::<String>()
+        ),
+        Value::Null => String::from("NULL"),
+    }
+}